@@ -1,6 +1,8 @@
 use crate::constants::ONE_EPOCH;
 use crate::helpers::ntoy;
-use crate::legacy_types::{LegacyNearxPoolStateResponse, LegacyRolesResponse};
+use crate::legacy_types::{
+    LegacyAccountResponse, LegacyNearxPoolStateResponse, LegacyRolesResponse,
+};
 use near_sdk::json_types::{U128, U64};
 use near_units::parse_near;
 use near_x::constants::NUM_EPOCHS_TO_UNLOCK;
@@ -117,7 +119,7 @@ impl IntegrationTestContext<Sandbox> {
                 .call(&worker, nearx_contract.id(), "storage_deposit")
                 .max_gas()
                 .args_json(json!({}))?
-                .deposit(3000000000000000000000)
+                .deposit(4000000000000000000000)
                 .transact()
                 .await?;
             let res = nearx_owner
@@ -421,7 +423,7 @@ impl IntegrationTestContext<Sandbox> {
         user.call(&self.worker, self.nearx_contract.id(), "storage_deposit")
             .max_gas()
             .args_json(json!({}))?
-            .deposit(3000000000000000000000)
+            .deposit(4000000000000000000000)
             .transact()
             .await?;
         user.call(&self.worker, self.nearx_contract.id(), "storage_deposit")
@@ -429,7 +431,7 @@ impl IntegrationTestContext<Sandbox> {
             .args_json(
                 json!({"account_id": receiving_contract.id().clone(), "registration_only": true}),
             )?
-            .deposit(3000000000000000000000)
+            .deposit(4000000000000000000000)
             .transact()
             .await?;
 
@@ -449,7 +451,7 @@ impl IntegrationTestContext<Sandbox> {
         user.call(&self.worker, self.nearx_contract.id(), "storage_deposit")
             .max_gas()
             .args_json(json!({}))?
-            .deposit(3000000000000000000000)
+            .deposit(4000000000000000000000)
             .transact()
             .await?;
 
@@ -469,7 +471,7 @@ impl IntegrationTestContext<Sandbox> {
         user.call(&self.worker, self.nearx_contract.id(), "storage_deposit")
             .max_gas()
             .args_json(json!({}))?
-            .deposit(3000000000000000000000)
+            .deposit(4000000000000000000000)
             .transact()
             .await?;
 
@@ -634,14 +636,14 @@ impl IntegrationTestContext<Sandbox> {
             .call(&self.worker, self.nearx_contract.id(), "storage_deposit")
             .max_gas()
             .args_json(json!({}))?
-            .deposit(3000000000000000000000)
+            .deposit(4000000000000000000000)
             .transact()
             .await?;
         sender
             .call(&self.worker, self.nearx_contract.id(), "storage_deposit")
             .max_gas()
             .args_json(json!({"account_id": receiver.id().clone(), "registration_only": true}))?
-            .deposit(3000000000000000000000)
+            .deposit(4000000000000000000000)
             .transact()
             .await?;
         sender
@@ -829,6 +831,19 @@ impl IntegrationTestContext<Sandbox> {
             .json::<AccountResponse>()
     }
 
+    /// get_user_account of a contract deployed before accounts kept a queue of unstake requests
+    pub async fn get_legacy_contract_user_account(
+        &self,
+        user: AccountId,
+    ) -> anyhow::Result<LegacyAccountResponse> {
+        self.nearx_contract
+            .call(&self.worker, "get_user_account")
+            .args_json(json!({ "account_id": user }))?
+            .view()
+            .await?
+            .json::<LegacyAccountResponse>()
+    }
+
     pub async fn get_legacy_user_account(
         &self,
         user: AccountId,
@@ -61,6 +61,16 @@ pub struct LegacyAccount {
     pub withdrawable_epoch_height: EpochHeight,
 }
 
+/// get_user_account response without the list of unstake requests
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LegacyAccountResponse {
+    pub account_id: AccountId,
+    pub unstaked_balance: U128,
+    pub staked_balance: U128,
+    pub withdrawable_epoch: U64,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct LegacyRolesResponse {
@@ -6,6 +6,7 @@ mod legacy_types;
 use crate::constants::ONE_EPOCH;
 use crate::helpers::{abs_diff_eq, ntoy};
 use context::IntegrationTestContext;
+use legacy_types::LegacyAccountResponse;
 use near_sdk::json_types::{U128, U64};
 use near_sdk::{AccountId, ONE_NEAR};
 use near_units::*;
@@ -13,7 +14,8 @@ use near_x::constants::gas::ON_STAKE_POOL_WITHDRAW_ALL_CB;
 use near_x::constants::NUM_EPOCHS_TO_UNLOCK;
 use near_x::state::{
     AccountResponse, Fraction, HumanReadableAccount, LegacyValidatorInfoResponse,
-    NearxPoolStateResponse, OperationsControlUpdateRequest, ValidatorInfoResponse, ValidatorType,
+    NearxPoolStateResponse, OperationsControlUpdateRequest, UnstakeRequestResponse,
+    ValidatorInfoResponse, ValidatorType,
 };
 use serde_json::json;
 use std::str::FromStr;
@@ -363,9 +365,15 @@ async fn test_contract_upgrade() -> anyhow::Result<()> {
         .await
         .is_err());
 
-    let user1_account = context.get_user_account(context.user1.id().clone()).await?;
-    let user2_account = context.get_user_account(context.user2.id().clone()).await?;
-    let user3_account = context.get_user_account(context.user3.id().clone()).await?;
+    let user1_account = context
+        .get_legacy_contract_user_account(context.user1.id().clone())
+        .await?;
+    let user2_account = context
+        .get_legacy_contract_user_account(context.user2.id().clone())
+        .await?;
+    let user3_account = context
+        .get_legacy_contract_user_account(context.user3.id().clone())
+        .await?;
 
     println!("user1_account is {:?}", user1_account);
     println!("user2_account is {:?}", user2_account);
@@ -373,7 +381,7 @@ async fn test_contract_upgrade() -> anyhow::Result<()> {
 
     assert_eq!(
         user1_account,
-        AccountResponse {
+        LegacyAccountResponse {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
@@ -382,7 +390,7 @@ async fn test_contract_upgrade() -> anyhow::Result<()> {
     );
     assert_eq!(
         user2_account,
-        AccountResponse {
+        LegacyAccountResponse {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
@@ -391,7 +399,7 @@ async fn test_contract_upgrade() -> anyhow::Result<()> {
     );
     assert_eq!(
         user3_account,
-        AccountResponse {
+        LegacyAccountResponse {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
@@ -474,11 +482,13 @@ async fn test_contract_upgrade() -> anyhow::Result<()> {
 
     context.unstake(&context.user1, U128(ntoy(5))).await?;
 
-    let user1_account = context.get_user_account(context.user1.id().clone()).await?;
+    let user1_account = context
+        .get_legacy_contract_user_account(context.user1.id().clone())
+        .await?;
     println!("user1_account is {:?}", user1_account);
     assert_eq!(
         user1_account,
-        AccountResponse {
+        LegacyAccountResponse {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(5)),
@@ -551,11 +561,13 @@ async fn test_contract_upgrade() -> anyhow::Result<()> {
     );
 
     context.unstake(&context.user2, U128(ntoy(5))).await?;
-    let user2_account = context.get_user_account(context.user2.id().clone()).await?;
+    let user2_account = context
+        .get_legacy_contract_user_account(context.user2.id().clone())
+        .await?;
     println!("user2_account is {:?}", user2_account);
     assert_eq!(
         user2_account,
-        AccountResponse {
+        LegacyAccountResponse {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(5)),
@@ -727,6 +739,8 @@ async fn test_contract_upgrade() -> anyhow::Result<()> {
             sync_validator_balance_paused: None,
             ft_transfer_paused: None,
             ft_transfer_call_paused: None,
+            instant_unstake_paused: None,
+            rebalance_epoch_paused: None,
         })
         .await?;
 
@@ -764,6 +778,8 @@ async fn test_all_epochs_paused() -> anyhow::Result<()> {
             sync_validator_balance_paused: Some(true),
             ft_transfer_paused: Some(true),
             ft_transfer_call_paused: Some(true),
+            instant_unstake_paused: None,
+            rebalance_epoch_paused: None,
         })
         .await?;
 
@@ -2024,7 +2040,8 @@ async fn test_stake_pool_failures_deposit() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -2033,7 +2050,8 @@ async fn test_stake_pool_failures_deposit() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -2042,7 +2060,8 @@ async fn test_stake_pool_failures_deposit() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -2149,7 +2168,8 @@ async fn test_stake_pool_failures_unstake() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -2158,7 +2178,8 @@ async fn test_stake_pool_failures_unstake() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -2167,7 +2188,8 @@ async fn test_stake_pool_failures_unstake() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -2259,7 +2281,11 @@ async fn test_stake_pool_failures_unstake() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -2268,7 +2294,8 @@ async fn test_stake_pool_failures_unstake() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -2277,7 +2304,8 @@ async fn test_stake_pool_failures_unstake() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -2387,7 +2415,8 @@ async fn test_stake_pool_failures_withdraw() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -2396,7 +2425,8 @@ async fn test_stake_pool_failures_withdraw() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -2405,7 +2435,8 @@ async fn test_stake_pool_failures_withdraw() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -2493,7 +2524,11 @@ async fn test_stake_pool_failures_withdraw() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -2502,7 +2537,8 @@ async fn test_stake_pool_failures_withdraw() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -2511,7 +2547,8 @@ async fn test_stake_pool_failures_withdraw() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -2683,7 +2720,11 @@ async fn test_eight_epochs_user_flows() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -2692,7 +2733,11 @@ async fn test_eight_epochs_user_flows() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -2701,7 +2746,8 @@ async fn test_eight_epochs_user_flows() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -2821,7 +2867,17 @@ async fn test_eight_epochs_user_flows() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(10)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(5)),
+                    withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(5)),
+                    withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+            ],
         }
     );
     assert_eq!(
@@ -2830,7 +2886,11 @@ async fn test_eight_epochs_user_flows() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -2839,7 +2899,8 @@ async fn test_eight_epochs_user_flows() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -2963,7 +3024,21 @@ async fn test_eight_epochs_user_flows() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(15000000000000000000000001),
             staked_balance: U128(3865648059777927791615260),
-            withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(5)),
+                    withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(5)),
+                    withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+                UnstakeRequestResponse {
+                    amount: U128(5000000000000000000000001),
+                    withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+            ],
         }
     );
     assert_eq!(
@@ -2972,7 +3047,11 @@ async fn test_eight_epochs_user_flows() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(17731296119555855583230522),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -2981,7 +3060,11 @@ async fn test_eight_epochs_user_flows() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(5000000000000000000000001),
             staked_balance: U128(12731296119555855583230521),
-            withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(5000000000000000000000001),
+                withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -3203,7 +3286,8 @@ async fn test_unstake_with_only_private_validators() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(15)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -3212,7 +3296,8 @@ async fn test_unstake_with_only_private_validators() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -3221,7 +3306,8 @@ async fn test_unstake_with_only_private_validators() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(8)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -3327,7 +3413,11 @@ async fn test_unstake_with_only_private_validators() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(12)),
             staked_balance: U128(ntoy(3)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(12)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -3336,7 +3426,11 @@ async fn test_unstake_with_only_private_validators() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(7)),
             staked_balance: U128(ntoy(3)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(7)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -3345,7 +3439,11 @@ async fn test_unstake_with_only_private_validators() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(1)),
             staked_balance: U128(ntoy(7)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(1)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
 
@@ -3590,7 +3688,8 @@ async fn test_unstake_with_private_validators() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(15)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -3599,7 +3698,8 @@ async fn test_unstake_with_private_validators() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -3608,7 +3708,8 @@ async fn test_unstake_with_private_validators() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -3711,7 +3812,11 @@ async fn test_unstake_with_private_validators() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(12)),
             staked_balance: U128(ntoy(3)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(12)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -3720,7 +3825,11 @@ async fn test_unstake_with_private_validators() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(7)),
             staked_balance: U128(ntoy(3)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(7)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -3729,7 +3838,11 @@ async fn test_unstake_with_private_validators() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(1)),
             staked_balance: U128(ntoy(1)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(1)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
 
@@ -3971,7 +4084,8 @@ async fn test_unstake_with_private_validators_2() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(15)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -3980,7 +4094,8 @@ async fn test_unstake_with_private_validators_2() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -3989,7 +4104,8 @@ async fn test_unstake_with_private_validators_2() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -4095,7 +4211,11 @@ async fn test_unstake_with_private_validators_2() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(15)),
             staked_balance: U128(ntoy(0)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(15)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -4104,7 +4224,11 @@ async fn test_unstake_with_private_validators_2() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(7)),
             staked_balance: U128(ntoy(3)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(7)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -4113,7 +4237,11 @@ async fn test_unstake_with_private_validators_2() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(1)),
             staked_balance: U128(ntoy(1)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(1)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
 
@@ -4393,7 +4521,8 @@ async fn test_bank_run_with_private_validators() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(15)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -4402,7 +4531,8 @@ async fn test_bank_run_with_private_validators() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -4411,7 +4541,8 @@ async fn test_bank_run_with_private_validators() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -4507,7 +4638,11 @@ async fn test_bank_run_with_private_validators() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(15)),
             staked_balance: U128(ntoy(0)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(15)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -4516,7 +4651,11 @@ async fn test_bank_run_with_private_validators() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(10)),
             staked_balance: U128(ntoy(0)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(10)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -4525,7 +4664,11 @@ async fn test_bank_run_with_private_validators() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(2)),
             staked_balance: U128(ntoy(0)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(2)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
 
@@ -4727,7 +4870,8 @@ async fn test_bank_run() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -4736,7 +4880,8 @@ async fn test_bank_run() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -4745,7 +4890,8 @@ async fn test_bank_run() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -4834,7 +4980,11 @@ async fn test_bank_run() -> anyhow::Result<()> {
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(10)),
             staked_balance: U128(ntoy(0)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(10)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -4843,7 +4993,11 @@ async fn test_bank_run() -> anyhow::Result<()> {
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(0)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -4852,7 +5006,11 @@ async fn test_bank_run() -> anyhow::Result<()> {
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(2)),
             staked_balance: U128(ntoy(0)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(2)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
 
@@ -5074,7 +5232,11 @@ async fn test_user_deposit_unstake_autcompounding_withdraw_with_grouped_epoch()
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(1)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -5083,7 +5245,11 @@ async fn test_user_deposit_unstake_autcompounding_withdraw_with_grouped_epoch()
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(1)),
             staked_balance: U128(ntoy(4)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(1)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -5092,7 +5258,11 @@ async fn test_user_deposit_unstake_autcompounding_withdraw_with_grouped_epoch()
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(4)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(4)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -5186,7 +5356,11 @@ async fn test_user_deposit_unstake_autcompounding_withdraw_with_grouped_epoch()
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(1)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -5195,7 +5369,17 @@ async fn test_user_deposit_unstake_autcompounding_withdraw_with_grouped_epoch()
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(7)),
             staked_balance: U128(ntoy(3)),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(1)),
+                    withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(6)),
+                    withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+            ],
         }
     );
     assert_eq!(
@@ -5204,7 +5388,11 @@ async fn test_user_deposit_unstake_autcompounding_withdraw_with_grouped_epoch()
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(4)),
             staked_balance: U128(ntoy(2)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(4)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -5307,7 +5495,11 @@ async fn test_user_deposit_unstake_autcompounding_withdraw_with_grouped_epoch()
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(12)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -5316,7 +5508,17 @@ async fn test_user_deposit_unstake_autcompounding_withdraw_with_grouped_epoch()
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(7)),
             staked_balance: U128(ntoy(11)),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(1)),
+                    withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(6)),
+                    withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+            ],
         }
     );
     assert_eq!(
@@ -5325,7 +5527,11 @@ async fn test_user_deposit_unstake_autcompounding_withdraw_with_grouped_epoch()
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(4)),
             staked_balance: U128(ntoy(7)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(4)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -6381,7 +6587,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(15)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -6390,7 +6600,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(15)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -6399,7 +6613,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(9)),
             staked_balance: U128(ntoy(11)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(9)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -6555,7 +6773,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(25)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -6564,7 +6786,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(25)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -6573,7 +6799,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(9)),
             staked_balance: U128(ntoy(21)),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(9)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -6659,7 +6889,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(30813953488372093023255813),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -6668,7 +6902,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(30813953488372093023255813),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -6677,7 +6915,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(ntoy(9)),
             staked_balance: U128(25883720930232558139534883),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(9)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -6692,7 +6934,17 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(14000000000000000000000001),
             staked_balance: U128(20883720930232558139534883),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(9)),
+                    withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+                UnstakeRequestResponse {
+                    amount: U128(5000000000000000000000001),
+                    withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+                },
+            ],
         }
     );
 
@@ -6982,7 +7234,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(33864839972369329956251439),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -6991,7 +7247,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(33864839972369329956251439),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -7000,7 +7260,17 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(14000000000000000000000001),
             staked_balance: U128(22951416071839742113746257),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(9)),
+                    withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+                UnstakeRequestResponse {
+                    amount: U128(5000000000000000000000001),
+                    withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+                },
+            ],
         }
     );
 
@@ -7086,7 +7356,17 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(6000000000000000000000001),
             staked_balance: U128(32864839972369329956251438),
-            withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![
+                UnstakeRequestResponse {
+                    amount: U128(ntoy(5)),
+                    withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+                UnstakeRequestResponse {
+                    amount: U128(1000000000000000000000001),
+                    withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+            ],
         }
     );
     assert_eq!(
@@ -7095,7 +7375,8 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(33864839972369329956251438),
-            withdrawable_epoch: U64(current_epoch_1.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -7104,7 +7385,11 @@ async fn test_user_stake_autocompound_unstake_withdraw_flows_across_epochs() ->
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(5000000000000000000000001),
             staked_balance: U128(17951416071839742113746256),
-            withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(5000000000000000000000001),
+                withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -7163,7 +7448,11 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch_2() -> anyhow::Res
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(13)),
             staked_balance: U128(ntoy(7)),
-            withdrawable_epoch: user1_account.withdrawable_epoch
+            withdrawable_epoch: user1_account.withdrawable_epoch,
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(13)),
+                withdrawable_epoch: user1_account.withdrawable_epoch,
+            }],
         }
     );
     assert_eq!(
@@ -7172,7 +7461,11 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch_2() -> anyhow::Res
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(10)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: user2_account.withdrawable_epoch
+            withdrawable_epoch: user2_account.withdrawable_epoch,
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(10)),
+                withdrawable_epoch: user2_account.withdrawable_epoch,
+            }],
         }
     );
     assert_eq!(
@@ -7181,7 +7474,11 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch_2() -> anyhow::Res
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: user3_account.withdrawable_epoch
+            withdrawable_epoch: user3_account.withdrawable_epoch,
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: user3_account.withdrawable_epoch,
+            }],
         }
     );
 
@@ -7506,7 +7803,8 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch_2() -> anyhow::Res
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(7)),
-            withdrawable_epoch: user1_account.withdrawable_epoch
+            withdrawable_epoch: user1_account.withdrawable_epoch,
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -7515,7 +7813,8 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch_2() -> anyhow::Res
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: user2_account.withdrawable_epoch
+            withdrawable_epoch: user2_account.withdrawable_epoch,
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -7524,7 +7823,8 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch_2() -> anyhow::Res
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: user3_account.withdrawable_epoch
+            withdrawable_epoch: user3_account.withdrawable_epoch,
+            unstake_requests: vec![],
         }
     );
 
@@ -7545,7 +7845,8 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch_2() -> anyhow::Res
             account_id: user1_account.account_id.clone(),
             unstaked_balance: U128(0),
             staked_balance: U128(ntoy(17)),
-            withdrawable_epoch: user1_account.withdrawable_epoch
+            withdrawable_epoch: user1_account.withdrawable_epoch,
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -7554,7 +7855,8 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch_2() -> anyhow::Res
             account_id: user2_account.account_id.clone(),
             unstaked_balance: U128(0),
             staked_balance: U128(ntoy(20)),
-            withdrawable_epoch: user2_account.withdrawable_epoch
+            withdrawable_epoch: user2_account.withdrawable_epoch,
+            unstake_requests: vec![],
         }
     );
 
@@ -7564,7 +7866,8 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch_2() -> anyhow::Res
             account_id: user3_account.account_id.clone(),
             unstaked_balance: U128(0),
             staked_balance: U128(ntoy(15)),
-            withdrawable_epoch: user3_account.withdrawable_epoch
+            withdrawable_epoch: user3_account.withdrawable_epoch,
+            unstake_requests: vec![],
         }
     );
 
@@ -7733,7 +8036,8 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -7742,7 +8046,8 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -7751,7 +8056,8 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -7876,7 +8182,8 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(11)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -7885,7 +8192,8 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(11)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -7894,7 +8202,8 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(11)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -7914,7 +8223,11 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(5000000000000000000000001),
             staked_balance: U128(5999999999999999999999999),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(5000000000000000000000001),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -7923,7 +8236,11 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(5000000000000000000000001),
             staked_balance: U128(5999999999999999999999999),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(5000000000000000000000001),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -7932,7 +8249,11 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(5000000000000000000000001),
             staked_balance: U128(5999999999999999999999999),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(5000000000000000000000001),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -8127,7 +8448,11 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(5000000000000000000000001),
             staked_balance: U128(6600000000000000435478171),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(5000000000000000000000001),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -8136,7 +8461,11 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(5000000000000000000000001),
             staked_balance: U128(6600000000000000435478171),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(5000000000000000000000001),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -8145,7 +8474,11 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(5000000000000000000000001),
             staked_balance: U128(6600000000000000435478171),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(5000000000000000000000001),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -8158,7 +8491,17 @@ async fn test_stake_unstake_and_withdraw_flow_with_reward_boost() -> anyhow::Res
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(6000000000000000000000002),
             staked_balance: U128(5600000000000000435478170),
-            withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![
+                UnstakeRequestResponse {
+                    amount: U128(5000000000000000000000001),
+                    withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+                },
+                UnstakeRequestResponse {
+                    amount: U128(1000000000000000000000001),
+                    withdrawable_epoch: U64(current_epoch_3.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+                },
+            ],
         }
     );
 
@@ -8382,7 +8725,8 @@ async fn test_bank_run_with_boosted_apr() -> anyhow::Result<()> {
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(11)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -8391,7 +8735,8 @@ async fn test_bank_run_with_boosted_apr() -> anyhow::Result<()> {
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(11)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -8400,7 +8745,8 @@ async fn test_bank_run_with_boosted_apr() -> anyhow::Result<()> {
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(11)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -8524,7 +8870,11 @@ async fn test_bank_run_with_boosted_apr() -> anyhow::Result<()> {
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(11)),
             staked_balance: U128(ntoy(0)),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(11)),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -8533,7 +8883,11 @@ async fn test_bank_run_with_boosted_apr() -> anyhow::Result<()> {
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(11)),
             staked_balance: U128(ntoy(0)),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(11)),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -8542,7 +8896,11 @@ async fn test_bank_run_with_boosted_apr() -> anyhow::Result<()> {
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(11)),
             staked_balance: U128(ntoy(0)),
-            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(11)),
+                withdrawable_epoch: U64(current_epoch_2.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -8763,7 +9121,11 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch() -> anyhow::Resul
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(3)),
             staked_balance: U128(ntoy(17)),
-            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(3)),
+                withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -8772,7 +9134,11 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch() -> anyhow::Resul
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(10)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(10)),
+                withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
     assert_eq!(
@@ -8781,7 +9147,11 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch() -> anyhow::Resul
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK),
+            }],
         }
     );
 
@@ -9128,7 +9498,8 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch() -> anyhow::Resul
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(17)),
-            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -9137,7 +9508,8 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch() -> anyhow::Resul
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
     assert_eq!(
@@ -9146,7 +9518,8 @@ async fn test_user_stake_unstake_withdraw_flows_in_same_epoch() -> anyhow::Resul
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
@@ -9398,7 +9771,11 @@ async fn test_stake_unstake_and_withdraw_flow_happy_flow() -> anyhow::Result<()>
             account_id: context.user1.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -9407,7 +9784,11 @@ async fn test_stake_unstake_and_withdraw_flow_happy_flow() -> anyhow::Result<()>
             account_id: context.user2.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(5)),
             staked_balance: U128(ntoy(5)),
-            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK + 1)
+            withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            unstake_requests: vec![UnstakeRequestResponse {
+                amount: U128(ntoy(5)),
+                withdrawable_epoch: U64(current_epoch.0 + NUM_EPOCHS_TO_UNLOCK + 1),
+            }],
         }
     );
     assert_eq!(
@@ -9416,7 +9797,8 @@ async fn test_stake_unstake_and_withdraw_flow_happy_flow() -> anyhow::Result<()>
             account_id: context.user3.id().clone().parse().unwrap(),
             unstaked_balance: U128(ntoy(0)),
             staked_balance: U128(ntoy(10)),
-            withdrawable_epoch: U64(0)
+            withdrawable_epoch: U64(0),
+            unstake_requests: vec![],
         }
     );
 
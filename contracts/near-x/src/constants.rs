@@ -10,6 +10,27 @@ pub const TEN_NEAR: u128 = 10 * ONE_NEAR;
 pub const K_NEAR: u128 = 1_000 * ONE_NEAR;
 
 pub const NUM_EPOCHS_TO_UNLOCK: EpochHeight = 4;
+// Validator performance score is rewards per staked NEAR per epoch scaled by this
+pub const PERFORMANCE_SCORE_PRECISION: u128 = 1_000_000_000_000_000_000;
+// Number of epochs the performance score is smoothed over
+pub const PERFORMANCE_SCORE_SMOOTHING_EPOCHS: u128 = 6;
+pub const PERFORMANCE_MULTIPLIER_PRECISION: u128 = 1_000_000;
+// Upper bound for the owner set max performance multiplier
+pub const MAX_PERFORMANCE_MULTIPLIER: u32 = 10;
+// Max number of pending unstake requests an account can hold
+pub const MAX_UNSTAKE_REQUESTS: usize = 8;
+// Max number of validators a single staking_epoch call splits the stake amount over
+pub const MAX_VALIDATORS_TO_STAKE_IN_EPOCH: usize = 4;
+// Smallest excess over the expected stake worth a rebalance unstake
+pub const MIN_REBALANCE_AMOUNT: u128 = ONE_NEAR;
+// Lowest per epoch unstake cap, keeps the number of epochs an unstake is queued over small
+pub const MIN_UNSTAKE_EPOCH_CAP: u128 = K_NEAR;
+// Number of exchange rate snapshots kept, one per epoch
+pub const PRICE_HISTORY_LENGTH: u64 = 730;
+// Roughly 12 hour epochs
+pub const EPOCHS_PER_YEAR: u64 = 730;
+// Max number of contracts the NearX price is pushed to, bounds the gas of the autocompounding callback
+pub const MAX_PRICE_SUBSCRIBERS: usize = 5;
 // Number of epochs to wait for reward fee to set
 pub const REWARD_FEE_SET_WAIT_TIME: EpochHeight = 4;
 
@@ -17,6 +38,25 @@ pub const REWARD_FEE_SET_WAIT_TIME: EpochHeight = 4;
 pub const ACCOUNTS_MAP: &str = "A";
 pub const VALIDATOR_MAP: &str = "B";
 pub const NEW_VALIDATOR_MAP: &str = "C";
+pub const WITHDRAWAL_TICKETS_MAP: &str = "D";
+pub const NEW_ACCOUNTS_MAP: &str = "E";
+pub const REBALANCE_UNSTAKES_MAP: &str = "F";
+pub const STORAGE_DEPOSITS_MAP: &str = "G";
+pub const FT_METADATA: &str = "H";
+pub const PROPOSALS_MAP: &str = "I";
+pub const COUNCIL_APPROVALS_MAP: &str = "J";
+pub const ROLES_MAP: &str = "K";
+pub const UNSTAKE_QUEUE_MAP: &str = "L";
+pub const PRICE_HISTORY: &str = "M";
+pub const QUARANTINED_BALANCES_MAP: &str = "N";
+pub const INSURANCE_FUND_HISTORY: &str = "O";
+pub const INSTANT_UNSTAKE_RESERVE_PENDING_MAP: &str = "P";
+
+/// Longest data URI accepted as the NearX icon
+pub const MAX_FT_ICON_LENGTH: usize = 10 * 1024;
+
+/// Attached to unstake_to_ticket to pay for the storage of the ticket, refunded on claim
+pub const WITHDRAWAL_TICKET_STORAGE_DEPOSIT: u128 = 10 * ONE_MILLI_NEAR;
 
 uint::construct_uint! {
         /// 256-bit unsigned integer.
@@ -52,6 +92,13 @@ pub mod gas {
 
     pub const ON_STAKE_POOL_GET_ACCOUNT_STAKED_BALANCE_CB: Gas = base_gas(1);
 
+    /// Gas attached to the on_nearx_price_update call of every price subscriber, added to the
+    /// autocompounding callback and to confirm_quarantined_balance for each subscriber
+    pub const ON_NEARX_PRICE_UPDATE: Gas = tera(5);
+
+    /// Gas kept aside by the autocompounding callback to schedule a price push and finish
+    pub const PUSH_NEARX_PRICE: Gas = tera(2);
+
     pub const ON_STAKE_POOL_GET_ACCOUNT_TOTAL_BALANCE: Gas = base_gas(1);
 
     pub const ON_STAKE_POOL_GET_ACCOUNT_TOTAL_BALANCE_CB: Gas = base_gas(1);
@@ -77,14 +124,29 @@ pub mod gas {
 
     pub const SYNC_VALIDATOR_EPOCH: Gas = base_gas(3);
 
+    /// Gas used by confirm_quarantined_balance to book the balance, before the price push
+    pub const CONFIRM_QUARANTINED_BALANCE: Gas = base_gas(1);
+
     pub const DRAIN_UNSTAKE: Gas = base_gas(3);
 
     pub const DRAIN_WITHDRAW: Gas = base_gas(3);
 
+    pub const REBALANCE_EPOCH: Gas = base_gas(3);
+
+    /// Gas kept aside by run_epoch to save its cursor after scheduling the epoch actions
+    pub const RUN_EPOCH: Gas = base_gas(1);
+
+    /// Gas used by run_epoch to schedule a single epoch action, on top of the attached gas
+    pub const RUN_EPOCH_ACTION: Gas = tera(5);
+
     pub const FT_TRANSFER_RESOLVE: Gas = tera(12);
 
     pub const FT_TRANSFER: Gas = tera(35);
 
+    pub const NFT_TRANSFER_RESOLVE: Gas = tera(12);
+
+    pub const NFT_TRANSFER: Gas = tera(35);
+
     const fn base_gas(n: u64) -> Gas {
         Gas(1_000_000_000_000 * 25 * n)
     }
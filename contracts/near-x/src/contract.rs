@@ -7,10 +7,11 @@ mod upgrade;
 mod util;
 
 use crate::state::*;
-use near_sdk::json_types::U128;
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadata;
+use near_sdk::json_types::{U128, U64};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::UnorderedMap,
+    collections::{LazyOption, LookupMap, TreeMap, UnorderedMap, Vector},
     env, ext_contract, near_bindgen,
     serde::{Deserialize, Serialize},
     AccountId, Balance, EpochHeight, PanicOnDefault, PromiseOrValue, PublicKey,
@@ -32,6 +33,36 @@ pub struct OperationControls {
     pub sync_validator_balance_paused: bool,
     pub ft_transfer_paused: bool,
     pub ft_transfer_call_paused: bool,
+    pub instant_unstake_paused: bool,
+    pub rebalance_epoch_paused: bool,
+}
+
+impl OperationControls {
+    fn paused_flags(&self) -> [bool; 13] {
+        [
+            self.stake_paused,
+            self.direct_stake_paused,
+            self.unstaked_paused,
+            self.withdraw_paused,
+            self.staking_epoch_paused,
+            self.unstaking_epoch_paused,
+            self.withdraw_epoch_paused,
+            self.autocompounding_epoch_paused,
+            self.sync_validator_balance_paused,
+            self.ft_transfer_paused,
+            self.ft_transfer_call_paused,
+            self.instant_unstake_paused,
+            self.rebalance_epoch_paused,
+        ]
+    }
+
+    /// Whether an operation paused in these controls runs again in `new_controls`
+    pub fn is_unpaused_in(&self, new_controls: &OperationControls) -> bool {
+        self.paused_flags()
+            .iter()
+            .zip(new_controls.paused_flags().iter())
+            .any(|(paused, new_paused)| *paused && !*new_paused)
+    }
 }
 
 #[derive(
@@ -51,6 +82,24 @@ pub struct LegacyOperationControlsV1 {
     pub ft_transfer_call_paused: bool,
 }
 
+#[derive(
+    Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LegacyOperationControlsV2 {
+    pub stake_paused: bool,
+    pub direct_stake_paused: bool,
+    pub unstaked_paused: bool,
+    pub withdraw_paused: bool,
+    pub staking_epoch_paused: bool,
+    pub unstaking_epoch_paused: bool,
+    pub withdraw_epoch_paused: bool,
+    pub autocompounding_epoch_paused: bool,
+    pub sync_validator_balance_paused: bool,
+    pub ft_transfer_paused: bool,
+    pub ft_transfer_call_paused: bool,
+}
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct NearxPool {
@@ -80,6 +129,10 @@ pub struct NearxPool {
     // User account map
     pub accounts: UnorderedMap<AccountId, Account>,
 
+    // Accounts in the single unstake slot layout, moved to `accounts` when touched or
+    // through migrate_accounts
+    pub legacy_accounts: UnorderedMap<AccountId, LegacyAccountV1>,
+
     pub validator_info_map: UnorderedMap<AccountId, ValidatorInfoWrapper>,
 
     pub total_validator_weight: u16,
@@ -115,6 +168,131 @@ pub struct NearxPool {
     pub operations_control: OperationControls,
 
     pub min_storage_reserve: u128,
+
+    // liquid NEAR set aside to pay out instant unstakes right away
+    pub instant_unstake_reserve: u128,
+
+    // NEAR the reserve paid out for burnt NearX which is still unbonding from the validators,
+    // keyed by the epoch it is due at. It goes back into the reserve once it is withdrawn
+    pub instant_unstake_reserve_pending: TreeMap<EpochHeight, Balance>,
+
+    pub max_instant_unstake_reserve: u128,
+
+    // the instant unstake fee moves from max to min as the reserve fills up
+    pub instant_unstake_min_fee: Fraction,
+
+    pub instant_unstake_max_fee: Fraction,
+
+    // instant unstake fees paid to the treasury as NearX, in NEAR
+    pub accumulated_instant_unstake_fees: u128,
+
+    // Withdrawal tickets minted by unstake_to_ticket, keyed by ticket id
+    pub withdrawal_tickets: UnorderedMap<String, WithdrawalTicket>,
+
+    pub last_withdrawal_ticket_id: u64,
+
+    // bounds of the performance score multiplier applied to validator weights
+    pub min_performance_multiplier: Fraction,
+
+    pub max_performance_multiplier: Fraction,
+
+    // number of consecutive failed epoch actions or zero reward epochs after which a validator
+    // is paused automatically. 0 disables the auto pause
+    pub validator_delinquency_threshold: u32,
+
+    // validators paused for delinquency which still have funds to be drained
+    pub validators_to_drain: Vec<AccountId>,
+
+    // where run_epoch stopped in the current epoch
+    pub run_epoch_cursor: RunEpochCursor,
+
+    // NEAR worth of NearX paid per useful permissionless epoch action. 0 turns keeper rewards off
+    pub keeper_reward_per_action: Balance,
+
+    pub max_keeper_rewards_per_epoch: Balance,
+
+    // share of the rewards fee that goes to the keeper budget instead of the treasury
+    pub keeper_rewards_fee_share: Fraction,
+
+    // NearX set aside to pay the keepers
+    pub keeper_budget_shares: u128,
+
+    pub keeper_rewards_epoch: EpochHeight,
+
+    pub keeper_rewards_paid_in_epoch: Balance,
+
+    pub unstake_strategy: UnstakeStrategy,
+
+    // amount unstaked by rebalance_epoch per validator, restaked once it is withdrawn
+    pub rebalance_unstakes: UnorderedMap<AccountId, Balance>,
+
+    // storage deposit of the accounts which deposited more than the registration minimum
+    pub storage_deposits: LookupMap<AccountId, Balance>,
+
+    pub ft_metadata: LazyOption<FungibleTokenMetadata>,
+
+    // owner actions waiting for their delay to pass, keyed by proposal id
+    pub proposals: UnorderedMap<u64, Proposal>,
+
+    pub last_proposal_id: u64,
+
+    // epochs an owner action waits in the proposal queue. 0 lets the owner act directly
+    pub proposal_delay_epochs: EpochHeight,
+
+    // accounts approving critical actions, the council is off while the threshold is 0
+    pub council_members: Vec<AccountId>,
+
+    pub council_threshold: u32,
+
+    pub council_approval_ttl_epochs: EpochHeight,
+
+    pub council_approvals: UnorderedMap<CouncilAction, Vec<CouncilApproval>>,
+
+    // accounts granted each admin role by the owner
+    pub roles: UnorderedMap<Role, Vec<AccountId>>,
+
+    // no cap on the NEAR unstaked in an epoch when None
+    pub unstake_epoch_cap: Option<UnstakeEpochCap>,
+
+    // unstaked NEAR waiting for the epoch cap, keyed by the epoch whose reconciliation takes
+    // the last of it. Every reconciliation takes up to the cap from the front of the queue
+    pub unstake_queue: TreeMap<EpochHeight, Balance>,
+
+    // total of the unstake queue
+    pub unstake_queue_amount: Balance,
+
+    // ring buffer of the exchange rate snapshots, one per epoch
+    pub price_history: Vector<PriceSnapshot>,
+
+    pub price_snapshots_taken: u64,
+
+    // contracts the NearX price is pushed to after every rewards distribution
+    pub price_subscribers: Vec<AccountId>,
+
+    // increased on every price push, lets subscribers drop stale or replayed updates
+    pub price_update_nonce: u64,
+
+    // None turns the exchange rate guard off, validator balance drops are then ignored
+    pub exchange_rate_bounds: Option<ExchangeRateBounds>,
+
+    // validator balance reports outside of the exchange rate bounds, waiting for the operator
+    pub quarantined_balances: UnorderedMap<AccountId, QuarantinedBalance>,
+
+    // NearX held by the contract to cover validator losses
+    pub insurance_fund_shares: u128,
+
+    // share of a validator loss, after the rewards buffer, the insurance fund covers right away.
+    // Set by governance, zero leaves every loss to `cover_loss_from_insurance_fund`
+    pub insurance_fund_coverage: Fraction,
+
+    // validator losses taken by NearX holders that the insurance fund has not covered yet
+    pub uncovered_validator_losses: Balance,
+
+    // share of the rewards fee left after the keeper budget that goes to the insurance fund
+    pub insurance_fee_share: Fraction,
+
+    // changes of the insurance fund, one record per epoch the fund changed in
+    pub insurance_fund_history: Vector<InsuranceFundRecord>,
 }
 
 #[near_bindgen]
@@ -144,7 +322,7 @@ pub struct LegacyNearxPoolV1 {
     pub last_reconcilation_epoch: EpochHeight,
 
     // User account map
-    pub accounts: UnorderedMap<AccountId, Account>,
+    pub accounts: UnorderedMap<AccountId, LegacyAccountV1>,
 
     pub validator_info_map: UnorderedMap<AccountId, ValidatorInfo>,
     pub total_validator_weight: u16,
@@ -193,7 +371,7 @@ pub struct LegacyNearxPoolV2 {
     pub last_reconcilation_epoch: EpochHeight,
 
     // User account map
-    pub accounts: UnorderedMap<AccountId, Account>,
+    pub accounts: UnorderedMap<AccountId, LegacyAccountV1>,
 
     pub validator_info_map: UnorderedMap<AccountId, ValidatorInfo>,
     pub total_validator_weight: u16,
@@ -254,7 +432,7 @@ pub struct LegacyNearxPoolV3 {
     pub last_reconcilation_epoch: EpochHeight,
 
     // User account map
-    pub accounts: UnorderedMap<AccountId, Account>,
+    pub accounts: UnorderedMap<AccountId, LegacyAccountV1>,
 
     pub validator_info_map: UnorderedMap<AccountId, LegacyValidatorInfoV1>,
     pub total_validator_weight: u16,
@@ -292,6 +470,72 @@ pub struct LegacyNearxPoolV3 {
     pub min_storage_reserve: u128,
 }
 
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct LegacyNearxPoolV4 {
+    pub owner_account_id: AccountId,
+
+    /// The total amount of tokens actually staked (the tokens are in the staking pools)
+    // nearx_price = (total_staked) / (total_stake_shares)
+    pub total_staked: u128,
+
+    /// how many "NearX" were minted.
+    pub total_stake_shares: u128, //total NearX minted
+
+    pub accumulated_staked_rewards: u128,
+
+    /// Amount of NEAR that is users requested to stake
+    pub user_amount_to_stake_in_epoch: Balance,
+    /// Amount of NEAR that is users requested to unstake
+    pub user_amount_to_unstake_in_epoch: Balance,
+
+    /// Amount of NEAR that actually needs to be staked in the epoch
+    pub reconciled_epoch_stake_amount: Balance,
+    /// Amount of NEAR that actually needs to be unstaked in the epoch
+    pub reconciled_epoch_unstake_amount: Balance,
+    /// Last epoch height stake/unstake amount were reconciled
+    pub last_reconcilation_epoch: EpochHeight,
+
+    // User account map
+    pub accounts: UnorderedMap<AccountId, LegacyAccountV1>,
+
+    pub validator_info_map: UnorderedMap<AccountId, ValidatorInfoWrapper>,
+
+    pub total_validator_weight: u16,
+
+    /// min amount accepted as deposit or stake
+    pub min_deposit_amount: u128,
+
+    pub operator_account_id: AccountId,
+
+    pub treasury_account_id: AccountId,
+
+    pub rewards_fee: Fraction,
+
+    // extra rewards which the owner/operator can drop to increase the exchange rate and increase the
+    // staked amounts of all users
+    pub rewards_buffer: u128,
+
+    pub accumulated_rewards_buffer: u128,
+
+    // Temp owner for owner update
+    // This is to have 2 commit owner update
+    pub temp_owner: Option<AccountId>,
+
+    pub temp_operator: Option<AccountId>,
+
+    pub temp_treasury: Option<AccountId>,
+
+    pub temp_reward_fee: Option<Fraction>,
+
+    pub last_reward_fee_set_epoch: EpochHeight,
+
+    // Operations control
+    pub operations_control: LegacyOperationControlsV2,
+
+    pub min_storage_reserve: u128,
+}
+
 //self-callbacks
 #[ext_contract(ext_staking_pool_callback)]
 pub trait ExtNearxStakingPoolCallbacks {
@@ -313,11 +557,28 @@ pub trait ExtNearxStakingPoolCallbacks {
         user: AccountId,
     ) -> PromiseOrValue<bool>;
 
-    fn on_stake_pool_deposit_and_stake(&mut self, validator_id: AccountId, amount: u128);
+    fn on_stake_pool_deposit_and_stake(
+        &mut self,
+        validator_id: AccountId,
+        amount: u128,
+        keeper_id: Option<AccountId>,
+    );
+
+    fn on_stake_pool_withdraw_all(
+        &mut self,
+        validator_id: AccountId,
+        amount: u128,
+        keeper_id: Option<AccountId>,
+    );
 
-    fn on_stake_pool_withdraw_all(&mut self, validator_id: AccountId, amount: u128);
+    fn on_stake_pool_unstake(
+        &mut self,
+        validator_id: AccountId,
+        amount_to_unstake: u128,
+        keeper_id: Option<AccountId>,
+    );
 
-    fn on_stake_pool_unstake(&mut self, validator_id: AccountId, amount_to_unstake: u128);
+    fn on_stake_pool_rebalance_unstake(&mut self, validator_id: AccountId, amount_to_unstake: u128);
 
     fn on_stake_pool_drain_unstake(&mut self, validator_id: AccountId, amount_to_unstake: u128);
 
@@ -326,6 +587,7 @@ pub trait ExtNearxStakingPoolCallbacks {
     fn on_get_sp_staked_balance_for_rewards(
         &mut self,
         validator_id: AccountId,
+        keeper_id: Option<AccountId>,
         #[callback] total_staked_balance: U128,
     ) -> PromiseOrValue<bool>;
 
@@ -336,6 +598,11 @@ pub trait ExtNearxStakingPoolCallbacks {
     );
 }
 
+#[ext_contract(ext_price_subscriber)]
+pub trait ExtNearxPriceSubscriber {
+    fn on_nearx_price_update(&mut self, price: U128, epoch: U64, nonce: U64);
+}
+
 #[ext_contract(ext_staking_pool)]
 pub trait ExtStakingPool {
     fn get_account_staked_balance(&self, account_id: AccountId) -> U128;
@@ -1,14 +1,16 @@
 use crate::constants::*;
 use crate::errors::*;
 use crate::events::Event;
+use crate::utils::proportional;
 use crate::{
     constants::{gas, NO_DEPOSIT},
     contract::*,
     state::*,
 };
+use near_contract_standards::fungible_token::events as nep141;
 use near_contract_standards::storage_management::StorageManagement;
 use near_sdk::{
-    is_promise_success, log, require, AccountId, Balance, Promise, PromiseOrValue, ONE_NEAR,
+    is_promise_success, log, require, AccountId, Balance, Gas, Promise, PromiseOrValue, ONE_NEAR,
 };
 
 #[near_bindgen]
@@ -30,6 +32,7 @@ impl NearxPool {
         user_amount: Balance,
         validator: AccountId,
     ) {
+        self.assert_staking_not_paused();
         self.assert_min_deposit_amount(user_amount);
 
         let account_id = env::predecessor_account_id();
@@ -85,6 +88,13 @@ impl NearxPool {
             self.internal_update_validator(&validator_info.account_id, &validator_info);
             self.internal_update_account(&user, acc);
 
+            nep141::FtMint {
+                owner_id: &user,
+                amount: &U128(num_shares),
+                memo: Some("manager_deposit_and_stake"),
+            }
+            .emit();
+
             Event::ManagerDepositAndStake {
                 account_id: user,
                 amount: U128(amount),
@@ -170,6 +180,13 @@ impl NearxPool {
             self.internal_update_validator(&validator_info.account_id, &validator_info);
             self.internal_update_account(&user, acc);
 
+            nep141::FtMint {
+                owner_id: &user,
+                amount: &U128(num_shares),
+                memo: Some("direct_deposit_and_stake"),
+            }
+            .emit();
+
             Event::DirectDepositAndStake {
                 account_id: user,
                 amount: U128(amount),
@@ -191,12 +208,16 @@ impl NearxPool {
         }
     }
 
-    pub(crate) fn internal_deposit_and_stake(&mut self, amount: u128) {
+    /// Stakes the amount and mints the NearX to the account, returns the minted NearX
+    pub(crate) fn internal_deposit_and_stake(
+        &mut self,
+        account_id: AccountId,
+        amount: u128,
+    ) -> u128 {
         self.assert_staking_not_paused();
 
         self.assert_min_deposit_amount(amount);
 
-        let account_id = env::predecessor_account_id();
         // we need to call storage_deposit and register the user storage before the user deposits
         let mut account = self.internal_get_account_unwrap(&account_id);
 
@@ -214,6 +235,13 @@ impl NearxPool {
         // Increase requested stake amount within the current epoch
         self.user_amount_to_stake_in_epoch += amount;
 
+        nep141::FtMint {
+            owner_id: &account_id,
+            amount: &U128(num_shares),
+            memo: Some("deposit_and_stake"),
+        }
+        .emit();
+
         Event::DepositAndStake {
             account_id,
             amount: U128(amount),
@@ -221,14 +249,15 @@ impl NearxPool {
             new_stake_shares: U128(account.stake_shares),
         }
         .emit();
+
+        num_shares
     }
 
-    pub(crate) fn internal_unstake(&mut self, amount: u128) {
+    pub(crate) fn internal_unstake(&mut self, account_id: AccountId, amount: u128) {
         self.assert_unstaking_not_paused();
 
         require!(amount > 0, ERROR_NON_POSITIVE_UNSTAKE_AMOUNT);
 
-        let account_id = env::predecessor_account_id();
         let mut account = self.internal_get_account(&account_id);
 
         require!(
@@ -250,7 +279,6 @@ impl NearxPool {
         );
 
         account.stake_shares -= num_shares;
-        account.unstaked_amount += receive_amount;
 
         let remaining_amount =
             self.staked_amount_from_num_shares_rounded_down(account.stake_shares);
@@ -263,33 +291,36 @@ impl NearxPool {
             num_shares += account.stake_shares;
 
             account.stake_shares = 0;
-            account.unstaked_amount += remaining_amount;
         }
 
-        account.withdrawable_epoch_height =
-            env::epoch_height() + self.get_unstake_release_epoch(account.unstaked_amount);
-        if self.last_reconcilation_epoch == env::epoch_height() {
-            // The unstake request is received after epoch_reconcilation
-            // so actual unstake will happen in the next epoch,
-            // which will put withdraw off for one more epoch.
-            account.withdrawable_epoch_height += 1;
-        }
+        let withdrawable_epoch_height = self.internal_schedule_unstake(receive_amount);
+
+        account.add_unstake_request(
+            receive_amount,
+            withdrawable_epoch_height,
+            env::epoch_height(),
+        );
+        self.assert_storage_deposit_covers(&account_id, &account);
 
         self.internal_update_account(&account_id, &account);
 
         self.total_staked -= receive_amount;
         self.total_stake_shares -= num_shares;
 
-        // Increase requested unstake amount within the current epoch
-        self.user_amount_to_unstake_in_epoch += receive_amount;
-
         Event::Unstake {
             account_id: account_id.clone(),
             unstaked_amount: U128(receive_amount),
             burnt_stake_shares: U128(num_shares),
-            new_unstaked_balance: U128(account.unstaked_amount),
+            new_unstaked_balance: U128(account.unstaked_amount()),
             new_stake_shares: U128(account.stake_shares),
-            unstaked_available_epoch_height: account.withdrawable_epoch_height,
+            unstaked_available_epoch_height: withdrawable_epoch_height,
+        }
+        .emit();
+
+        nep141::FtBurn {
+            owner_id: &account_id,
+            amount: &U128(num_shares),
+            memo: Some("unstake"),
         }
         .emit();
 
@@ -300,6 +331,421 @@ impl NearxPool {
         .emit();
     }
 
+    /// Adds the unstaked amount to the epoch reconciliations and returns the epoch it can be
+    /// withdrawn at. With an unstake epoch cap the amount is queued behind the earlier unstakes
+    /// and reconciled by the epoch the queue reaches it at the current cap.
+    pub(crate) fn internal_schedule_unstake(&mut self, amount: Balance) -> EpochHeight {
+        // An unstake received after epoch_reconcilation is only reconciled in the next epoch,
+        // which puts the withdraw off for one more epoch.
+        let first_epoch = if self.last_reconcilation_epoch == env::epoch_height() {
+            env::epoch_height() + 1
+        } else {
+            env::epoch_height()
+        };
+
+        let epoch = if let Some(cap) = self.get_unstake_epoch_cap_amount() {
+            // the first epoch also takes the unstakes not going through the queue
+            let scheduled_amount =
+                self.user_amount_to_unstake_in_epoch + self.unstake_queue_amount + amount;
+            let last_epoch =
+                first_epoch + (scheduled_amount.saturating_sub(1) / cap) as EpochHeight;
+
+            let queued_amount = self.unstake_queue.get(&last_epoch).unwrap_or(0);
+            self.unstake_queue
+                .insert(&last_epoch, &(queued_amount + amount));
+            self.unstake_queue_amount += amount;
+            last_epoch
+        } else {
+            self.user_amount_to_unstake_in_epoch += amount;
+            first_epoch
+        };
+
+        epoch + self.get_unstake_release_epoch(amount)
+    }
+
+    /// Records the exchange rate the first time it is called in an epoch, the oldest snapshot
+    /// is overwritten once the history is full
+    pub(crate) fn internal_record_price_snapshot(&mut self) {
+        if self.price_snapshots_taken > 0 {
+            let latest_index = (self.price_snapshots_taken - 1) % PRICE_HISTORY_LENGTH;
+            if let Some(latest_snapshot) = self.price_history.get(latest_index) {
+                if latest_snapshot.epoch == env::epoch_height() {
+                    return;
+                }
+            }
+        }
+
+        let snapshot = PriceSnapshot {
+            epoch: env::epoch_height(),
+            total_staked: self.total_staked,
+            total_stake_shares: self.total_stake_shares,
+        };
+        let index = self.price_snapshots_taken % PRICE_HISTORY_LENGTH;
+        if index < self.price_history.len() {
+            self.price_history.replace(index, &snapshot);
+        } else {
+            self.price_history.push(&snapshot);
+        }
+        self.price_snapshots_taken += 1;
+    }
+
+    /// Price snapshots from the oldest to the latest
+    pub(crate) fn internal_price_history(&self) -> Vec<PriceSnapshot> {
+        let len = self.price_history.len();
+        if len == 0 {
+            return vec![];
+        }
+
+        let oldest_index = self.price_snapshots_taken % len;
+        (0..len)
+            .map(|i| self.price_history.get((oldest_index + i) % len).unwrap())
+            .collect()
+    }
+
+    /// Gas the autocompounding callback needs on top of its own to push the price to every
+    /// price subscriber
+    pub(crate) fn internal_price_push_gas(&self) -> Gas {
+        Gas(gas::ON_NEARX_PRICE_UPDATE.0 * self.price_subscribers.len() as u64)
+    }
+
+    /// Pushes the NearX price to the price subscribers. It runs on every rewards distribution,
+    /// so subscribers get one update per autocompounded validator, ordered by the nonce.
+    /// Subscribers left once the prepaid gas runs low are skipped until the next distribution
+    pub(crate) fn internal_push_price_to_subscribers(&mut self) {
+        if self.price_subscribers.is_empty() {
+            return;
+        }
+
+        self.price_update_nonce += 1;
+        let price = self.get_nearx_price();
+        let epoch = U64(env::epoch_height());
+        let nonce = U64(self.price_update_nonce);
+
+        let mut subscribers = vec![];
+        let mut skipped_subscribers = vec![];
+        for subscriber in self.price_subscribers.clone() {
+            let gas_left = env::prepaid_gas().0.saturating_sub(env::used_gas().0);
+            if gas_left < gas::ON_NEARX_PRICE_UPDATE.0 + gas::PUSH_NEARX_PRICE.0 {
+                log!("Not enough gas to push the price to {}", subscriber);
+                skipped_subscribers.push(subscriber);
+                continue;
+            }
+
+            ext_price_subscriber::ext(subscriber.clone())
+                .with_attached_deposit(NO_DEPOSIT)
+                .with_static_gas(gas::ON_NEARX_PRICE_UPDATE)
+                .on_nearx_price_update(price, epoch, nonce);
+            subscribers.push(subscriber);
+        }
+
+        Event::NearxPriceUpdatePushed {
+            price,
+            epoch,
+            nonce,
+            subscribers,
+            skipped_subscribers,
+        }
+        .emit();
+    }
+
+    /// Books the increase of the validator balance up to `new_total_balance` as rewards and
+    /// mints the rewards fee. Returns whether the treasury got any shares
+    pub(crate) fn internal_distribute_rewards(
+        &mut self,
+        validator_info: &mut ValidatorInfo,
+        new_total_balance: Balance,
+        keeper_to_reward: Option<AccountId>,
+    ) -> bool {
+        let rewards = new_total_balance - validator_info.staked;
+
+        //updated accumulated_staked_rewards value for the contract
+        self.accumulated_staked_rewards += rewards;
+        //updated new "staked" value for this pool
+        validator_info.staked = new_total_balance;
+        // consider rewards to unstakable since its excess rewards and rewards get distributed to all users
+        validator_info.max_unstakable_limit = validator_info.max_unstakable_limit + rewards;
+
+        let operator_fee = rewards * self.rewards_fee;
+        log!("operator fee is {}", operator_fee);
+        self.total_staked += rewards;
+        let mut treasury_account_shares =
+            self.num_shares_from_staked_amount_rounded_down(operator_fee);
+
+        self.internal_update_validator(&validator_info.account_id, validator_info);
+
+        // part of the fee goes to the keeper budget
+        let keeper_budget_shares = treasury_account_shares * self.keeper_rewards_fee_share;
+        self.keeper_budget_shares += keeper_budget_shares;
+        self.total_stake_shares += keeper_budget_shares;
+        treasury_account_shares -= keeper_budget_shares;
+
+        if keeper_budget_shares > 0 {
+            nep141::FtMint {
+                owner_id: &env::current_account_id(),
+                amount: &U128(keeper_budget_shares),
+                memo: Some("keeper_budget"),
+            }
+            .emit();
+        }
+
+        // part of the fee left goes to the insurance fund
+        let insurance_fund_shares = treasury_account_shares * self.insurance_fee_share;
+        if insurance_fund_shares > 0 {
+            self.insurance_fund_shares += insurance_fund_shares;
+            self.total_stake_shares += insurance_fund_shares;
+            treasury_account_shares -= insurance_fund_shares;
+
+            nep141::FtMint {
+                owner_id: &env::current_account_id(),
+                amount: &U128(insurance_fund_shares),
+                memo: Some("insurance_fund"),
+            }
+            .emit();
+
+            let mut record = self.internal_current_insurance_fund_record();
+            record.fee_shares += insurance_fund_shares;
+            self.internal_save_insurance_fund_record(record);
+        }
+
+        if treasury_account_shares > 0 {
+            // Mint shares for the treasury account
+            let treasury_account_id = self.treasury_account_id.clone();
+            let mut treasury_account = self.internal_get_account(&treasury_account_id);
+            treasury_account.stake_shares += treasury_account_shares;
+            self.total_stake_shares += treasury_account_shares;
+            self.internal_update_account(&treasury_account_id, &treasury_account);
+
+            nep141::FtMint {
+                owner_id: &treasury_account_id,
+                amount: &U128(treasury_account_shares),
+                memo: Some("rewards_fee"),
+            }
+            .emit();
+        }
+
+        // paid once all the fee shares are minted, at the final exchange rate
+        if let Some(keeper_id) = keeper_to_reward {
+            self.internal_reward_keeper(&keeper_id, 1);
+        }
+
+        self.internal_push_price_to_subscribers();
+
+        treasury_account_shares > 0
+    }
+
+    /// Takes over the staked and unstaked balance synced from the validator. A total balance
+    /// short by more than the rounding of the staking pool is booked as a loss
+    pub(crate) fn internal_sync_validator_balance(
+        &mut self,
+        validator_info: &mut ValidatorInfo,
+        staked: Balance,
+        unstaked: Balance,
+    ) {
+        Event::BalanceSyncedFromValidator {
+            validator_id: validator_info.account_id.clone(),
+            old_staked_balance: U128(validator_info.staked),
+            old_unstaked_balance: U128(validator_info.unstaked_amount),
+            staked_balance: U128(staked),
+            unstaked_balance: U128(unstaked),
+        }
+        .emit();
+
+        let old_total_balance = validator_info.total_balance();
+        validator_info.staked = staked;
+        validator_info.unstaked_amount = unstaked;
+        validator_info.max_unstakable_limit =
+            std::cmp::min(validator_info.max_unstakable_limit, staked);
+        self.internal_update_validator(&validator_info.account_id, validator_info);
+
+        let new_total_balance = validator_info.total_balance();
+        if old_total_balance > new_total_balance + 10000 {
+            self.internal_cover_validator_loss(
+                &validator_info.account_id,
+                old_total_balance,
+                new_total_balance,
+            );
+        }
+    }
+
+    /// Books the drop of the validator stake down to `new_balance` as a loss
+    pub(crate) fn internal_book_validator_loss(
+        &mut self,
+        validator_info: &mut ValidatorInfo,
+        new_balance: Balance,
+    ) {
+        let old_balance = validator_info.staked;
+
+        validator_info.staked = new_balance;
+        validator_info.max_unstakable_limit =
+            std::cmp::min(validator_info.max_unstakable_limit, new_balance);
+        self.internal_update_validator(&validator_info.account_id, validator_info);
+
+        self.internal_cover_validator_loss(&validator_info.account_id, old_balance, new_balance);
+    }
+
+    /// Takes the loss of a validator balance dropping from `old_balance` to `new_balance` out of
+    /// the total staked. The liquid rewards buffer goes first and is staked again in place of the
+    /// lost stake, then the insurance fund burns its shares to make NearX holders whole for its
+    /// coverage of the rest. The rewards buffer is already counted in the total staked, so what
+    /// it covers keeps the validator stake up but still lowers the NearX price. Only the insurance
+    /// fund holds the price up, everything else it leaves is socialized and recorded as uncovered.
+    /// The insurance fund is only spent here within the coverage governance set beforehand
+    pub(crate) fn internal_cover_validator_loss(
+        &mut self,
+        validator_id: &AccountId,
+        old_balance: Balance,
+        new_balance: Balance,
+    ) {
+        let loss = old_balance - new_balance;
+        let old_price = self.get_nearx_price();
+
+        self.total_staked = self.total_staked.saturating_sub(loss);
+
+        let covered_by_rewards_buffer = std::cmp::min(loss, self.rewards_buffer);
+        self.rewards_buffer -= covered_by_rewards_buffer;
+        self.user_amount_to_stake_in_epoch += covered_by_rewards_buffer;
+
+        let insurance_fund_shares_burnt = std::cmp::min(
+            self.num_shares_from_staked_amount_rounded_down(
+                (loss - covered_by_rewards_buffer) * self.insurance_fund_coverage,
+            ),
+            self.insurance_fund_shares,
+        );
+        let covered_by_insurance_fund =
+            self.internal_burn_insurance_fund_shares(insurance_fund_shares_burnt);
+        let socialized = loss - covered_by_insurance_fund;
+        self.uncovered_validator_losses += socialized;
+
+        Event::ValidatorLoss {
+            validator_id: validator_id.clone(),
+            old_balance: U128(old_balance),
+            new_balance: U128(new_balance),
+            loss: U128(loss),
+            covered_by_rewards_buffer: U128(covered_by_rewards_buffer),
+            covered_by_insurance_fund: U128(covered_by_insurance_fund),
+            insurance_fund_shares_burnt: U128(insurance_fund_shares_burnt),
+            socialized: U128(socialized),
+            old_price,
+            new_price: self.get_nearx_price(),
+        }
+        .emit();
+    }
+
+    /// Burns NearX of the insurance fund, handing its worth over to the other NearX holders.
+    /// Returns the NEAR worth of the burnt shares
+    pub(crate) fn internal_burn_insurance_fund_shares(&mut self, shares: u128) -> Balance {
+        if shares == 0 {
+            return 0;
+        }
+
+        let amount = self.staked_amount_from_num_shares_rounded_down(shares);
+        self.insurance_fund_shares -= shares;
+        self.total_stake_shares -= shares;
+
+        nep141::FtBurn {
+            owner_id: &env::current_account_id(),
+            amount: &U128(shares),
+            memo: Some("insurance_fund"),
+        }
+        .emit();
+
+        let mut record = self.internal_current_insurance_fund_record();
+        record.burnt_shares += shares;
+        record.covered_amount += amount;
+        self.internal_save_insurance_fund_record(record);
+
+        amount
+    }
+
+    /// Insurance fund record of the current epoch, a new one if the fund did not change yet
+    pub(crate) fn internal_current_insurance_fund_record(&self) -> InsuranceFundRecord {
+        let epoch = env::epoch_height();
+        self.internal_last_insurance_fund_record()
+            .filter(|record| record.epoch == epoch)
+            .unwrap_or(InsuranceFundRecord {
+                epoch,
+                fee_shares: 0,
+                deposited_shares: 0,
+                burnt_shares: 0,
+                covered_amount: 0,
+                fund_shares: self.insurance_fund_shares,
+            })
+    }
+
+    /// Saves the insurance fund record of the current epoch along with the fund shares
+    pub(crate) fn internal_save_insurance_fund_record(&mut self, mut record: InsuranceFundRecord) {
+        record.fund_shares = self.insurance_fund_shares;
+
+        let is_last_record = self
+            .internal_last_insurance_fund_record()
+            .map(|last_record| last_record.epoch == record.epoch)
+            .unwrap_or_default();
+        if is_last_record {
+            self.insurance_fund_history
+                .replace(self.insurance_fund_history.len() - 1, &record);
+        } else {
+            self.insurance_fund_history.push(&record);
+        }
+    }
+
+    fn internal_last_insurance_fund_record(&self) -> Option<InsuranceFundRecord> {
+        match self.insurance_fund_history.len() {
+            0 => None,
+            len => self.insurance_fund_history.get(len - 1),
+        }
+    }
+
+    /// Largest balance change the exchange rate bounds allowed if the balance reported by the
+    /// validator differs from `old_balance` by more, None if it is within the bounds or there
+    /// are none
+    pub(crate) fn internal_exceeded_exchange_rate_bound(
+        &self,
+        old_balance: Balance,
+        reported_balance: Balance,
+        last_redeemed_rewards_epoch: EpochHeight,
+    ) -> Option<Balance> {
+        let bounds = self.exchange_rate_bounds?;
+
+        // the first report of a validator is held to the bounds of a single epoch
+        let epochs = if last_redeemed_rewards_epoch == 0 {
+            1
+        } else {
+            std::cmp::max(
+                1,
+                env::epoch_height().saturating_sub(last_redeemed_rewards_epoch),
+            )
+        };
+
+        let (change, max_change_per_epoch) = if reported_balance >= old_balance {
+            (
+                reported_balance - old_balance,
+                self.total_staked * bounds.max_increase_per_epoch,
+            )
+        } else {
+            (
+                old_balance - reported_balance,
+                self.total_staked * bounds.max_decrease_per_epoch,
+            )
+        };
+        let max_change = max_change_per_epoch.saturating_mul(epochs as u128);
+
+        if change > max_change {
+            Some(max_change)
+        } else {
+            None
+        }
+    }
+
+    /// Most NEAR the unstakes of an epoch can take out of the validators
+    pub(crate) fn get_unstake_epoch_cap_amount(&self) -> Option<Balance> {
+        self.unstake_epoch_cap.map(|cap| match cap {
+            UnstakeEpochCap::Absolute { amount } => amount.0,
+            UnstakeEpochCap::ShareOfTotalStaked { share } => {
+                std::cmp::max(self.total_staked * share, MIN_UNSTAKE_EPOCH_CAP)
+            }
+        })
+    }
+
     // Make this return a promise
     pub(crate) fn internal_withdraw(&mut self, amount: Balance) {
         self.assert_withdraw_not_paused();
@@ -309,29 +755,32 @@ impl NearxPool {
 
         require!(amount_to_send > 0, ERROR_NON_POSITIVE_WITHDRAWAL);
 
-        let account = self.internal_get_account(&account_id);
+        let mut account = self.internal_get_account(&account_id);
         require!(
-            account.unstaked_amount >= amount_to_send,
+            account.unstaked_amount() >= amount_to_send,
             ERROR_NOT_ENOUGH_UNSTAKED_AMOUNT_TO_WITHDRAW
         );
         require!(
-            account.withdrawable_epoch_height <= env::epoch_height(),
+            account.withdrawable_amount(env::epoch_height()) >= amount_to_send,
             ERROR_UNSTAKED_AMOUNT_IN_UNBONDING_PERIOD
         );
 
+        // the instant unstake reserve is not available for regular withdrawals
         require!(
-            env::account_balance().saturating_sub(self.min_storage_reserve) >= amount_to_send,
+            env::account_balance()
+                .saturating_sub(self.min_storage_reserve + self.instant_unstake_reserve)
+                >= amount_to_send,
             ERROR_NOT_ENOUGH_BALANCE_FOR_STORAGE
         );
 
-        let mut account = self.internal_get_account(&account_id);
-        account.unstaked_amount -= amount_to_send;
+        account.sub_withdrawable_amount(amount_to_send, env::epoch_height());
 
         let storage_balance_bounds = self.storage_balance_bounds();
-        // If the unstaked amount is less than the minimum required storage amount for storage, then send the remaining amount back to the user
-        if account.unstaked_amount <= storage_balance_bounds.min.0 {
-            amount_to_send += account.unstaked_amount;
-            account.unstaked_amount = 0;
+        // If the withdrawable amount left is less than the minimum required storage amount for storage, then send the remaining amount back to the user
+        let remaining_amount = account.withdrawable_amount(env::epoch_height());
+        if remaining_amount <= storage_balance_bounds.min.0 {
+            amount_to_send += remaining_amount;
+            account.sub_withdrawable_amount(remaining_amount, env::epoch_height());
         }
 
         self.internal_update_account(&account_id, &account);
@@ -339,13 +788,363 @@ impl NearxPool {
         Event::Withdraw {
             account_id: account_id.clone(),
             amount: U128(amount_to_send),
-            new_unstaked_balance: U128(account.unstaked_amount),
+            new_unstaked_balance: U128(account.unstaked_amount()),
+        }
+        .emit();
+
+        Promise::new(account_id).transfer(amount_to_send);
+    }
+
+    pub(crate) fn internal_instant_unstake(&mut self, amount: u128) {
+        self.assert_instant_unstake_not_paused();
+
+        require!(amount > 0, ERROR_NON_POSITIVE_UNSTAKE_AMOUNT);
+
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_get_account(&account_id);
+
+        require!(
+            self.total_staked > 0,
+            ERROR_NOT_ENOUGH_CONTRACT_STAKED_AMOUNT
+        );
+
+        let num_shares = self.num_shares_from_staked_amount_rounded_up(amount);
+        require!(num_shares > 0, ERROR_NON_POSITIVE_UNSTAKING_SHARES);
+        require!(
+            account.stake_shares >= num_shares,
+            ERROR_NOT_ENOUGH_STAKED_AMOUNT_TO_UNSTAKE
+        );
+
+        // the reserve pays out, so round against the user
+        let receive_amount = self.staked_amount_from_num_shares_rounded_down(num_shares);
+        let fee = self.get_instant_unstake_fee_amount(receive_amount);
+        let amount_to_send = receive_amount - fee;
+        require!(
+            amount_to_send > 0,
+            ERROR_NON_POSITIVE_UNSTAKE_RECEVIE_AMOUNT
+        );
+        require!(
+            amount_to_send <= self.instant_unstake_reserve,
+            ERROR_NOT_ENOUGH_INSTANT_UNSTAKE_LIQUIDITY
+        );
+
+        // the fee stays staked and is handed over to the treasury, only the rest is burnt
+        let fee_shares = self.num_shares_from_staked_amount_rounded_down(fee);
+        let burnt_shares = num_shares - fee_shares;
+
+        account.stake_shares -= num_shares;
+        self.internal_update_account(&account_id, &account);
+
+        if fee_shares > 0 {
+            let treasury_account_id = self.treasury_account_id.clone();
+            let mut treasury_account = self.internal_get_account(&treasury_account_id);
+            treasury_account.stake_shares += fee_shares;
+            self.internal_update_account(&treasury_account_id, &treasury_account);
+
+            nep141::FtTransfer {
+                old_owner_id: &account_id,
+                new_owner_id: &treasury_account_id,
+                amount: &U128(fee_shares),
+                memo: Some("instant_unstake_fee"),
+            }
+            .emit();
+        }
+
+        self.total_staked -= amount_to_send;
+        self.total_stake_shares -= burnt_shares;
+
+        // The NEAR backing the burnt NearX is still with the validators. It is unstaked like any
+        // other user unstake, but it is owed to the reserve.
+        let release_epoch = self.internal_schedule_unstake(amount_to_send);
+        self.instant_unstake_reserve -= amount_to_send;
+        let pending_amount = self
+            .instant_unstake_reserve_pending
+            .get(&release_epoch)
+            .unwrap_or(0);
+        self.instant_unstake_reserve_pending
+            .insert(&release_epoch, &(pending_amount + amount_to_send));
+        self.accumulated_instant_unstake_fees += fee;
+
+        Event::InstantUnstake {
+            account_id: account_id.clone(),
+            amount: U128(amount_to_send),
+            fee: U128(fee),
+            fee_shares: U128(fee_shares),
+            burnt_stake_shares: U128(burnt_shares),
+            new_stake_shares: U128(account.stake_shares),
+        }
+        .emit();
+
+        nep141::FtBurn {
+            owner_id: &account_id,
+            amount: &U128(burnt_shares),
+            memo: Some("instant_unstake"),
+        }
+        .emit();
+
+        Event::FtBurn {
+            account_id: account_id.clone(),
+            amount: U128(burnt_shares),
         }
         .emit();
 
         Promise::new(account_id).transfer(amount_to_send);
     }
 
+    /// Moves NEAR withdrawn from a validator back into the instant unstake reserve, up to what
+    /// the reserve is owed for the releases that are due, oldest first. The reserve gets all of
+    /// it back even above the max reserve amount, the owner can withdraw the excess.
+    pub(crate) fn internal_refill_instant_unstake_reserve(&mut self, amount_received: Balance) {
+        let released: Vec<(EpochHeight, Balance)> = self
+            .instant_unstake_reserve_pending
+            .iter()
+            .take_while(|(release_epoch, _)| *release_epoch <= env::epoch_height())
+            .collect();
+
+        let mut refill_amount = 0;
+        for (release_epoch, amount) in released {
+            let taken = std::cmp::min(amount, amount_received - refill_amount);
+            if taken == 0 {
+                break;
+            }
+            if taken == amount {
+                self.instant_unstake_reserve_pending.remove(&release_epoch);
+            } else {
+                self.instant_unstake_reserve_pending
+                    .insert(&release_epoch, &(amount - taken));
+            }
+            refill_amount += taken;
+        }
+        if refill_amount == 0 {
+            return;
+        }
+
+        self.instant_unstake_reserve += refill_amount;
+
+        Event::InstantUnstakeReserveRefilled {
+            amount: U128(refill_amount),
+            new_reserve: U128(self.instant_unstake_reserve),
+        }
+        .emit();
+    }
+
+    /// The fee is at max fee when the reserve is empty and goes down linearly to min fee
+    /// as the reserve fills up to the max reserve amount.
+    pub(crate) fn get_instant_unstake_fee_amount(&self, amount: Balance) -> Balance {
+        let min_fee = amount * self.instant_unstake_min_fee;
+        let max_fee = amount * self.instant_unstake_max_fee;
+        if self.max_instant_unstake_reserve == 0 {
+            return max_fee;
+        }
+
+        let reserve = std::cmp::min(
+            self.instant_unstake_reserve,
+            self.max_instant_unstake_reserve,
+        );
+        max_fee - proportional(max_fee - min_fee, reserve, self.max_instant_unstake_reserve)
+    }
+
+    /// Stakes with all the given validators at once. Every deposit_and_stake has its own
+    /// callback, so a failure is only rolled back for the validator it happened on
+    pub(crate) fn internal_joint_staking_epoch(
+        &mut self,
+        validators_to_stake: Vec<(ValidatorInfo, Balance)>,
+    ) {
+        for (validator, amount_to_stake) in validators_to_stake {
+            self.internal_staking_epoch(validator, amount_to_stake);
+        }
+    }
+
+    /// Stakes `amount` of the reconciled stake amount with the validator
+    pub(crate) fn internal_staking_epoch(
+        &mut self,
+        validator: ValidatorInfo,
+        amount_to_stake: u128,
+    ) {
+        // update internal state
+        self.reconciled_epoch_stake_amount = self
+            .reconciled_epoch_stake_amount
+            .checked_sub(amount_to_stake)
+            .unwrap();
+
+        Event::StakingEpochAttempt {
+            validator_id: validator.account_id.clone(),
+            amount: U128(amount_to_stake),
+        }
+        .emit();
+
+        // do staking on selected validator
+        ext_staking_pool::ext(validator.account_id.clone())
+            .with_attached_deposit(amount_to_stake)
+            .with_static_gas(gas::ON_STAKE_POOL_DEPOSIT_AND_STAKE)
+            .deposit_and_stake()
+            .then(
+                ext_staking_pool_callback::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(gas::ON_STAKE_POOL_DEPOSIT_AND_STAKE_CB)
+                    .on_stake_pool_deposit_and_stake(
+                        validator.account_id,
+                        amount_to_stake,
+                        Some(env::predecessor_account_id()),
+                    ),
+            );
+    }
+
+    /// Unstakes `amount` of the reconciled unstake amount from the validator
+    pub(crate) fn internal_unstaking_epoch(
+        &mut self,
+        mut validator_info: ValidatorInfo,
+        amount_to_unstake: u128,
+    ) {
+        self.reconciled_epoch_unstake_amount -= amount_to_unstake;
+        validator_info.staked -= amount_to_unstake;
+        validator_info.last_unstake_start_epoch = validator_info.unstake_start_epoch;
+        validator_info.unstake_start_epoch = env::epoch_height();
+
+        self.internal_update_validator(&validator_info.account_id, &validator_info);
+
+        ext_staking_pool::ext(validator_info.account_id.clone())
+            .with_static_gas(gas::ON_STAKE_POOL_UNSTAKE)
+            .with_attached_deposit(NO_DEPOSIT)
+            .unstake(U128(amount_to_unstake))
+            .then(
+                ext_staking_pool_callback::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(gas::ON_STAKE_POOL_UNSTAKE_CB)
+                    .on_stake_pool_unstake(
+                        validator_info.account_id.clone(),
+                        amount_to_unstake,
+                        Some(env::predecessor_account_id()),
+                    ),
+            );
+
+        Event::UnstakingEpochAttempt {
+            validator_id: validator_info.account_id,
+            amount: U128(amount_to_unstake),
+        }
+        .emit();
+    }
+
+    /// Fetches the validator staked balance to distribute the rewards earned since the last call
+    pub(crate) fn internal_autocompounding_epoch(&mut self, validator_info: ValidatorInfo) {
+        self.internal_record_price_snapshot();
+
+        log!(
+            "Fetching total balance from the staking pool {}",
+            validator_info.account_id
+        );
+
+        ext_staking_pool::ext(validator_info.account_id.clone())
+            .with_attached_deposit(NO_DEPOSIT)
+            .with_static_gas(gas::ON_STAKE_POOL_GET_ACCOUNT_STAKED_BALANCE)
+            .get_account_staked_balance(env::current_account_id())
+            .then(
+                ext_staking_pool_callback::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(
+                        gas::ON_STAKE_POOL_GET_ACCOUNT_STAKED_BALANCE_CB
+                            + self.internal_price_push_gas(),
+                    )
+                    .on_get_sp_staked_balance_for_rewards(
+                        validator_info.account_id.clone(),
+                        Some(env::predecessor_account_id()),
+                    ),
+            );
+
+        Event::AutocompoundingEpochRewardsAttempt {
+            validator_id: validator_info.account_id,
+        }
+        .emit();
+    }
+
+    /// Withdraws the whole released unstaked amount of the validator
+    pub(crate) fn internal_withdraw_epoch(&mut self, mut validator_info: ValidatorInfo) {
+        let amount = validator_info.unstaked_amount;
+
+        validator_info.unstaked_amount -= amount;
+
+        self.internal_update_validator(&validator_info.account_id, &validator_info);
+
+        ext_staking_pool::ext(validator_info.account_id.clone())
+            .with_static_gas(gas::ON_STAKE_POOL_WITHDRAW_ALL)
+            .with_attached_deposit(NO_DEPOSIT)
+            .withdraw(U128(amount))
+            .then(
+                ext_staking_pool_callback::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(gas::ON_STAKE_POOL_WITHDRAW_ALL_CB)
+                    .on_stake_pool_withdraw_all(
+                        validator_info.account_id.clone(),
+                        amount,
+                        Some(env::predecessor_account_id()),
+                    ),
+            );
+
+        Event::WithdrawEpochAttempt {
+            validator_id: validator_info.account_id,
+            amount: U128(amount),
+        }
+        .emit();
+    }
+
+    /// Pays the keeper for `num_actions` useful epoch actions out of the keeper budget,
+    /// up to the per epoch cap. Keepers need to be registered to receive NearX
+    pub(crate) fn internal_reward_keeper(&mut self, keeper_id: &AccountId, num_actions: u32) {
+        if num_actions == 0 || self.keeper_reward_per_action == 0 {
+            return;
+        }
+
+        let mut keeper_account = match self.internal_get_account_option(keeper_id) {
+            Some(account) => account,
+            None => {
+                log!("Keeper {} is not registered, no reward paid", keeper_id);
+                return;
+            }
+        };
+
+        if self.keeper_rewards_epoch != env::epoch_height() {
+            self.keeper_rewards_epoch = env::epoch_height();
+            self.keeper_rewards_paid_in_epoch = 0;
+        }
+
+        let reward = std::cmp::min(
+            self.keeper_reward_per_action * num_actions as u128,
+            self.max_keeper_rewards_per_epoch
+                .saturating_sub(self.keeper_rewards_paid_in_epoch),
+        );
+        let reward_shares = std::cmp::min(
+            self.num_shares_from_staked_amount_rounded_down(reward),
+            self.keeper_budget_shares,
+        );
+        if reward_shares == 0 {
+            return;
+        }
+        let reward = self.staked_amount_from_num_shares_rounded_down(reward_shares);
+
+        self.keeper_budget_shares -= reward_shares;
+        self.keeper_rewards_paid_in_epoch += reward;
+
+        keeper_account.stake_shares += reward_shares;
+        self.internal_update_account(keeper_id, &keeper_account);
+
+        // the keeper budget is held by the contract itself
+        nep141::FtTransfer {
+            old_owner_id: &env::current_account_id(),
+            new_owner_id: keeper_id,
+            amount: &U128(reward_shares),
+            memo: Some("keeper_reward"),
+        }
+        .emit();
+
+        Event::KeeperRewarded {
+            keeper_id: keeper_id.clone(),
+            num_actions,
+            amount: U128(reward),
+            shares: U128(reward_shares),
+        }
+        .emit();
+    }
+
     pub(crate) fn internal_get_validator(&self, validator: &AccountId) -> ValidatorInfo {
         if let Some(val_info) = self.validator_info_map.get(validator) {
             val_info.into_current()
@@ -363,6 +1162,78 @@ impl NearxPool {
             .insert(validator, &validator_info.clone().into());
     }
 
+    /// Pauses a validator that has crossed the delinquency threshold and queues it for draining.
+    /// A validator still in its unbonding period is left untouched, the next delinquent epoch
+    /// pauses it once the unstaked amount has been released.
+    pub(crate) fn internal_auto_pause_if_delinquent(&mut self, validator_info: &mut ValidatorInfo) {
+        if validator_info.paused()
+            || !validator_info.is_delinquent(self.validator_delinquency_threshold)
+            || validator_info.pending_unstake_release()
+        {
+            return;
+        }
+
+        let old_weight = validator_info.weight;
+        self.total_validator_weight -= old_weight;
+        validator_info.weight = 0;
+
+        if !self
+            .validators_to_drain
+            .contains(&validator_info.account_id)
+        {
+            self.validators_to_drain
+                .push(validator_info.account_id.clone());
+        }
+
+        Event::ValidatorAutoPaused {
+            account_id: validator_info.account_id.clone(),
+            old_weight,
+            failed_epoch_actions: validator_info.failed_epoch_actions,
+            zero_reward_epochs: validator_info.zero_reward_epochs,
+        }
+        .emit();
+    }
+
+    /// Council approvals for the action given by current members which have not expired
+    pub(crate) fn internal_get_council_approvals(
+        &self,
+        action: &CouncilAction,
+    ) -> Vec<CouncilApproval> {
+        self.council_approvals
+            .get(action)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|approval| {
+                approval.expires_epoch >= env::epoch_height()
+                    && self.council_members.contains(&approval.approver)
+            })
+            .collect()
+    }
+
+    /// Checks that the council approved the action and uses up the approvals. Does nothing
+    /// while no council is set.
+    pub(crate) fn internal_use_council_approvals(&mut self, action: CouncilAction) {
+        if self.council_threshold == 0 {
+            return;
+        }
+
+        let approvals = self.internal_get_council_approvals(&action);
+        require!(
+            approvals.len() >= self.council_threshold as usize,
+            ERROR_NOT_ENOUGH_COUNCIL_APPROVALS
+        );
+        self.council_approvals.remove(&action);
+
+        Event::CouncilActionExecuted {
+            action,
+            approvers: approvals
+                .into_iter()
+                .map(|approval| approval.approver)
+                .collect(),
+        }
+        .emit();
+    }
+
     pub(crate) fn num_shares_from_staked_amount_rounded_down(&self, amount: Balance) -> u128 {
         // At this point the er will be 1
         if self.total_stake_shares == 0 || self.total_staked == 0 {
@@ -405,37 +1276,175 @@ impl NearxPool {
         .as_u128()
     }
 
+    pub(crate) fn internal_get_account_option(&self, account_id: &AccountId) -> Option<Account> {
+        self.accounts.get(account_id).or_else(|| {
+            self.legacy_accounts
+                .get(account_id)
+                .map(|legacy_account| legacy_account.into_current())
+        })
+    }
+
     pub(crate) fn internal_get_account(&self, account_id: &AccountId) -> Account {
-        self.accounts.get(account_id).unwrap_or_default()
+        self.internal_get_account_option(account_id)
+            .unwrap_or_default()
     }
 
     pub(crate) fn internal_get_account_unwrap(&self, account_id: &AccountId) -> Account {
-        self.accounts
-            .get(account_id)
+        self.internal_get_account_option(account_id)
             .expect("Account is not registered. Please register the account using storage_deposit")
     }
 
     pub(crate) fn internal_update_account(&mut self, account_id: &AccountId, account: &Account) {
         // accounts can only be removed by storage_unregister
         self.accounts.insert(account_id, account);
+        // the account is now stored in the new layout
+        if !self.legacy_accounts.is_empty() {
+            self.legacy_accounts.remove(account_id);
+        }
+    }
+
+    pub(crate) fn internal_remove_account(&mut self, account_id: &AccountId) {
+        self.accounts.remove(account_id);
+        self.legacy_accounts.remove(account_id);
+    }
+
+    /// Average performance score of the active validators which have one
+    pub(crate) fn get_average_performance_score(&self) -> Option<u128> {
+        let scores: Vec<u128> = self
+            .validator_info_map
+            .values()
+            .map(|wrapped_validator| wrapped_validator.into_current())
+            .filter(|validator| !validator.paused())
+            .filter_map(|validator| validator.performance_score)
+            .collect();
+
+        if scores.is_empty() {
+            None
+        } else {
+            Some(scores.iter().sum::<u128>() / scores.len() as u128)
+        }
+    }
+
+    /// Validator weight scaled by its performance relative to the average, kept within the
+    /// owner set multiplier bounds
+    pub(crate) fn get_validator_effective_weight(
+        &self,
+        validator: &ValidatorInfo,
+        average_performance_score: Option<u128>,
+    ) -> u128 {
+        let min_multiplier = PERFORMANCE_MULTIPLIER_PRECISION * self.min_performance_multiplier;
+        let max_multiplier = PERFORMANCE_MULTIPLIER_PRECISION * self.max_performance_multiplier;
+
+        let multiplier = match (validator.performance_score, average_performance_score) {
+            (Some(score), Some(average_score)) if average_score > 0 => {
+                proportional(score, PERFORMANCE_MULTIPLIER_PRECISION, average_score)
+                    .clamp(min_multiplier, max_multiplier)
+            }
+            _ => PERFORMANCE_MULTIPLIER_PRECISION,
+        };
+
+        validator.weight as u128 * multiplier
+    }
+
+    pub(crate) fn get_total_effective_weight(
+        &self,
+        average_performance_score: Option<u128>,
+    ) -> u128 {
+        self.validator_info_map
+            .values()
+            .map(|wrapped_validator| {
+                self.get_validator_effective_weight(
+                    &wrapped_validator.into_current(),
+                    average_performance_score,
+                )
+            })
+            .sum()
     }
 
-    pub(crate) fn get_validator_expected_stake(&self, validator: &ValidatorInfo) -> Balance {
-        if validator.weight == 0 {
+    pub(crate) fn internal_get_validator_expected_stake(
+        &self,
+        validator: &ValidatorInfo,
+        average_performance_score: Option<u128>,
+        total_effective_weight: u128,
+    ) -> Balance {
+        if validator.weight == 0 || total_effective_weight == 0 {
             0
         } else {
-            self.total_staked * (validator.weight as u128) / (self.total_validator_weight as u128)
+            proportional(
+                self.total_staked,
+                self.get_validator_effective_weight(validator, average_performance_score),
+                total_effective_weight,
+            )
         }
     }
 
+    /// Splits `amount` over the `max_validators` validators that are furthest below their
+    /// expected stake, filling up the most under-staked validator first
+    pub(crate) fn get_validators_to_stake(
+        &self,
+        amount: Balance,
+        max_validators: usize,
+    ) -> Vec<(ValidatorInfo, Balance)> {
+        let average_performance_score = self.get_average_performance_score();
+        let total_effective_weight = self.get_total_effective_weight(average_performance_score);
+
+        let mut under_staked_validators: Vec<(ValidatorInfo, Balance)> = self
+            .validator_info_map
+            .values()
+            .filter_map(|wrapped_validator| {
+                let validator = wrapped_validator.into_current();
+                let target_amount = self.internal_get_validator_expected_stake(
+                    &validator,
+                    average_performance_score,
+                    total_effective_weight,
+                );
+                if validator.staked < target_amount {
+                    let delta = target_amount - validator.staked;
+                    Some((validator, delta))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        // stable sort, so ties keep the validator map order like get_validator_to_stake
+        under_staked_validators.sort_by_key(|(_, delta)| std::cmp::Reverse(*delta));
+
+        let mut remaining_amount = amount;
+        let mut validators_to_stake: Vec<(ValidatorInfo, Balance)> = vec![];
+        for (validator, delta) in under_staked_validators.into_iter().take(max_validators) {
+            if remaining_amount == 0 {
+                break;
+            }
+            let amount_to_stake = std::cmp::min(delta, remaining_amount);
+            remaining_amount -= amount_to_stake;
+            validators_to_stake.push((validator, amount_to_stake));
+        }
+
+        // do not leave dust behind for another staking round
+        if remaining_amount <= ONE_NEAR {
+            if let Some((_, amount_to_stake)) = validators_to_stake.last_mut() {
+                *amount_to_stake += remaining_amount;
+            }
+        }
+
+        validators_to_stake
+    }
+
     #[private]
     pub fn get_validator_to_stake(&self, amount: Balance) -> (Option<ValidatorInfo>, Balance) {
         let mut selected_validator = None;
         let mut amount_to_stake: Balance = 0;
 
+        let average_performance_score = self.get_average_performance_score();
+        let total_effective_weight = self.get_total_effective_weight(average_performance_score);
+
         for wrapped_validator in self.validator_info_map.values() {
             let validator = wrapped_validator.into_current();
-            let target_amount = self.get_validator_expected_stake(&validator);
+            let target_amount = self.internal_get_validator_expected_stake(
+                &validator,
+                average_performance_score,
+                total_effective_weight,
+            );
             if validator.staked < target_amount {
                 let delta = std::cmp::min(target_amount - validator.staked, amount);
                 if delta > amount_to_stake {
@@ -455,8 +1464,6 @@ impl NearxPool {
 
     #[private]
     pub fn get_validator_to_unstake(&self) -> (Option<ValidatorInfo>, u128) {
-        let mut max_validator_stake_amount: u128 = 0;
-        let mut current_validator: Option<ValidatorInfo> = None;
         let mut total_unstakable_amount: u128 = 0;
         let mut unstake_full_amount_from_private_validators = false;
 
@@ -478,24 +1485,141 @@ impl NearxPool {
             unstake_full_amount_from_private_validators = true;
         }
 
-        for wrapped_validator in self.validator_info_map.values() {
-            let validator = wrapped_validator.into_current();
-            if !validator.pending_unstake_release() && !validator.paused() {
-                let mut validator_staked_amount = validator.max_unstakable_limit;
-                if unstake_full_amount_from_private_validators {
-                    validator_staked_amount = validator.staked;
+        let unstakable_validators: Vec<(ValidatorInfo, u128)> = self
+            .validator_info_map
+            .values()
+            .map(|wrapped_validator| wrapped_validator.into_current())
+            .filter(|validator| !validator.pending_unstake_release() && !validator.paused())
+            .map(|validator| {
+                let validator_staked_amount = if unstake_full_amount_from_private_validators {
+                    validator.staked
+                } else {
+                    validator.max_unstakable_limit
+                };
+                (validator, validator_staked_amount)
+            })
+            .collect();
+
+        match self.unstake_strategy {
+            UnstakeStrategy::LargestFirst => {
+                Self::get_largest_unstakable_validator(unstakable_validators)
+            }
+            UnstakeStrategy::WeightDeviationFirst => {
+                let average_performance_score = self.get_average_performance_score();
+                let total_effective_weight =
+                    self.get_total_effective_weight(average_performance_score);
+
+                let mut max_deviation: i128 = i128::MIN;
+                let mut current_validator: Option<(ValidatorInfo, u128)> = None;
+                for (validator, validator_staked_amount) in unstakable_validators {
+                    if validator_staked_amount == 0 {
+                        continue;
+                    }
+                    let deviation = validator.staked as i128
+                        - self.internal_get_validator_expected_stake(
+                            &validator,
+                            average_performance_score,
+                            total_effective_weight,
+                        ) as i128;
+                    if deviation > max_deviation {
+                        max_deviation = deviation;
+                        current_validator = Some((validator, validator_staked_amount));
+                    }
                 }
 
-                if validator_staked_amount.gt(&max_validator_stake_amount) {
-                    max_validator_stake_amount = validator_staked_amount;
-                    current_validator = Some(validator)
+                match current_validator {
+                    Some((validator, amount)) => (Some(validator), amount),
+                    None => (None, 0),
                 }
             }
+            UnstakeStrategy::ProRata => {
+                let total_amount: u128 = unstakable_validators
+                    .iter()
+                    .map(|(_, validator_staked_amount)| validator_staked_amount)
+                    .sum();
+
+                let (current_validator, validator_staked_amount) =
+                    Self::get_largest_unstakable_validator(unstakable_validators);
+                if current_validator.is_none() {
+                    return (None, 0);
+                }
+
+                // the validators already unstaked from drop out of the total, so every later
+                // call takes the same share of what is left
+                let mut amount = proportional(
+                    self.reconciled_epoch_unstake_amount,
+                    validator_staked_amount,
+                    total_amount,
+                );
+                if amount == 0 {
+                    amount = self.reconciled_epoch_unstake_amount;
+                }
+
+                (
+                    current_validator,
+                    std::cmp::min(amount, validator_staked_amount),
+                )
+            }
+        }
+    }
+
+    fn get_largest_unstakable_validator(
+        unstakable_validators: Vec<(ValidatorInfo, u128)>,
+    ) -> (Option<ValidatorInfo>, u128) {
+        let mut max_validator_stake_amount: u128 = 0;
+        let mut current_validator: Option<ValidatorInfo> = None;
+
+        for (validator, validator_staked_amount) in unstakable_validators {
+            if validator_staked_amount.gt(&max_validator_stake_amount) {
+                max_validator_stake_amount = validator_staked_amount;
+                current_validator = Some(validator)
+            }
         }
 
         (current_validator, max_validator_stake_amount)
     }
 
+    /// Picks the validator furthest above its expected stake that can be unstaked from now,
+    /// along with its excess capped by the max unstakable limit
+    pub(crate) fn get_validator_to_rebalance(&self) -> (Option<ValidatorInfo>, Balance) {
+        let average_performance_score = self.get_average_performance_score();
+        let total_effective_weight = self.get_total_effective_weight(average_performance_score);
+
+        let mut max_excess: u128 = 0;
+        let mut current_validator: Option<(ValidatorInfo, u128)> = None;
+        for wrapped_validator in self.validator_info_map.values() {
+            let validator = wrapped_validator.into_current();
+
+            // paused validators get drained instead, and unstaking while an unstaked amount is
+            // still held by the validator would restart its unbonding period
+            if validator.paused()
+                || validator.pending_unstake_release()
+                || validator.unstaked_amount > 0
+            {
+                continue;
+            }
+
+            let excess =
+                validator
+                    .staked
+                    .saturating_sub(self.internal_get_validator_expected_stake(
+                        &validator,
+                        average_performance_score,
+                        total_effective_weight,
+                    ));
+            let amount = std::cmp::min(excess, validator.max_unstakable_limit);
+            if amount >= MIN_REBALANCE_AMOUNT && excess > max_excess {
+                max_excess = excess;
+                current_validator = Some((validator, amount));
+            }
+        }
+
+        match current_validator {
+            Some((validator, amount)) => (Some(validator), amount),
+            None => (None, 0),
+        }
+    }
+
     #[private]
     pub fn get_unstake_release_epoch(&self, amount: u128) -> EpochHeight {
         let mut available_amount: Balance = 0;
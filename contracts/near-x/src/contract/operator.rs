@@ -2,11 +2,12 @@ use crate::errors::*;
 use crate::events::*;
 use crate::utils::*;
 use crate::{
-    constants::{gas, NO_DEPOSIT},
+    constants::{gas, MAX_VALIDATORS_TO_STAKE_IN_EPOCH, NO_DEPOSIT},
     contract::*,
     state::*,
 };
-use near_sdk::{env, log, near_bindgen, require};
+use near_sdk::json_types::U64;
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require};
 
 #[near_bindgen]
 impl NearxPool {
@@ -29,51 +30,42 @@ impl NearxPool {
             return false;
         }
 
-        let validator_to_stake_info =
-            self.get_validator_to_stake(self.reconciled_epoch_stake_amount);
+        // every validator staked with needs its own deposit_and_stake and callback
+        let max_validators = std::cmp::min(
+            MAX_VALIDATORS_TO_STAKE_IN_EPOCH as u64,
+            (env::prepaid_gas().0 - gas::STAKING_EPOCH.0)
+                / (gas::ON_STAKE_POOL_DEPOSIT_AND_STAKE.0
+                    + gas::ON_STAKE_POOL_DEPOSIT_AND_STAKE_CB.0),
+        );
+        let validators_to_stake = self
+            .get_validators_to_stake(self.reconciled_epoch_stake_amount, max_validators as usize);
         require!(
-            validator_to_stake_info.0.is_some(),
+            !validators_to_stake.is_empty(),
             ERROR_NO_VALIDATOR_AVAILABLE_TO_STAKE
         );
 
-        let validator = validator_to_stake_info.0.unwrap();
-
-        let amount_to_stake = validator_to_stake_info.1;
+        let total_amount_to_stake: Balance = validators_to_stake
+            .iter()
+            .map(|(_, amount_to_stake)| amount_to_stake)
+            .sum();
 
         require!(
-            env::account_balance() >= amount_to_stake + self.min_storage_reserve,
+            env::account_balance() >= total_amount_to_stake + self.min_storage_reserve,
             ERROR_MIN_BALANCE_FOR_CONTRACT_STORAGE
         );
 
-        // update internal state
-        self.reconciled_epoch_stake_amount = self
-            .reconciled_epoch_stake_amount
-            .checked_sub(amount_to_stake)
-            .unwrap();
-
-        // do staking on selected validator
-        ext_staking_pool::ext(validator.account_id.clone())
-            .with_attached_deposit(amount_to_stake)
-            .with_static_gas(gas::ON_STAKE_POOL_DEPOSIT_AND_STAKE)
-            .deposit_and_stake()
-            .then(
-                ext_staking_pool_callback::ext(env::current_account_id())
-                    .with_attached_deposit(NO_DEPOSIT)
-                    .with_static_gas(gas::ON_STAKE_POOL_DEPOSIT_AND_STAKE_CB)
-                    .on_stake_pool_deposit_and_stake(validator.account_id.clone(), amount_to_stake),
-            );
-
-        Event::StakingEpochAttempt {
-            validator_id: validator.account_id,
-            amount: U128(amount_to_stake),
-        }
-        .emit();
+        self.internal_joint_staking_epoch(validators_to_stake);
 
         true
     }
 
     #[private]
-    pub fn on_stake_pool_deposit_and_stake(&mut self, validator_id: AccountId, amount: Balance) {
+    pub fn on_stake_pool_deposit_and_stake(
+        &mut self,
+        validator_id: AccountId,
+        amount: Balance,
+        keeper_id: Option<AccountId>,
+    ) {
         let mut validator_info = self.internal_get_validator(&validator_id);
         if is_promise_success() {
             validator_info.staked += amount;
@@ -82,20 +74,28 @@ impl NearxPool {
             // at any time. Only funds staked directly with the validator is not unstakable
             // initially all validators should have a non zero max unstakable limit
             validator_info.max_unstakable_limit += amount;
+            validator_info.failed_epoch_actions = 0;
 
             Event::StakingEpochCallbackSuccess {
                 validator_id: validator_id.clone(),
                 amount: U128(amount),
             }
             .emit();
+
+            if let Some(keeper_id) = keeper_id {
+                self.internal_reward_keeper(&keeper_id, 1);
+            }
         } else {
             self.reconciled_epoch_stake_amount += amount;
+            validator_info.failed_epoch_actions += 1;
 
             Event::StakingEpochCallbackFailed {
                 validator_id: validator_id.clone(),
                 amount: U128(amount),
             }
             .emit();
+
+            self.internal_auto_pause_if_delinquent(&mut validator_info);
         }
 
         self.internal_update_validator(&validator_id, &validator_info);
@@ -106,7 +106,8 @@ impl NearxPool {
 
         let min_gas = gas::AUTOCOMPOUNDING_EPOCH
             + gas::ON_STAKE_POOL_GET_ACCOUNT_STAKED_BALANCE
-            + gas::ON_STAKE_POOL_GET_ACCOUNT_STAKED_BALANCE_CB;
+            + gas::ON_STAKE_POOL_GET_ACCOUNT_STAKED_BALANCE_CB
+            + self.internal_price_push_gas();
         require!(
             env::prepaid_gas() >= min_gas,
             format!("{}. require at least {:?}", ERROR_NOT_ENOUGH_GAS, min_gas)
@@ -124,44 +125,78 @@ impl NearxPool {
             return;
         }
 
-        log!(
-            "Fetching total balance from the staking pool {}",
-            validator_info.account_id
-        );
-
-        ext_staking_pool::ext(validator_info.account_id.clone())
-            .with_attached_deposit(NO_DEPOSIT)
-            .with_static_gas(gas::ON_STAKE_POOL_GET_ACCOUNT_STAKED_BALANCE)
-            .get_account_staked_balance(env::current_account_id())
-            .then(
-                ext_staking_pool_callback::ext(env::current_account_id())
-                    .with_attached_deposit(NO_DEPOSIT)
-                    .with_static_gas(gas::ON_STAKE_POOL_GET_ACCOUNT_STAKED_BALANCE_CB)
-                    .on_get_sp_staked_balance_for_rewards(validator_info.account_id),
-            );
-
-        Event::AutocompoundingEpochRewardsAttempt {
-            validator_id: validator,
-        }
-        .emit();
+        self.internal_autocompounding_epoch(validator_info);
     }
 
     #[private]
     pub fn on_get_sp_staked_balance_for_rewards(
         &mut self,
         validator_id: AccountId,
+        keeper_id: Option<AccountId>,
         #[callback] total_staked_balance: U128,
     ) -> PromiseOrValue<bool> {
         let mut validator_info = self.internal_get_validator(&validator_id);
 
+        let last_redeemed_rewards_epoch = validator_info.last_redeemed_rewards_epoch;
         validator_info.last_redeemed_rewards_epoch = env::epoch_height();
 
+        // only the first autocompounding of the validator in an epoch that brings in rewards
+        // is worth a keeper reward
+        let keeper_to_reward =
+            keeper_id.filter(|_| last_redeemed_rewards_epoch != env::epoch_height());
+
         //new_total_balance has the new staked amount for this pool
         let new_total_balance = total_staked_balance.0;
 
+        // the latest report replaces a quarantined one
+        self.quarantined_balances.remove(&validator_info.account_id);
+        if let Some(max_change) = self.internal_exceeded_exchange_rate_bound(
+            validator_info.staked,
+            new_total_balance,
+            last_redeemed_rewards_epoch,
+        ) {
+            self.quarantined_balances.insert(
+                &validator_info.account_id,
+                &QuarantinedBalance {
+                    staked: validator_info.staked,
+                    reported: new_total_balance,
+                    epoch: env::epoch_height(),
+                    unstaked: validator_info.unstaked_amount,
+                    reported_unstaked: None,
+                },
+            );
+            self.internal_update_validator(&validator_info.account_id, &validator_info);
+
+            Event::ValidatorBalanceQuarantined {
+                validator_id: validator_info.account_id,
+                staked: U128(validator_info.staked),
+                reported: U128(new_total_balance),
+                max_change: U128(max_change),
+            }
+            .emit();
+
+            return PromiseOrValue::Value(false);
+        }
+
         //compute rewards, as new balance minus old balance
         let rewards = new_total_balance.saturating_sub(validator_info.staked);
 
+        // the first autocompounding has no reference epoch to measure the yield against
+        if last_redeemed_rewards_epoch > 0 {
+            validator_info.update_performance_score(
+                rewards,
+                env::epoch_height().saturating_sub(last_redeemed_rewards_epoch),
+            );
+        }
+
+        // a validator with nothing staked is not expected to earn anything
+        if rewards > 0 {
+            validator_info.zero_reward_epochs = 0;
+        } else if validator_info.staked > 0 {
+            validator_info.zero_reward_epochs += 1;
+            self.internal_auto_pause_if_delinquent(&mut validator_info);
+        }
+
         self.internal_update_validator(&validator_info.account_id, &validator_info);
 
         Event::AutocompoundingEpochRewards {
@@ -173,38 +208,92 @@ impl NearxPool {
         .emit();
 
         if rewards > 0 {
-            //updated accumulated_staked_rewards value for the contract
-            self.accumulated_staked_rewards += rewards;
-            //updated new "staked" value for this pool
-            validator_info.staked = new_total_balance;
-            // consider rewards to unstakable since its excess rewards and rewards get distributed to all users
-            validator_info.max_unstakable_limit = validator_info.max_unstakable_limit + rewards;
-
-            let operator_fee = rewards * self.rewards_fee;
-            log!("operator fee is {}", operator_fee);
-            self.total_staked += rewards;
-            let treasury_account_shares =
-                self.num_shares_from_staked_amount_rounded_down(operator_fee);
+            PromiseOrValue::Value(self.internal_distribute_rewards(
+                &mut validator_info,
+                new_total_balance,
+                keeper_to_reward,
+            ))
+        } else {
+            // drops past the rounding of the staking pool are losses, the exchange rate bounds
+            // only decide above whether they wait in quarantine first
+            if new_total_balance + 10000 < validator_info.staked {
+                self.internal_book_validator_loss(&mut validator_info, new_total_balance);
+            }
+            PromiseOrValue::Value(false)
+        }
+    }
 
-            self.internal_update_validator(&validator_info.account_id, &validator_info);
+    /// Books a quarantined validator balance report, increases are distributed as rewards and
+    /// drops are booked as losses
+    #[payable]
+    pub fn confirm_quarantined_balance(&mut self, validator_id: AccountId) {
+        self.assert_role(Role::ValidatorManager);
+        assert_one_yocto();
+
+        // booking rewards pushes the new price to the price subscribers
+        let min_gas = gas::CONFIRM_QUARANTINED_BALANCE + self.internal_price_push_gas();
+        require!(
+            env::prepaid_gas() >= min_gas,
+            format!("{}. require at least {:?}", ERROR_NOT_ENOUGH_GAS, min_gas)
+        );
 
-            if treasury_account_shares > 0 {
-                // Mint shares for the treasury account
-                let treasury_account_id = self.treasury_account_id.clone();
-                let mut treasury_account = self.internal_get_account(&treasury_account_id);
-                treasury_account.stake_shares += treasury_account_shares;
-                self.total_stake_shares += treasury_account_shares;
-                self.internal_update_account(&treasury_account_id, &treasury_account);
+        let quarantined_balance = self
+            .quarantined_balances
+            .remove(&validator_id)
+            .unwrap_or_else(|| panic!("{}", ERROR_NO_QUARANTINED_BALANCE));
+        let mut validator_info = self.internal_get_validator(&validator_id);
+        require!(
+            validator_info.staked == quarantined_balance.staked
+                && (quarantined_balance.reported_unstaked.is_none()
+                    || validator_info.unstaked_amount == quarantined_balance.unstaked),
+            ERROR_QUARANTINED_BALANCE_STALE
+        );
 
-                PromiseOrValue::Value(true)
-            } else {
-                PromiseOrValue::Value(false)
-            }
-        } else {
-            PromiseOrValue::Value(false)
+        Event::QuarantinedBalanceConfirmed {
+            validator_id,
+            staked: U128(quarantined_balance.staked),
+            reported: U128(quarantined_balance.reported),
+        }
+        .emit();
+
+        if let Some(reported_unstaked) = quarantined_balance.reported_unstaked {
+            self.internal_sync_validator_balance(
+                &mut validator_info,
+                quarantined_balance.reported,
+                reported_unstaked,
+            );
+        } else if quarantined_balance.reported > validator_info.staked {
+            validator_info.zero_reward_epochs = 0;
+            self.internal_distribute_rewards(
+                &mut validator_info,
+                quarantined_balance.reported,
+                None,
+            );
+        } else if quarantined_balance.reported < validator_info.staked {
+            self.internal_book_validator_loss(&mut validator_info, quarantined_balance.reported);
         }
     }
 
+    /// Drops a quarantined validator balance report, the next autocompounding of the validator
+    /// reports its balance again
+    #[payable]
+    pub fn reject_quarantined_balance(&mut self, validator_id: AccountId) {
+        self.assert_role(Role::ValidatorManager);
+        assert_one_yocto();
+
+        let quarantined_balance = self
+            .quarantined_balances
+            .remove(&validator_id)
+            .unwrap_or_else(|| panic!("{}", ERROR_NO_QUARANTINED_BALANCE));
+
+        Event::QuarantinedBalanceRejected {
+            validator_id,
+            staked: U128(quarantined_balance.staked),
+            reported: U128(quarantined_balance.reported),
+        }
+        .emit();
+    }
+
     pub fn unstaking_epoch(&mut self) -> bool {
         self.assert_unstaking_epoch_not_paused();
 
@@ -230,7 +319,7 @@ impl NearxPool {
             ERROR_NO_VALIDATOR_AVAILABLE_FOR_UNSTAKE
         );
 
-        let mut validator_info = validator_to_unstake.unwrap();
+        let validator_info = validator_to_unstake.unwrap();
 
         let amount_to_unstake = std::cmp::min(
             validator_unstakable_amount,
@@ -242,7 +331,80 @@ impl NearxPool {
             ERROR_CANNOT_UNSTAKED_MORE_THAN_STAKED_AMOUNT
         );
 
-        self.reconciled_epoch_unstake_amount -= amount_to_unstake;
+        self.internal_unstaking_epoch(validator_info, amount_to_unstake);
+
+        true
+    }
+
+    #[private]
+    pub fn on_stake_pool_unstake(
+        &mut self,
+        validator_id: AccountId,
+        amount_to_unstake: u128,
+        keeper_id: Option<AccountId>,
+    ) {
+        let mut validator = self.internal_get_validator(&validator_id);
+
+        if is_promise_success() {
+            validator.unstaked_amount += amount_to_unstake;
+
+            // we might unstake more then the max_unstakable limit at times. This will happen when the max unstakable limit for the
+            // validators has not been correctly updated. Ideally this case should never come
+            // but we do not want to risk protocol insolvency for this
+            validator.max_unstakable_limit = validator
+                .max_unstakable_limit
+                .saturating_sub(amount_to_unstake);
+            validator.failed_epoch_actions = 0;
+
+            Event::UnstakingEpochCallbackSuccess {
+                validator_id: validator_id.clone(),
+                amount: U128(amount_to_unstake),
+            }
+            .emit();
+
+            if let Some(keeper_id) = keeper_id {
+                self.internal_reward_keeper(&keeper_id, 1);
+            }
+        } else {
+            self.reconciled_epoch_unstake_amount += amount_to_unstake;
+            validator.staked += amount_to_unstake;
+            validator.unstake_start_epoch = validator.last_unstake_start_epoch;
+            validator.failed_epoch_actions += 1;
+
+            Event::UnstakingEpochCallbackFailed {
+                validator_id: validator_id.clone(),
+                amount: U128(amount_to_unstake),
+            }
+            .emit();
+
+            self.internal_auto_pause_if_delinquent(&mut validator);
+        }
+
+        self.internal_update_validator(&validator_id, &validator);
+    }
+
+    // unstakes the excess of the most over-target validator, the withdrawn amount gets restaked
+    // into under-target validators by staking_epoch
+    pub fn rebalance_epoch(&mut self) -> bool {
+        self.assert_operator_or_owner();
+        self.assert_rebalance_epoch_not_paused();
+
+        let min_gas =
+            gas::REBALANCE_EPOCH + gas::ON_STAKE_POOL_UNSTAKE + gas::ON_STAKE_POOL_UNSTAKE_CB;
+        require!(
+            env::prepaid_gas() >= min_gas,
+            format!("{}. require at least {:?}", ERROR_NOT_ENOUGH_GAS, min_gas)
+        );
+
+        let (validator_to_rebalance, amount_to_unstake) = self.get_validator_to_rebalance();
+        let mut validator_info = match validator_to_rebalance {
+            Some(validator_info) => validator_info,
+            None => {
+                log!("No validator to rebalance");
+                return false;
+            }
+        };
+
         validator_info.staked -= amount_to_unstake;
         validator_info.last_unstake_start_epoch = validator_info.unstake_start_epoch;
         validator_info.unstake_start_epoch = env::epoch_height();
@@ -257,10 +419,13 @@ impl NearxPool {
                 ext_staking_pool_callback::ext(env::current_account_id())
                     .with_attached_deposit(NO_DEPOSIT)
                     .with_static_gas(gas::ON_STAKE_POOL_UNSTAKE_CB)
-                    .on_stake_pool_unstake(validator_info.account_id.clone(), amount_to_unstake),
+                    .on_stake_pool_rebalance_unstake(
+                        validator_info.account_id.clone(),
+                        amount_to_unstake,
+                    ),
             );
 
-        Event::UnstakingEpochAttempt {
+        Event::RebalanceEpochAttempt {
             validator_id: validator_info.account_id,
             amount: U128(amount_to_unstake),
         }
@@ -270,30 +435,34 @@ impl NearxPool {
     }
 
     #[private]
-    pub fn on_stake_pool_unstake(&mut self, validator_id: AccountId, amount_to_unstake: u128) {
+    pub fn on_stake_pool_rebalance_unstake(
+        &mut self,
+        validator_id: AccountId,
+        amount_to_unstake: u128,
+    ) {
         let mut validator = self.internal_get_validator(&validator_id);
 
         if is_promise_success() {
             validator.unstaked_amount += amount_to_unstake;
-
-            // we might unstake more then the max_unstakable limit at times. This will happen when the max unstakable limit for the
-            // validators has not been correctly updated. Ideally this case should never come
-            // but we do not want to risk protocol insolvency for this
             validator.max_unstakable_limit = validator
                 .max_unstakable_limit
                 .saturating_sub(amount_to_unstake);
 
-            Event::UnstakingEpochCallbackSuccess {
+            // restaked once withdraw_epoch gets it back from the validator
+            let rebalance_amount = self.rebalance_unstakes.get(&validator_id).unwrap_or(0);
+            self.rebalance_unstakes
+                .insert(&validator_id, &(rebalance_amount + amount_to_unstake));
+
+            Event::RebalanceEpochCallbackSuccess {
                 validator_id: validator_id.clone(),
                 amount: U128(amount_to_unstake),
             }
             .emit();
         } else {
-            self.reconciled_epoch_unstake_amount += amount_to_unstake;
             validator.staked += amount_to_unstake;
             validator.unstake_start_epoch = validator.last_unstake_start_epoch;
 
-            Event::UnstakingEpochCallbackFailed {
+            Event::RebalanceEpochCallbackFailed {
                 validator_id: validator_id.clone(),
                 amount: U128(amount_to_unstake),
             }
@@ -315,7 +484,7 @@ impl NearxPool {
             format!("{}. require at least {:?}", ERROR_NOT_ENOUGH_GAS, min_gas)
         );
 
-        let mut validator_info = self.internal_get_validator(&validator);
+        let validator_info = self.internal_get_validator(&validator);
 
         // If we run epoch_withdraw before drain_withdraw for a validator, we will loose the drained funds.
         // So don't run epoch_withdraw for a paused validator
@@ -330,32 +499,16 @@ impl NearxPool {
             ERROR_VALIDATOR_UNSTAKE_STILL_UNBONDING
         );
 
-        let amount = validator_info.unstaked_amount;
-
-        validator_info.unstaked_amount -= amount;
-
-        self.internal_update_validator(&validator, &validator_info);
-
-        ext_staking_pool::ext(validator_info.account_id.clone())
-            .with_static_gas(gas::ON_STAKE_POOL_WITHDRAW_ALL)
-            .with_attached_deposit(NO_DEPOSIT)
-            .withdraw(U128(amount))
-            .then(
-                ext_staking_pool_callback::ext(env::current_account_id())
-                    .with_attached_deposit(NO_DEPOSIT)
-                    .with_static_gas(gas::ON_STAKE_POOL_WITHDRAW_ALL_CB)
-                    .on_stake_pool_withdraw_all(validator_info.account_id, amount),
-            );
-
-        Event::WithdrawEpochAttempt {
-            validator_id: validator,
-            amount: U128(amount),
-        }
-        .emit();
+        self.internal_withdraw_epoch(validator_info);
     }
 
     #[private]
-    pub fn on_stake_pool_withdraw_all(&mut self, validator_id: AccountId, amount: u128) {
+    pub fn on_stake_pool_withdraw_all(
+        &mut self,
+        validator_id: AccountId,
+        amount: u128,
+        keeper_id: Option<AccountId>,
+    ) {
         if !is_promise_success() {
             let mut validator_info = self.internal_get_validator(&validator_id);
             validator_info.unstaked_amount += amount;
@@ -367,11 +520,32 @@ impl NearxPool {
             }
             .emit();
         } else {
+            // the part unstaked by rebalance_epoch is not owed to any user, stake it again
+            let mut amount_received = amount;
+            if let Some(rebalance_amount) = self.rebalance_unstakes.remove(&validator_id) {
+                let amount_to_restake = std::cmp::min(rebalance_amount, amount);
+                self.user_amount_to_stake_in_epoch += amount_to_restake;
+                amount_received -= amount_to_restake;
+
+                Event::RebalanceRestake {
+                    validator_id: validator_id.clone(),
+                    amount: U128(amount_to_restake),
+                }
+                .emit();
+            }
+
+            // the instant unstake reserve only takes back what has actually been withdrawn
+            self.internal_refill_instant_unstake_reserve(amount_received);
+
             Event::WithdrawEpochCallbackSuccess {
                 validator_id,
                 amount: U128(amount),
             }
             .emit();
+
+            if let Some(keeper_id) = keeper_id {
+                self.internal_reward_keeper(&keeper_id, 1);
+            }
         }
     }
 
@@ -411,34 +585,60 @@ impl NearxPool {
         let mut validator = self.internal_get_validator(&validator_id);
 
         let new_total_balance = account.staked_balance.0 + account.unstaked_balance.0;
-        require!(
-            abs_diff_eq(new_total_balance, validator.total_balance(), 10000),
-            ERROR_VALIDATOR_TOTAL_BALANCE_OUT_OF_SYNC
-        );
-
-        require!(
-            abs_diff_eq(account.staked_balance.0, validator.staked, 5000),
-            ERROR_VALIDATOR_STAKED_BALANCE_OUT_OF_SYNC
-        );
-        require!(
-            abs_diff_eq(account.unstaked_balance.0, validator.unstaked_amount, 5000),
-            ERROR_VALIDATOR_UNSTAKED_BALANCE_OUT_OF_SYNC
-        );
+        let old_total_balance = validator.total_balance();
+
+        // a validator balance short by more than the rounding of the staking pool lost part of
+        // the stake, e.g. to slashing
+        let has_shortfall = old_total_balance > new_total_balance + 10000;
+        if has_shortfall {
+            // drops beyond the exchange rate bounds wait for the validator manager, like the
+            // autocompounding reports
+            if let Some(max_change) = self.internal_exceeded_exchange_rate_bound(
+                old_total_balance,
+                new_total_balance,
+                validator.last_redeemed_rewards_epoch,
+            ) {
+                self.quarantined_balances.insert(
+                    &validator_id,
+                    &QuarantinedBalance {
+                        staked: validator.staked,
+                        reported: account.staked_balance.0,
+                        epoch: env::epoch_height(),
+                        unstaked: validator.unstaked_amount,
+                        reported_unstaked: Some(account.unstaked_balance.0),
+                    },
+                );
+
+                Event::ValidatorBalanceQuarantined {
+                    validator_id,
+                    staked: U128(old_total_balance),
+                    reported: U128(new_total_balance),
+                    max_change: U128(max_change),
+                }
+                .emit();
+                return;
+            }
+        } else {
+            require!(
+                abs_diff_eq(new_total_balance, old_total_balance, 10000),
+                ERROR_VALIDATOR_TOTAL_BALANCE_OUT_OF_SYNC
+            );
 
-        Event::BalanceSyncedFromValidator {
-            validator_id: validator_id.clone(),
-            old_staked_balance: U128(validator.staked),
-            old_unstaked_balance: U128(validator.unstaked_amount),
-            staked_balance: account.staked_balance,
-            unstaked_balance: account.unstaked_balance,
+            require!(
+                abs_diff_eq(account.staked_balance.0, validator.staked, 5000),
+                ERROR_VALIDATOR_STAKED_BALANCE_OUT_OF_SYNC
+            );
+            require!(
+                abs_diff_eq(account.unstaked_balance.0, validator.unstaked_amount, 5000),
+                ERROR_VALIDATOR_UNSTAKED_BALANCE_OUT_OF_SYNC
+            );
         }
-        .emit();
 
-        // update balance
-        validator.staked = account.staked_balance.0;
-        validator.unstaked_amount = account.unstaked_balance.0;
-
-        self.internal_update_validator(&validator_id, &validator);
+        self.internal_sync_validator_balance(
+            &mut validator,
+            account.staked_balance.0,
+            account.unstaked_balance.0,
+        );
     }
 
     #[private]
@@ -448,6 +648,37 @@ impl NearxPool {
         }
         self.last_reconcilation_epoch = env::epoch_height();
 
+        self.internal_record_price_snapshot();
+
+        // queued unstakes join the reconciliation up to the unstake epoch cap, and in full once
+        // their epoch is due
+        let mut queue_room = self
+            .get_unstake_epoch_cap_amount()
+            .map_or(Balance::MAX, |cap| {
+                cap.saturating_sub(self.user_amount_to_unstake_in_epoch)
+            });
+        while let Some(queued_epoch) = self.unstake_queue.min() {
+            let queued_amount = self.unstake_queue.get(&queued_epoch).unwrap_or(0);
+            let amount = if queued_epoch <= env::epoch_height() {
+                queued_amount
+            } else {
+                std::cmp::min(queued_amount, queue_room)
+            };
+            if amount == 0 {
+                break;
+            }
+
+            if amount == queued_amount {
+                self.unstake_queue.remove(&queued_epoch);
+            } else {
+                self.unstake_queue
+                    .insert(&queued_epoch, &(queued_amount - amount));
+            }
+            queue_room = queue_room.saturating_sub(amount);
+            self.unstake_queue_amount -= amount;
+            self.user_amount_to_unstake_in_epoch += amount;
+        }
+
         // here we use += because cleanup amount might not be 0
         self.reconciled_epoch_stake_amount += self.user_amount_to_stake_in_epoch;
         self.reconciled_epoch_unstake_amount += self.user_amount_to_unstake_in_epoch;
@@ -485,8 +716,196 @@ impl NearxPool {
         .emit();
     }
 
+    /// Runs the autocompounding and withdraw of every validator, then stakes or unstakes the
+    /// reconciled amount, scheduling at most `max_actions` of them within the prepaid gas.
+    /// The progress is saved, so the next call carries on where this one stopped.
+    /// Keep calling this method until the summary says done
+    pub fn run_epoch(&mut self, max_actions: u32) -> RunEpochSummary {
+        require!(max_actions > 0, ERROR_NON_POSITIVE_MAX_ACTIONS);
+
+        let epoch_height = env::epoch_height();
+        if self.run_epoch_cursor.epoch_height != epoch_height {
+            self.run_epoch_cursor = RunEpochCursor {
+                epoch_height,
+                validator_ids: self.validator_info_map.keys().collect(),
+                next_step: 0,
+            };
+        }
+
+        let max_actions = max_actions as usize;
+        let mut actions: Vec<EpochActionResponse> = vec![];
+        // set when an action was due but did not fit in this call
+        let mut out_of_budget = false;
+
+        let num_validators = self.run_epoch_cursor.validator_ids.len() as u64;
+
+        while self.run_epoch_cursor.next_step < 2 * num_validators {
+            let step = self.run_epoch_cursor.next_step;
+            let validator_id =
+                self.run_epoch_cursor.validator_ids[(step % num_validators) as usize].clone();
+            // the validator was removed since the start of the epoch
+            let validator_info = match self.validator_info_map.get(&validator_id) {
+                Some(validator_info) => validator_info.into_current(),
+                None => {
+                    self.run_epoch_cursor.next_step += 1;
+                    continue;
+                }
+            };
+
+            if step < num_validators {
+                if !self.operations_control.autocompounding_epoch_paused
+                    && validator_info.staked > 0
+                    && validator_info.last_redeemed_rewards_epoch != epoch_height
+                {
+                    if actions.len() >= max_actions
+                        || !has_gas_left(
+                            gas::ON_STAKE_POOL_GET_ACCOUNT_STAKED_BALANCE
+                                + gas::ON_STAKE_POOL_GET_ACCOUNT_STAKED_BALANCE_CB
+                                + self.internal_price_push_gas()
+                                + gas::RUN_EPOCH_ACTION
+                                + gas::RUN_EPOCH,
+                        )
+                    {
+                        out_of_budget = true;
+                        break;
+                    }
+
+                    actions.push(EpochActionResponse {
+                        action: EpochAction::Autocompounding,
+                        validator_id: validator_info.account_id.clone(),
+                        amount: None,
+                    });
+                    self.internal_autocompounding_epoch(validator_info);
+                }
+            } else if !self.operations_control.withdraw_epoch_paused
+                && !validator_info.paused()
+                && validator_info.unstaked_amount > 0
+                && !validator_info.pending_unstake_release()
+            {
+                if actions.len() >= max_actions
+                    || !has_gas_left(
+                        gas::ON_STAKE_POOL_WITHDRAW_ALL
+                            + gas::ON_STAKE_POOL_WITHDRAW_ALL_CB
+                            + gas::RUN_EPOCH_ACTION
+                            + gas::RUN_EPOCH,
+                    )
+                {
+                    out_of_budget = true;
+                    break;
+                }
+
+                actions.push(EpochActionResponse {
+                    action: EpochAction::Withdraw,
+                    validator_id: validator_info.account_id.clone(),
+                    amount: Some(U128(validator_info.unstaked_amount)),
+                });
+                self.internal_withdraw_epoch(validator_info);
+            }
+
+            self.run_epoch_cursor.next_step += 1;
+        }
+
+        let validator_steps_left =
+            (2 * num_validators).saturating_sub(self.run_epoch_cursor.next_step);
+
+        // a validator only picks up its new stake in the callback, so stake and unstake
+        // at most once per call
+        let mut staking_scheduled = false;
+        if validator_steps_left == 0 {
+            self.epoch_reconcilation();
+
+            if !self.operations_control.staking_epoch_paused
+                && self.reconciled_epoch_stake_amount > 0
+            {
+                let stake_action_gas = gas::ON_STAKE_POOL_DEPOSIT_AND_STAKE
+                    + gas::ON_STAKE_POOL_DEPOSIT_AND_STAKE_CB
+                    + gas::RUN_EPOCH_ACTION;
+                let gas_left = env::prepaid_gas()
+                    .0
+                    .saturating_sub(env::used_gas().0 + gas::RUN_EPOCH.0);
+                let max_validators = std::cmp::min(
+                    std::cmp::min(
+                        MAX_VALIDATORS_TO_STAKE_IN_EPOCH,
+                        max_actions.saturating_sub(actions.len()),
+                    ),
+                    (gas_left / stake_action_gas.0) as usize,
+                );
+
+                if max_validators == 0 {
+                    out_of_budget = true;
+                } else {
+                    let validators_to_stake = self.get_validators_to_stake(
+                        self.reconciled_epoch_stake_amount,
+                        max_validators,
+                    );
+                    let total_amount_to_stake: Balance = validators_to_stake
+                        .iter()
+                        .map(|(_, amount_to_stake)| amount_to_stake)
+                        .sum();
+
+                    if !validators_to_stake.is_empty()
+                        && env::account_balance()
+                            >= total_amount_to_stake + self.min_storage_reserve
+                    {
+                        for (validator, amount_to_stake) in validators_to_stake.iter() {
+                            actions.push(EpochActionResponse {
+                                action: EpochAction::Stake,
+                                validator_id: validator.account_id.clone(),
+                                amount: Some(U128(*amount_to_stake)),
+                            });
+                        }
+                        self.internal_joint_staking_epoch(validators_to_stake);
+                        staking_scheduled = true;
+                    }
+                }
+            }
+
+            if !self.operations_control.unstaking_epoch_paused
+                && self.reconciled_epoch_unstake_amount > 0
+            {
+                if let (Some(validator), validator_unstakable_amount) =
+                    self.get_validator_to_unstake()
+                {
+                    let amount_to_unstake = std::cmp::min(
+                        validator_unstakable_amount,
+                        self.reconciled_epoch_unstake_amount,
+                    );
+                    if amount_to_unstake <= validator.staked {
+                        if actions.len() >= max_actions
+                            || !has_gas_left(
+                                gas::ON_STAKE_POOL_UNSTAKE
+                                    + gas::ON_STAKE_POOL_UNSTAKE_CB
+                                    + gas::RUN_EPOCH_ACTION
+                                    + gas::RUN_EPOCH,
+                            )
+                        {
+                            out_of_budget = true;
+                        } else {
+                            actions.push(EpochActionResponse {
+                                action: EpochAction::Unstake,
+                                validator_id: validator.account_id.clone(),
+                                amount: Some(U128(amount_to_unstake)),
+                            });
+                            self.internal_unstaking_epoch(validator, amount_to_unstake);
+                            staking_scheduled = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        // the keeper is rewarded in the callback of every action that succeeds
+        RunEpochSummary {
+            epoch_height: U64(epoch_height),
+            actions,
+            validator_steps_left: U64(validator_steps_left),
+            done: validator_steps_left == 0 && !staking_scheduled && !out_of_budget,
+        }
+    }
+
+    /// Unstakes everything from a paused validator. Like pausing it, draining is not timelocked
     pub fn drain_unstake(&mut self, validator: AccountId) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::ValidatorManager);
 
         let min_gas =
             gas::DRAIN_UNSTAKE + gas::ON_STAKE_POOL_UNSTAKE + gas::ON_STAKE_POOL_UNSTAKE_CB;
@@ -573,7 +992,7 @@ impl NearxPool {
 
     /// Withdraw from a drained validator
     pub fn drain_withdraw(&mut self, validator: AccountId) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::ValidatorManager);
 
         // make sure enough gas was given
         let min_gas = gas::DRAIN_WITHDRAW
@@ -630,6 +1049,9 @@ impl NearxPool {
         if is_promise_success() {
             // stake the drained amount into the next epoch
             self.user_amount_to_stake_in_epoch += amount_to_withdraw;
+            self.validators_to_drain.retain(|v| v != &validator_id);
+            // a pending rebalance amount is part of what got drained
+            self.rebalance_unstakes.remove(&validator_id);
 
             Event::DrainWithdrawCallbackSuccess {
                 validator_id,
@@ -1,10 +1,20 @@
-use crate::constants::{ACCOUNTS_MAP, REWARD_FEE_SET_WAIT_TIME, VALIDATOR_MAP};
+use crate::constants::{
+    ACCOUNTS_MAP, COUNCIL_APPROVALS_MAP, EPOCHS_PER_YEAR, FT_METADATA,
+    INSTANT_UNSTAKE_RESERVE_PENDING_MAP, INSURANCE_FUND_HISTORY, MAX_PERFORMANCE_MULTIPLIER,
+    MAX_PRICE_SUBSCRIBERS, MIN_UNSTAKE_EPOCH_CAP, NEW_ACCOUNTS_MAP, PRICE_HISTORY, PROPOSALS_MAP,
+    QUARANTINED_BALANCES_MAP, REBALANCE_UNSTAKES_MAP, REWARD_FEE_SET_WAIT_TIME, ROLES_MAP,
+    STORAGE_DEPOSITS_MAP, U256, UNSTAKE_QUEUE_MAP, VALIDATOR_MAP, WITHDRAWAL_TICKETS_MAP,
+};
 use crate::errors::*;
 use crate::events::Event;
+use crate::fungible_token::default_ft_metadata;
+use crate::utils::proportional;
 use crate::{contract::*, state::*};
+use near_contract_standards::fungible_token::events as nep141;
+use near_contract_standards::storage_management::StorageManagement;
 use near_sdk::json_types::U64;
 use near_sdk::near_bindgen;
-use near_sdk::{assert_one_yocto, require, ONE_NEAR};
+use near_sdk::{assert_one_yocto, require, Promise, ONE_NEAR};
 
 #[near_bindgen]
 impl NearxPool {
@@ -48,7 +58,8 @@ impl NearxPool {
             reconciled_epoch_stake_amount: 0,
             reconciled_epoch_unstake_amount: 0,
             total_stake_shares: 0,
-            accounts: UnorderedMap::new(ACCOUNTS_MAP.as_bytes()),
+            accounts: UnorderedMap::new(NEW_ACCOUNTS_MAP.as_bytes()),
+            legacy_accounts: UnorderedMap::new(ACCOUNTS_MAP.as_bytes()),
             min_deposit_amount: ONE_NEAR,
             validator_info_map: UnorderedMap::new(VALIDATOR_MAP.as_bytes()),
             total_staked: 0,
@@ -71,12 +82,61 @@ impl NearxPool {
                 sync_validator_balance_paused: false,
                 ft_transfer_paused: false,
                 ft_transfer_call_paused: false,
+                instant_unstake_paused: false,
+                rebalance_epoch_paused: false,
             },
             treasury_account_id,
             total_validator_weight: 0,
             rewards_buffer: 0,
             accumulated_rewards_buffer: 0,
             min_storage_reserve: 0,
+            instant_unstake_reserve: 0,
+            instant_unstake_reserve_pending: TreeMap::new(
+                INSTANT_UNSTAKE_RESERVE_PENDING_MAP.as_bytes(),
+            ),
+            max_instant_unstake_reserve: 0,
+            instant_unstake_min_fee: Fraction::new(3, 1000),
+            instant_unstake_max_fee: Fraction::new(3, 100),
+            accumulated_instant_unstake_fees: 0,
+            withdrawal_tickets: UnorderedMap::new(WITHDRAWAL_TICKETS_MAP.as_bytes()),
+            last_withdrawal_ticket_id: 0,
+            min_performance_multiplier: Fraction::new(1, 1),
+            max_performance_multiplier: Fraction::new(1, 1),
+            validator_delinquency_threshold: 0,
+            validators_to_drain: vec![],
+            run_epoch_cursor: RunEpochCursor::default(),
+            keeper_reward_per_action: 0,
+            max_keeper_rewards_per_epoch: 0,
+            keeper_rewards_fee_share: Fraction::new(0, 1),
+            keeper_budget_shares: 0,
+            keeper_rewards_epoch: 0,
+            keeper_rewards_paid_in_epoch: 0,
+            unstake_strategy: UnstakeStrategy::LargestFirst,
+            rebalance_unstakes: UnorderedMap::new(REBALANCE_UNSTAKES_MAP.as_bytes()),
+            storage_deposits: LookupMap::new(STORAGE_DEPOSITS_MAP.as_bytes()),
+            ft_metadata: LazyOption::new(FT_METADATA.as_bytes(), Some(&default_ft_metadata())),
+            proposals: UnorderedMap::new(PROPOSALS_MAP.as_bytes()),
+            last_proposal_id: 0,
+            proposal_delay_epochs: 0,
+            council_members: vec![],
+            council_threshold: 0,
+            council_approval_ttl_epochs: 0,
+            council_approvals: UnorderedMap::new(COUNCIL_APPROVALS_MAP.as_bytes()),
+            roles: UnorderedMap::new(ROLES_MAP.as_bytes()),
+            unstake_epoch_cap: None,
+            unstake_queue: TreeMap::new(UNSTAKE_QUEUE_MAP.as_bytes()),
+            unstake_queue_amount: 0,
+            price_history: Vector::new(PRICE_HISTORY.as_bytes()),
+            price_snapshots_taken: 0,
+            price_subscribers: vec![],
+            price_update_nonce: 0,
+            exchange_rate_bounds: None,
+            quarantined_balances: UnorderedMap::new(QUARANTINED_BALANCES_MAP.as_bytes()),
+            insurance_fund_shares: 0,
+            insurance_fund_coverage: Fraction::new(0, 1),
+            uncovered_validator_losses: 0,
+            insurance_fee_share: Fraction::new(0, 1),
+            insurance_fund_history: Vector::new(INSURANCE_FUND_HISTORY.as_bytes()),
         }
     }
 
@@ -85,17 +145,136 @@ impl NearxPool {
     */
     #[payable]
     pub fn update_rewards_buffer(&mut self) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::RewardsBufferDepositor);
         self.internal_update_rewards_buffer(env::attached_deposit())
     }
 
+    #[payable]
+    pub fn fund_instant_unstake_reserve(&mut self) {
+        self.assert_operator_or_owner();
+
+        let amount = env::attached_deposit();
+        require!(amount > 0, ERROR_DEPOSIT_SHOULD_BE_GREATER_THAN_ZERO);
+
+        require!(
+            self.instant_unstake_reserve + amount <= self.max_instant_unstake_reserve,
+            ERROR_INSTANT_UNSTAKE_RESERVE_CAP_EXCEEDED
+        );
+
+        self.instant_unstake_reserve += amount;
+
+        Event::InstantUnstakeReserveFunded {
+            amount_added: U128(amount),
+            new_reserve: U128(self.instant_unstake_reserve),
+        }
+        .emit();
+    }
+
+    /// Sends NEAR of the instant unstake reserve back to the owner. NEAR the reserve paid out
+    /// and which is still unbonding can only be withdrawn once it is back in the reserve
+    #[payable]
+    pub fn withdraw_instant_unstake_reserve(&mut self, amount: U128) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_withdraw_instant_unstake_reserve(amount);
+    }
+
+    pub(crate) fn internal_withdraw_instant_unstake_reserve(&mut self, amount: U128) {
+        require!(amount.0 > 0, ERROR_NON_POSITIVE_WITHDRAWAL);
+        require!(
+            amount.0 <= self.instant_unstake_reserve,
+            ERROR_NOT_ENOUGH_INSTANT_UNSTAKE_RESERVE
+        );
+
+        self.instant_unstake_reserve -= amount.0;
+
+        Event::InstantUnstakeReserveWithdrawn {
+            account_id: self.owner_account_id.clone(),
+            amount,
+            new_reserve: U128(self.instant_unstake_reserve),
+        }
+        .emit();
+
+        Promise::new(self.owner_account_id.clone()).transfer(amount.0);
+    }
+
+    /// Stakes the attached NEAR into the keeper budget, which pays the keeper rewards in NearX.
+    /// Not timelocked, it only adds NEAR and a proposal cannot carry the deposit
+    #[payable]
+    pub fn fund_keeper_budget(&mut self) {
+        self.assert_owner_calling();
+
+        let amount = env::attached_deposit();
+        require!(amount > 0, ERROR_DEPOSIT_SHOULD_BE_GREATER_THAN_ZERO);
+
+        let num_shares = self.num_shares_from_staked_amount_rounded_down(amount);
+        require!(num_shares > 0, ERROR_NON_POSITIVE_STAKE_SHARES);
+
+        self.keeper_budget_shares += num_shares;
+        self.total_staked += amount;
+        self.total_stake_shares += num_shares;
+
+        self.user_amount_to_stake_in_epoch += amount;
+
+        nep141::FtMint {
+            owner_id: &env::current_account_id(),
+            amount: &U128(num_shares),
+            memo: Some("keeper_budget"),
+        }
+        .emit();
+
+        Event::KeeperBudgetFunded {
+            amount: U128(amount),
+            minted_stake_shares: U128(num_shares),
+            new_budget_shares: U128(self.keeper_budget_shares),
+        }
+        .emit();
+    }
+
+    /// Stakes the attached NEAR into the insurance fund, which covers validator losses in NearX
+    #[payable]
+    pub fn fund_insurance_fund(&mut self) {
+        let amount = env::attached_deposit();
+        require!(amount > 0, ERROR_DEPOSIT_SHOULD_BE_GREATER_THAN_ZERO);
+
+        let num_shares = self.num_shares_from_staked_amount_rounded_down(amount);
+        require!(num_shares > 0, ERROR_NON_POSITIVE_STAKE_SHARES);
+
+        self.insurance_fund_shares += num_shares;
+        self.total_staked += amount;
+        self.total_stake_shares += num_shares;
+
+        self.user_amount_to_stake_in_epoch += amount;
+
+        nep141::FtMint {
+            owner_id: &env::current_account_id(),
+            amount: &U128(num_shares),
+            memo: Some("insurance_fund"),
+        }
+        .emit();
+
+        Event::InsuranceFundFunded {
+            account_id: env::predecessor_account_id(),
+            amount: U128(amount),
+            minted_stake_shares: U128(num_shares),
+            new_fund_shares: U128(self.insurance_fund_shares),
+        }
+        .emit();
+
+        let mut record = self.internal_current_insurance_fund_record();
+        record.deposited_shares += num_shares;
+        self.internal_save_insurance_fund_record(record);
+    }
+
     // the difference b/w manager_deposit_and_stake and direct_deposit_and_stake is that direct
     // deposit_and_stake only allows the user to directly stake into a private validator. manager_deposit
     // _and_stake allows manager to deposit to any validator directly. For private validators, we keep
     // track of user activity offchain and update the max unstakable limit if the tracked user has does certain blacklisted actions
     #[payable]
     pub fn manager_deposit_and_stake(&mut self, validator: AccountId) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::ValidatorManager);
         self.internal_manager_deposit_and_stake(env::attached_deposit(), validator);
     }
 
@@ -106,7 +285,43 @@ impl NearxPool {
 
     #[payable]
     pub fn deposit_and_stake(&mut self) {
-        self.internal_deposit_and_stake(env::attached_deposit());
+        self.internal_deposit_and_stake(env::predecessor_account_id(), env::attached_deposit());
+    }
+
+    /// Stakes the attached deposit and mints the NearX to `receiver_id`, registering it out of
+    /// the attached deposit if needed. When `msg` is set, the NearX is then passed to the
+    /// receiver's `ft_on_transfer` as with `ft_transfer_call` and the unused NearX is refunded to
+    /// the caller, who needs to be registered for that. Returns the NearX the receiver kept.
+    #[payable]
+    pub fn deposit_and_stake_for(
+        &mut self,
+        receiver_id: AccountId,
+        msg: Option<String>,
+    ) -> PromiseOrValue<U128> {
+        let sender_id = env::predecessor_account_id();
+        if msg.is_some() {
+            self.assert_ft_transfer_call_not_paused();
+            require!(sender_id != receiver_id, ERROR_SENDER_RECEIVER_SAME);
+            // refunds of the unused NearX go to the caller
+            self.internal_get_account_unwrap(&sender_id);
+        }
+
+        let mut amount = env::attached_deposit();
+        if self.internal_get_account_option(&receiver_id).is_none() {
+            let min_balance = self.storage_balance_bounds().min.0;
+            require!(amount > min_balance, ERROR_NOT_ENOUGH_BALANCE_FOR_STORAGE);
+            self.internal_register_account(&receiver_id);
+            amount -= min_balance;
+        }
+
+        let num_shares = self.internal_deposit_and_stake(receiver_id.clone(), amount);
+
+        match msg {
+            Some(msg) => self
+                .internal_ft_transfer_call(sender_id, receiver_id, U128(num_shares), msg)
+                .into(),
+            None => PromiseOrValue::Value(U128(num_shares)),
+        }
     }
 
     /// Unstakes all staked balance from the inner account of the predecessor.
@@ -115,7 +330,7 @@ impl NearxPool {
         let account_id = env::predecessor_account_id();
         let account = self.internal_get_account(&account_id);
         let amount = self.staked_amount_from_num_shares_rounded_down(account.stake_shares);
-        self.internal_unstake(amount);
+        self.internal_unstake(account_id, amount);
     }
 
     /// Unstakes the given amount from the inner account of the predecessor.
@@ -123,19 +338,40 @@ impl NearxPool {
     /// The new total unstaked balance will be available for withdrawal in four epochs.
     pub fn unstake(&mut self, amount: U128) {
         let amount: Balance = amount.into();
-        self.internal_unstake(amount);
+        self.internal_unstake(env::predecessor_account_id(), amount);
+    }
+
+    /// Unstakes the given amount and pays it out right away from the instant unstake reserve,
+    /// minus the instant unstake fee. The fee is not unstaked, its NearX goes to the treasury.
+    pub fn instant_unstake(&mut self, amount: U128) {
+        let amount: Balance = amount.into();
+        self.internal_instant_unstake(amount);
+    }
+
+    /// Unstakes the given amount from the inner account of the predecessor and mints a
+    /// withdrawal ticket (NEP-171) holding the unstaked amount and its own release epoch.
+    /// Requires `WITHDRAWAL_TICKET_STORAGE_DEPOSIT` attached, which is refunded on claim.
+    #[payable]
+    pub fn unstake_to_ticket(&mut self, amount: U128) -> String {
+        let amount: Balance = amount.into();
+        self.internal_unstake_to_ticket(amount)
+    }
+
+    /// Pays out the unstaked amount held by the ticket to its owner and burns the ticket.
+    pub fn claim(&mut self, ticket_id: String) {
+        self.internal_claim(ticket_id);
     }
 
-    /// Withdraws the entire unstaked balance from the predecessor account.
-    /// It's only allowed if the `unstake` action was not performed in the four most recent epochs.
+    /// Withdraws every unstake request of the predecessor account which has gone through
+    /// the unbonding period.
     pub fn withdraw_all(&mut self) {
         let account_id = env::predecessor_account_id();
         let account = self.internal_get_account(&account_id);
-        self.internal_withdraw(account.unstaked_amount);
+        self.internal_withdraw(account.withdrawable_amount(env::epoch_height()));
     }
 
     /// Withdraws the non staked balance for given account.
-    /// It's only allowed if the `unstake` action was not performed in the four most recent epochs.
+    /// The amount is taken from the unstake requests which have gone through the unbonding period.
     pub fn withdraw(&mut self, amount: U128) {
         let amount: Balance = amount.into();
         self.internal_withdraw(amount);
@@ -144,9 +380,10 @@ impl NearxPool {
     /*
        Validator pool addition and deletion
     */
+    /// Not timelocked, a misbehaving validator has to be paused and drained right away
     #[payable]
     pub fn pause_validator(&mut self, validator: AccountId) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::ValidatorManager);
         assert_one_yocto();
 
         let mut validator_info = self.internal_get_validator(&validator);
@@ -172,15 +409,21 @@ impl NearxPool {
 
     #[payable]
     pub fn remove_validator(&mut self, validator: AccountId) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::ValidatorManager);
         assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_remove_validator(validator);
+    }
 
+    pub(crate) fn internal_remove_validator(&mut self, validator: AccountId) {
         let validator_info = self.internal_get_validator(&validator);
 
         require!(validator_info.is_empty(), ERROR_INVALID_VALIDATOR_REMOVAL);
 
         self.total_validator_weight -= validator_info.weight;
         self.validator_info_map.remove(&validator);
+        self.validators_to_drain.retain(|v| v != &validator);
 
         Event::ValidatorRemoved {
             account_id: validator,
@@ -190,8 +433,14 @@ impl NearxPool {
 
     #[payable]
     pub fn add_validator(&mut self, validator: AccountId, weight: u16) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::ValidatorManager);
         assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_add_validator(validator, weight);
+    }
+
+    pub(crate) fn internal_add_validator(&mut self, validator: AccountId, weight: u16) {
         if self.validator_info_map.get(&validator).is_some() {
             panic!("{}", ERROR_VALIDATOR_IS_ALREADY_PRESENT);
         }
@@ -210,15 +459,27 @@ impl NearxPool {
 
     #[payable]
     pub fn update_validator(&mut self, validator: AccountId, weight: u16) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::ValidatorManager);
         assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_validator_weight(validator, weight);
+    }
 
+    pub(crate) fn internal_set_validator_weight(&mut self, validator: AccountId, weight: u16) {
         let mut validator_info = self.internal_get_validator(&validator);
 
         if weight == 0 {
             require!(false, ERROR_INVALID_VALIDATOR_WEIGHT);
         }
 
+        // bringing a paused validator back gives it a clean delinquency record
+        if validator_info.paused() {
+            validator_info.failed_epoch_actions = 0;
+            validator_info.zero_reward_epochs = 0;
+            self.validators_to_drain.retain(|v| v != &validator);
+        }
+
         // update total weight
         self.total_validator_weight = self.total_validator_weight + weight - validator_info.weight;
         validator_info.weight = weight;
@@ -239,7 +500,7 @@ impl NearxPool {
         validator: AccountId,
         initial_max_unstakable_limit: Option<U128>,
     ) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::ValidatorManager);
         assert_one_yocto();
 
         let mut validator_info = self.internal_get_validator(&validator);
@@ -272,7 +533,7 @@ impl NearxPool {
 
     #[payable]
     pub fn make_validator_public(&mut self, validator: AccountId) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::ValidatorManager);
         assert_one_yocto();
 
         let mut validator_info = self.internal_get_validator(&validator);
@@ -300,7 +561,7 @@ impl NearxPool {
         validator: AccountId,
         amount_unstaked: U128,
     ) {
-        self.assert_operator_or_owner();
+        self.assert_role(Role::ValidatorManager);
         assert_one_yocto();
 
         let mut validator_info = self.internal_get_validator(&validator);
@@ -334,7 +595,12 @@ impl NearxPool {
     pub fn set_owner(&mut self, new_owner: AccountId) {
         assert_one_yocto();
         self.assert_owner_calling();
+        self.assert_not_timelocked();
 
+        self.internal_set_owner(new_owner);
+    }
+
+    pub(crate) fn internal_set_owner(&mut self, new_owner: AccountId) {
         // owner, operator, treasury and current contract address should all be different
         require!(
             new_owner != self.operator_account_id,
@@ -349,6 +615,9 @@ impl NearxPool {
             new_owner != env::current_account_id(),
             ERROR_OWNER_CURRENT_CONTRACT_SAME
         );
+        self.internal_use_council_approvals(CouncilAction::SetOwner {
+            new_owner: new_owner.clone(),
+        });
 
         self.temp_owner = Some(new_owner.clone());
         Event::SetOwner {
@@ -383,7 +652,12 @@ impl NearxPool {
     pub fn set_operator_id(&mut self, new_operator_account_id: AccountId) {
         assert_one_yocto();
         self.assert_owner_calling();
+        self.assert_not_timelocked();
+
+        self.internal_set_operator_id(new_operator_account_id);
+    }
 
+    pub(crate) fn internal_set_operator_id(&mut self, new_operator_account_id: AccountId) {
         // owner, operator, treasury and current contract address should all be different
         require!(
             new_operator_account_id != self.operator_account_id,
@@ -436,7 +710,12 @@ impl NearxPool {
     pub fn set_treasury_id(&mut self, new_treasury_account_id: AccountId) {
         assert_one_yocto();
         self.assert_owner_calling();
+        self.assert_not_timelocked();
 
+        self.internal_set_treasury_id(new_treasury_account_id);
+    }
+
+    pub(crate) fn internal_set_treasury_id(&mut self, new_treasury_account_id: AccountId) {
         // owner, operator, treasury and current contract address should all be different
         require!(
             new_treasury_account_id != self.operator_account_id,
@@ -491,7 +770,54 @@ impl NearxPool {
         update_operations_control_request: OperationsControlUpdateRequest,
     ) {
         assert_one_yocto();
-        self.assert_owner_calling();
+        self.assert_role(Role::Pauser);
+        // pausing stays quick, resuming operations is an owner action
+        if update_operations_control_request.has_unpause(&self.operations_control) {
+            self.assert_owner_calling();
+            self.assert_not_timelocked();
+        }
+
+        self.internal_update_operations_control(update_operations_control_request);
+    }
+
+    /// Pauses every operation at once. The owner resumes them one group at a time through
+    /// update_operations_control.
+    #[payable]
+    pub fn emergency_pause(&mut self) {
+        assert_one_yocto();
+        self.assert_role(Role::Guardian);
+
+        self.operations_control = OperationControls {
+            stake_paused: true,
+            direct_stake_paused: true,
+            unstaked_paused: true,
+            withdraw_paused: true,
+            staking_epoch_paused: true,
+            unstaking_epoch_paused: true,
+            withdraw_epoch_paused: true,
+            autocompounding_epoch_paused: true,
+            sync_validator_balance_paused: true,
+            ft_transfer_paused: true,
+            ft_transfer_call_paused: true,
+            instant_unstake_paused: true,
+            rebalance_epoch_paused: true,
+        };
+
+        Event::EmergencyPause {
+            caller: env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    pub(crate) fn internal_update_operations_control(
+        &mut self,
+        update_operations_control_request: OperationsControlUpdateRequest,
+    ) {
+        require!(
+            update_operations_control_request.unpaused_group_count(&self.operations_control) <= 1,
+            ERROR_UNPAUSE_ONE_GROUP_AT_A_TIME
+        );
+        let old_operations_control = self.operations_control;
 
         self.operations_control.stake_paused = update_operations_control_request
             .stake_paused
@@ -526,6 +852,19 @@ impl NearxPool {
         self.operations_control.ft_transfer_call_paused = update_operations_control_request
             .ft_transfer_call_paused
             .unwrap_or(self.operations_control.ft_transfer_call_paused);
+        self.operations_control.instant_unstake_paused = update_operations_control_request
+            .instant_unstake_paused
+            .unwrap_or(self.operations_control.instant_unstake_paused);
+        self.operations_control.rebalance_epoch_paused = update_operations_control_request
+            .rebalance_epoch_paused
+            .unwrap_or(self.operations_control.rebalance_epoch_paused);
+
+        // pausing is quick, resuming paused operations needs the council
+        if old_operations_control.is_unpaused_in(&self.operations_control) {
+            self.internal_use_council_approvals(CouncilAction::Unpause {
+                update_operations_control_request,
+            });
+        }
 
         Event::UpdateOperationsControl {
             operations_control: OperationControls {
@@ -542,6 +881,8 @@ impl NearxPool {
                     .sync_validator_balance_paused,
                 ft_transfer_paused: self.operations_control.ft_transfer_paused,
                 ft_transfer_call_paused: self.operations_control.ft_transfer_call_paused,
+                instant_unstake_paused: self.operations_control.instant_unstake_paused,
+                rebalance_epoch_paused: self.operations_control.rebalance_epoch_paused,
             },
         }
         .emit();
@@ -558,6 +899,12 @@ impl NearxPool {
     pub fn set_reward_fee(&mut self, numerator: u32, denominator: u32) {
         self.assert_owner_calling();
         assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_reward_fee(numerator, denominator);
+    }
+
+    pub(crate) fn internal_set_reward_fee(&mut self, numerator: u32, denominator: u32) {
         require!(numerator * 10 <= denominator); // less than or equal to 10%
 
         let old_reward_fee = self.rewards_fee;
@@ -584,8 +931,13 @@ impl NearxPool {
                 self.last_reward_fee_set_epoch + REWARD_FEE_SET_WAIT_TIME <= env::epoch_height(),
                 ERROR_TEMP_REWARD_FEE_SET_IN_WAIT_PERIOD
             );
+            let temp_reward_fee = self.temp_reward_fee.unwrap();
+            self.internal_use_council_approvals(CouncilAction::CommitRewardFee {
+                numerator: temp_reward_fee.numerator,
+                denominator: temp_reward_fee.denominator,
+            });
 
-            self.rewards_fee = self.temp_reward_fee.unwrap();
+            self.rewards_fee = temp_reward_fee;
             self.temp_reward_fee = None;
 
             Event::CommitRewardFee {
@@ -601,7 +953,12 @@ impl NearxPool {
     pub fn set_min_deposit(&mut self, min_deposit: U128) {
         self.assert_owner_calling();
         assert_one_yocto();
+        self.assert_not_timelocked();
 
+        self.internal_set_min_deposit(min_deposit);
+    }
+
+    pub(crate) fn internal_set_min_deposit(&mut self, min_deposit: U128) {
         require!(min_deposit > U128(ONE_NEAR), ERROR_MIN_DEPOSIT_TOO_LOW);
         require!(
             min_deposit < U128(100 * ONE_NEAR),
@@ -618,6 +975,723 @@ impl NearxPool {
         .emit();
     }
 
+    #[payable]
+    pub fn set_instant_unstake_fee(&mut self, min_fee: Fraction, max_fee: Fraction) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_instant_unstake_fee(min_fee, max_fee);
+    }
+
+    pub(crate) fn internal_set_instant_unstake_fee(
+        &mut self,
+        min_fee: Fraction,
+        max_fee: Fraction,
+    ) {
+        require!(
+            min_fee.denominator > 0 && max_fee.denominator > 0,
+            ERROR_INVALID_INSTANT_UNSTAKE_FEE
+        );
+        // max fee less than or equal to 10% and min fee less than or equal to max fee
+        require!(
+            (max_fee.numerator as u64) * 10 <= max_fee.denominator as u64,
+            ERROR_INVALID_INSTANT_UNSTAKE_FEE
+        );
+        require!(
+            (min_fee.numerator as u64) * (max_fee.denominator as u64)
+                <= (max_fee.numerator as u64) * (min_fee.denominator as u64),
+            ERROR_INVALID_INSTANT_UNSTAKE_FEE
+        );
+
+        self.instant_unstake_min_fee = min_fee;
+        self.instant_unstake_max_fee = max_fee;
+
+        Event::SetInstantUnstakeFee { min_fee, max_fee }.emit();
+    }
+
+    /// Sets the most NEAR `fund_instant_unstake_reserve` can fill the reserve up to, the fee is at
+    /// its min once the reserve reaches it. NEAR paid out by the reserve always comes back into
+    /// it once withdrawn from the validators, even above the max, and nothing ever overflows to
+    /// the NearX holders. Lowering the max leaves the excess in the reserve until the owner takes
+    /// it out with `withdraw_instant_unstake_reserve`
+    #[payable]
+    pub fn set_max_instant_unstake_reserve(&mut self, max_reserve: U128) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_max_instant_unstake_reserve(max_reserve);
+    }
+
+    pub(crate) fn internal_set_max_instant_unstake_reserve(&mut self, max_reserve: U128) {
+        let old_max_reserve = self.max_instant_unstake_reserve;
+        self.max_instant_unstake_reserve = max_reserve.0;
+
+        Event::SetMaxInstantUnstakeReserve {
+            old_max_reserve: U128(old_max_reserve),
+            new_max_reserve: max_reserve,
+        }
+        .emit();
+    }
+
+    /// Sets how far the validator performance score can move a validator's target stake
+    /// away from its weight. A multiplier of 1 on both ends turns the scoring off.
+    #[payable]
+    pub fn set_performance_multiplier_bounds(&mut self, min: Fraction, max: Fraction) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_performance_multiplier_bounds(min, max);
+    }
+
+    pub(crate) fn internal_set_performance_multiplier_bounds(
+        &mut self,
+        min: Fraction,
+        max: Fraction,
+    ) {
+        require!(
+            min.denominator > 0 && max.denominator > 0,
+            ERROR_INVALID_PERFORMANCE_MULTIPLIER_BOUNDS
+        );
+        // min <= 1 <= max <= MAX_PERFORMANCE_MULTIPLIER
+        require!(
+            min.numerator <= min.denominator
+                && max.numerator >= max.denominator
+                && (max.numerator as u64)
+                    <= (max.denominator as u64) * (MAX_PERFORMANCE_MULTIPLIER as u64),
+            ERROR_INVALID_PERFORMANCE_MULTIPLIER_BOUNDS
+        );
+
+        self.min_performance_multiplier = min;
+        self.max_performance_multiplier = max;
+
+        Event::SetPerformanceMultiplierBounds { min, max }.emit();
+    }
+
+    /// Sets how unstaking_epoch spreads the unstaked amount over the validators
+    #[payable]
+    pub fn set_unstake_strategy(&mut self, strategy: UnstakeStrategy) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_unstake_strategy(strategy);
+    }
+
+    pub(crate) fn internal_set_unstake_strategy(&mut self, strategy: UnstakeStrategy) {
+        let old_strategy = self.unstake_strategy;
+        self.unstake_strategy = strategy;
+
+        Event::SetUnstakeStrategy {
+            old_strategy,
+            new_strategy: strategy,
+        }
+        .emit();
+    }
+
+    /// Caps the NEAR unstaked in a single epoch, unstakes above the cap are queued into later
+    /// epochs and can be withdrawn later. None removes the cap.
+    #[payable]
+    pub fn set_unstake_epoch_cap(&mut self, cap: Option<UnstakeEpochCap>) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_unstake_epoch_cap(cap);
+    }
+
+    pub(crate) fn internal_set_unstake_epoch_cap(&mut self, cap: Option<UnstakeEpochCap>) {
+        match cap {
+            Some(UnstakeEpochCap::Absolute { amount }) => {
+                require!(
+                    amount.0 >= MIN_UNSTAKE_EPOCH_CAP,
+                    ERROR_INVALID_UNSTAKE_EPOCH_CAP
+                )
+            }
+            Some(UnstakeEpochCap::ShareOfTotalStaked { share }) => require!(
+                share.numerator > 0 && share.numerator <= share.denominator,
+                ERROR_INVALID_UNSTAKE_EPOCH_CAP
+            ),
+            None => {}
+        }
+
+        let old_cap = self.unstake_epoch_cap;
+        self.unstake_epoch_cap = cap;
+
+        Event::SetUnstakeEpochCap {
+            old_cap,
+            new_cap: cap,
+        }
+        .emit();
+    }
+
+    /// Bounds how much a single validator balance report may move the exchange rate per epoch.
+    /// Reports outside of the bounds are quarantined until the operator confirms them. None
+    /// turns the guard off
+    #[payable]
+    pub fn set_exchange_rate_bounds(&mut self, bounds: Option<ExchangeRateBounds>) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_exchange_rate_bounds(bounds);
+    }
+
+    pub(crate) fn internal_set_exchange_rate_bounds(&mut self, bounds: Option<ExchangeRateBounds>) {
+        if let Some(bounds) = bounds {
+            for bound in [bounds.max_increase_per_epoch, bounds.max_decrease_per_epoch] {
+                require!(
+                    bound.numerator > 0 && bound.numerator <= bound.denominator,
+                    ERROR_INVALID_EXCHANGE_RATE_BOUNDS
+                );
+            }
+        }
+
+        let old_bounds = self.exchange_rate_bounds;
+        self.exchange_rate_bounds = bounds;
+
+        Event::SetExchangeRateBounds {
+            old_bounds,
+            new_bounds: bounds,
+        }
+        .emit();
+    }
+
+    /// Sets the share of a validator loss left after the rewards buffer that the insurance fund
+    /// covers as soon as the loss is booked. Setting it is the governance call that spends the
+    /// fund automatically: it is an owner action behind the proposal timelock like
+    /// `cover_loss_from_insurance_fund`, and it only applies to losses booked afterwards. The
+    /// rest of a loss is recorded as uncovered, which is all `cover_loss_from_insurance_fund`
+    /// can cover, so no loss is covered twice. A zero coverage leaves every loss to that call
+    #[payable]
+    pub fn set_insurance_fund_coverage(&mut self, coverage: Fraction) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_insurance_fund_coverage(coverage);
+    }
+
+    pub(crate) fn internal_set_insurance_fund_coverage(&mut self, coverage: Fraction) {
+        require!(
+            coverage.denominator > 0 && coverage.numerator <= coverage.denominator,
+            ERROR_INVALID_INSURANCE_FUND_COVERAGE
+        );
+
+        let old_coverage = self.insurance_fund_coverage;
+        self.insurance_fund_coverage = coverage;
+
+        Event::SetInsuranceFundCoverage {
+            old_coverage,
+            new_coverage: coverage,
+        }
+        .emit();
+    }
+
+    /// Sets the share of the rewards fee, after the keeper budget, minted into the insurance fund
+    #[payable]
+    pub fn set_insurance_fee_share(&mut self, fee_share: Fraction) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_insurance_fee_share(fee_share);
+    }
+
+    pub(crate) fn internal_set_insurance_fee_share(&mut self, fee_share: Fraction) {
+        require!(
+            fee_share.denominator > 0 && fee_share.numerator <= fee_share.denominator,
+            ERROR_INVALID_INSURANCE_FEE_SHARE
+        );
+
+        let old_fee_share = self.insurance_fee_share;
+        self.insurance_fee_share = fee_share;
+
+        Event::SetInsuranceFeeShare {
+            old_fee_share,
+            new_fee_share: fee_share,
+        }
+        .emit();
+    }
+
+    /// Burns `amount` NEAR worth of the insurance fund to make NearX holders whole for a
+    /// validator loss that lowered the NearX price. The amount is capped by the recorded losses
+    /// left uncovered. This is the only way to spend the fund besides the coverage set with
+    /// `set_insurance_fund_coverage`
+    #[payable]
+    pub fn cover_loss_from_insurance_fund(&mut self, amount: U128) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_cover_loss_from_insurance_fund(amount);
+    }
+
+    pub(crate) fn internal_cover_loss_from_insurance_fund(&mut self, amount: U128) {
+        let shares = self.num_shares_from_staked_amount_rounded_down(amount.0);
+        require!(shares > 0, ERROR_NON_POSITIVE_STAKE_SHARES);
+        require!(
+            shares <= self.insurance_fund_shares,
+            ERROR_NOT_ENOUGH_INSURANCE_FUND
+        );
+        require!(
+            amount.0 <= self.uncovered_validator_losses,
+            ERROR_COVERAGE_ABOVE_UNCOVERED_LOSSES
+        );
+
+        let old_price = self.get_nearx_price();
+        let covered_amount = self.internal_burn_insurance_fund_shares(shares);
+        self.uncovered_validator_losses -= covered_amount;
+
+        Event::InsuranceFundLossCovered {
+            amount: U128(covered_amount),
+            burnt_shares: U128(shares),
+            old_price,
+            new_price: self.get_nearx_price(),
+        }
+        .emit();
+    }
+
+    /// Sets the reward paid to callers of the permissionless epoch methods for every action
+    /// that did useful work, the cap on the rewards paid in an epoch and the share of the
+    /// rewards fee that tops up the keeper budget
+    #[payable]
+    pub fn set_keeper_incentives(
+        &mut self,
+        reward_per_action: U128,
+        max_rewards_per_epoch: U128,
+        rewards_fee_share: Fraction,
+    ) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_keeper_incentives(
+            reward_per_action,
+            max_rewards_per_epoch,
+            rewards_fee_share,
+        );
+    }
+
+    pub(crate) fn internal_set_keeper_incentives(
+        &mut self,
+        reward_per_action: U128,
+        max_rewards_per_epoch: U128,
+        rewards_fee_share: Fraction,
+    ) {
+        require!(
+            rewards_fee_share.denominator > 0
+                && rewards_fee_share.numerator <= rewards_fee_share.denominator,
+            ERROR_INVALID_KEEPER_REWARDS_FEE_SHARE
+        );
+
+        self.keeper_reward_per_action = reward_per_action.0;
+        self.max_keeper_rewards_per_epoch = max_rewards_per_epoch.0;
+        self.keeper_rewards_fee_share = rewards_fee_share;
+
+        Event::SetKeeperIncentives {
+            reward_per_action,
+            max_rewards_per_epoch,
+            rewards_fee_share,
+        }
+        .emit();
+    }
+
+    /// Sets the number of consecutive failed staking/unstaking epochs or zero reward epochs
+    /// after which a validator is paused and queued for draining. 0 turns the auto pause off.
+    #[payable]
+    pub fn set_validator_delinquency_threshold(&mut self, threshold: u32) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_validator_delinquency_threshold(threshold);
+    }
+
+    pub(crate) fn internal_set_validator_delinquency_threshold(&mut self, threshold: u32) {
+        let old_threshold = self.validator_delinquency_threshold;
+        self.validator_delinquency_threshold = threshold;
+
+        Event::SetValidatorDelinquencyThreshold {
+            old_threshold,
+            new_threshold: threshold,
+        }
+        .emit();
+    }
+
+    /// Sets the number of epochs owner actions wait in the proposal queue. Once it is above 0
+    /// the owner methods are timelocked and only run through execute_proposal.
+    #[payable]
+    pub fn set_proposal_delay(&mut self, epochs: U64) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_proposal_delay(epochs.0);
+    }
+
+    pub(crate) fn internal_set_proposal_delay(&mut self, epochs: EpochHeight) {
+        let old_delay = self.proposal_delay_epochs;
+        self.proposal_delay_epochs = epochs;
+
+        Event::SetProposalDelay {
+            old_delay: U64(old_delay),
+            new_delay: U64(epochs),
+        }
+        .emit();
+    }
+
+    /// Queues an owner action, it can be executed once the proposal delay has passed
+    #[payable]
+    pub fn propose(&mut self, action: ProposalAction) -> U64 {
+        self.assert_owner_calling();
+        assert_one_yocto();
+
+        self.last_proposal_id += 1;
+        let proposal_id = self.last_proposal_id;
+        let proposal = Proposal {
+            action: action.clone(),
+            proposer: env::predecessor_account_id(),
+            created_epoch: env::epoch_height(),
+            executable_epoch: env::epoch_height() + self.proposal_delay_epochs,
+        };
+        self.proposals.insert(&proposal_id, &proposal);
+
+        Event::ProposalCreated {
+            proposal_id: U64(proposal_id),
+            action,
+            proposer: proposal.proposer,
+            executable_epoch: U64(proposal.executable_epoch),
+        }
+        .emit();
+
+        U64(proposal_id)
+    }
+
+    #[payable]
+    pub fn cancel_proposal(&mut self, proposal_id: U64) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+
+        require!(
+            self.proposals.remove(&proposal_id.0).is_some(),
+            ERROR_PROPOSAL_NOT_FOUND
+        );
+
+        Event::ProposalCancelled { proposal_id }.emit();
+    }
+
+    /// Runs a queued owner action whose delay has passed
+    #[payable]
+    pub fn execute_proposal(&mut self, proposal_id: U64) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+
+        let proposal = self
+            .proposals
+            .get(&proposal_id.0)
+            .unwrap_or_else(|| panic!("{}", ERROR_PROPOSAL_NOT_FOUND));
+        require!(
+            proposal.executable_epoch <= env::epoch_height(),
+            ERROR_PROPOSAL_IN_DELAY_PERIOD
+        );
+        require!(
+            !matches!(proposal.action, ProposalAction::Upgrade { .. }),
+            ERROR_UPGRADE_PROPOSAL_RUNS_THROUGH_UPGRADE
+        );
+        self.proposals.remove(&proposal_id.0);
+
+        match proposal.action.clone() {
+            ProposalAction::SetOwner { new_owner } => self.internal_set_owner(new_owner),
+            ProposalAction::SetOperator {
+                new_operator_account_id,
+            } => self.internal_set_operator_id(new_operator_account_id),
+            ProposalAction::SetTreasury {
+                new_treasury_account_id,
+            } => self.internal_set_treasury_id(new_treasury_account_id),
+            ProposalAction::UpdateOperationsControl {
+                update_operations_control_request,
+            } => self.internal_update_operations_control(update_operations_control_request),
+            ProposalAction::SetRewardFee {
+                numerator,
+                denominator,
+            } => self.internal_set_reward_fee(numerator, denominator),
+            ProposalAction::SetMinDeposit { min_deposit } => {
+                self.internal_set_min_deposit(min_deposit)
+            }
+            ProposalAction::AddValidator { validator, weight } => {
+                self.internal_add_validator(validator, weight)
+            }
+            ProposalAction::SetInstantUnstakeFee { min_fee, max_fee } => {
+                self.internal_set_instant_unstake_fee(min_fee, max_fee)
+            }
+            ProposalAction::SetMaxInstantUnstakeReserve { max_reserve } => {
+                self.internal_set_max_instant_unstake_reserve(max_reserve)
+            }
+            ProposalAction::WithdrawInstantUnstakeReserve { amount } => {
+                self.internal_withdraw_instant_unstake_reserve(amount)
+            }
+            ProposalAction::SetPerformanceMultiplierBounds { min, max } => {
+                self.internal_set_performance_multiplier_bounds(min, max)
+            }
+            ProposalAction::SetUnstakeStrategy { strategy } => {
+                self.internal_set_unstake_strategy(strategy)
+            }
+            ProposalAction::SetKeeperIncentives {
+                reward_per_action,
+                max_rewards_per_epoch,
+                rewards_fee_share,
+            } => self.internal_set_keeper_incentives(
+                reward_per_action,
+                max_rewards_per_epoch,
+                rewards_fee_share,
+            ),
+            ProposalAction::SetValidatorDelinquencyThreshold { threshold } => {
+                self.internal_set_validator_delinquency_threshold(threshold)
+            }
+            ProposalAction::SetProposalDelay { epochs } => self.internal_set_proposal_delay(epochs),
+            ProposalAction::SetCouncil {
+                members,
+                threshold,
+                approval_ttl_epochs,
+            } => self.internal_set_council(members, threshold, approval_ttl_epochs),
+            ProposalAction::SetUnstakeEpochCap { cap } => self.internal_set_unstake_epoch_cap(cap),
+            ProposalAction::GrantRole { role, account_id } => {
+                self.internal_grant_role(role, account_id)
+            }
+            ProposalAction::RevokeRole { role, account_id } => {
+                self.internal_revoke_role(role, account_id)
+            }
+            ProposalAction::AddPriceSubscriber { account_id } => {
+                self.internal_add_price_subscriber(account_id)
+            }
+            ProposalAction::RemovePriceSubscriber { account_id } => {
+                self.internal_remove_price_subscriber(account_id)
+            }
+            ProposalAction::SetExchangeRateBounds { bounds } => {
+                self.internal_set_exchange_rate_bounds(bounds)
+            }
+            ProposalAction::SetInsuranceFundCoverage { coverage } => {
+                self.internal_set_insurance_fund_coverage(coverage)
+            }
+            ProposalAction::SetInsuranceFeeShare { fee_share } => {
+                self.internal_set_insurance_fee_share(fee_share)
+            }
+            ProposalAction::CoverLossFromInsuranceFund { amount } => {
+                self.internal_cover_loss_from_insurance_fund(amount)
+            }
+            ProposalAction::UpdateValidator { validator, weight } => {
+                self.internal_set_validator_weight(validator, weight)
+            }
+            ProposalAction::RemoveValidator { validator } => {
+                self.internal_remove_validator(validator)
+            }
+            ProposalAction::UpdateFtMetadata { metadata } => {
+                self.internal_update_ft_metadata(metadata.0)
+            }
+            ProposalAction::Upgrade { .. } => unreachable!(),
+        }
+
+        Event::ProposalExecuted {
+            proposal_id,
+            action: proposal.action,
+        }
+        .emit();
+    }
+
+    /// Sets the accounts approving critical actions and how many distinct approvals an action
+    /// needs. Approvals count for `approval_ttl_epochs` epochs. An empty council with a threshold
+    /// of 0 turns the approvals off.
+    #[payable]
+    pub fn set_council(
+        &mut self,
+        members: Vec<AccountId>,
+        threshold: u32,
+        approval_ttl_epochs: U64,
+    ) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_set_council(members, threshold, approval_ttl_epochs.0);
+    }
+
+    pub(crate) fn internal_set_council(
+        &mut self,
+        members: Vec<AccountId>,
+        threshold: u32,
+        approval_ttl_epochs: EpochHeight,
+    ) {
+        let mut distinct_members = members.clone();
+        distinct_members.sort();
+        distinct_members.dedup();
+        require!(
+            distinct_members.len() == members.len()
+                && threshold as usize <= members.len()
+                && (threshold > 0 || members.is_empty()),
+            ERROR_INVALID_COUNCIL
+        );
+        self.internal_use_council_approvals(CouncilAction::SetCouncil {
+            members: members.clone(),
+            threshold,
+            approval_ttl_epochs,
+        });
+
+        self.council_members = members.clone();
+        self.council_threshold = threshold;
+        self.council_approval_ttl_epochs = approval_ttl_epochs;
+
+        Event::SetCouncil {
+            members,
+            threshold,
+            approval_ttl_epochs: U64(approval_ttl_epochs),
+        }
+        .emit();
+    }
+
+    #[payable]
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_grant_role(role, account_id);
+    }
+
+    pub(crate) fn internal_grant_role(&mut self, role: Role, account_id: AccountId) {
+        let mut account_ids = self.roles.get(&role).unwrap_or_default();
+        require!(
+            !account_ids.contains(&account_id),
+            ERROR_ROLE_ALREADY_GRANTED
+        );
+        account_ids.push(account_id.clone());
+        self.roles.insert(&role, &account_ids);
+
+        Event::RoleGranted { role, account_id }.emit();
+    }
+
+    #[payable]
+    pub fn revoke_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_revoke_role(role, account_id);
+    }
+
+    pub(crate) fn internal_revoke_role(&mut self, role: Role, account_id: AccountId) {
+        let mut account_ids = self.roles.get(&role).unwrap_or_default();
+        require!(account_ids.contains(&account_id), ERROR_ROLE_NOT_GRANTED);
+        account_ids.retain(|role_account_id| *role_account_id != account_id);
+        if account_ids.is_empty() {
+            self.roles.remove(&role);
+        } else {
+            self.roles.insert(&role, &account_ids);
+        }
+
+        Event::RoleRevoked { role, account_id }.emit();
+    }
+
+    /// Registers a contract to receive the NearX price through `on_nearx_price_update` after
+    /// every rewards distribution
+    #[payable]
+    pub fn add_price_subscriber(&mut self, account_id: AccountId) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_add_price_subscriber(account_id);
+    }
+
+    pub(crate) fn internal_add_price_subscriber(&mut self, account_id: AccountId) {
+        require!(
+            !self.price_subscribers.contains(&account_id),
+            ERROR_PRICE_SUBSCRIBER_ALREADY_ADDED
+        );
+        require!(
+            self.price_subscribers.len() < MAX_PRICE_SUBSCRIBERS,
+            ERROR_TOO_MANY_PRICE_SUBSCRIBERS
+        );
+        self.price_subscribers.push(account_id.clone());
+
+        Event::PriceSubscriberAdded { account_id }.emit();
+    }
+
+    #[payable]
+    pub fn remove_price_subscriber(&mut self, account_id: AccountId) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_remove_price_subscriber(account_id);
+    }
+
+    pub(crate) fn internal_remove_price_subscriber(&mut self, account_id: AccountId) {
+        require!(
+            self.price_subscribers.contains(&account_id),
+            ERROR_PRICE_SUBSCRIBER_NOT_FOUND
+        );
+        self.price_subscribers
+            .retain(|subscriber| *subscriber != account_id);
+
+        Event::PriceSubscriberRemoved { account_id }.emit();
+    }
+
+    /// Approves a critical action as a council member, approving again extends the expiry
+    #[payable]
+    pub fn approve_council_action(&mut self, action: CouncilAction) {
+        assert_one_yocto();
+        let approver = env::predecessor_account_id();
+        require!(
+            self.council_members.contains(&approver),
+            ERROR_NOT_COUNCIL_MEMBER
+        );
+
+        let expires_epoch = env::epoch_height() + self.council_approval_ttl_epochs;
+        let mut approvals: Vec<CouncilApproval> = self
+            .internal_get_council_approvals(&action)
+            .into_iter()
+            .filter(|approval| approval.approver != approver)
+            .collect();
+        approvals.push(CouncilApproval {
+            approver: approver.clone(),
+            expires_epoch,
+        });
+        self.council_approvals.insert(&action, &approvals);
+
+        Event::CouncilActionApproved {
+            action,
+            approver,
+            expires_epoch: U64(expires_epoch),
+        }
+        .emit();
+    }
+
+    #[payable]
+    pub fn revoke_council_approval(&mut self, action: CouncilAction) {
+        assert_one_yocto();
+        let approver = env::predecessor_account_id();
+        require!(
+            self.council_members.contains(&approver),
+            ERROR_NOT_COUNCIL_MEMBER
+        );
+
+        let approvals: Vec<CouncilApproval> = self
+            .internal_get_council_approvals(&action)
+            .into_iter()
+            .filter(|approval| approval.approver != approver)
+            .collect();
+        if approvals.is_empty() {
+            self.council_approvals.remove(&action);
+        } else {
+            self.council_approvals.insert(&action, &approvals);
+        }
+
+        Event::CouncilApprovalRevoked { action, approver }.emit();
+    }
+
     // View methods
 
     pub fn get_account_staked_balance(&self, account_id: AccountId) -> U128 {
@@ -665,6 +1739,11 @@ impl NearxPool {
             temp_owner: self.temp_owner.clone(),
             temp_operator: self.temp_operator.clone(),
             temp_treasury: self.temp_treasury.clone(),
+            granted_roles: self
+                .roles
+                .iter()
+                .map(|(role, account_ids)| RoleResponse { role, account_ids })
+                .collect(),
         }
     }
 
@@ -676,11 +1755,19 @@ impl NearxPool {
         let account = self.internal_get_account(&account_id);
         AccountResponse {
             account_id,
-            unstaked_balance: U128(account.unstaked_amount),
+            unstaked_balance: U128(account.unstaked_amount()),
             staked_balance: self
                 .staked_amount_from_num_shares_rounded_down(account.stake_shares)
                 .into(),
-            withdrawable_epoch: U64(account.withdrawable_epoch_height),
+            withdrawable_epoch: U64(account.withdrawable_epoch_height()),
+            unstake_requests: account
+                .unstake_requests
+                .iter()
+                .map(|request| UnstakeRequestResponse {
+                    amount: U128(request.amount),
+                    withdrawable_epoch: U64(request.withdrawable_epoch_height),
+                })
+                .collect(),
         }
     }
 
@@ -688,22 +1775,23 @@ impl NearxPool {
         let account = self.internal_get_account(&account_id);
         HumanReadableAccount {
             account_id,
-            unstaked_balance: U128(account.unstaked_amount),
+            unstaked_balance: U128(account.unstaked_amount()),
             staked_balance: self
                 .staked_amount_from_num_shares_rounded_down(account.stake_shares)
                 .into(),
-            can_withdraw: account.withdrawable_epoch_height <= env::epoch_height(),
+            can_withdraw: account.withdrawable_epoch_height() <= env::epoch_height(),
         }
     }
 
     pub fn get_number_of_accounts(&self) -> u64 {
-        self.accounts.len()
+        self.accounts.len() + self.legacy_accounts.len()
     }
 
     pub fn get_snapshot_users(&self, from: usize, length: usize) -> Vec<SnapshotUser> {
         self.accounts
             .keys_as_vector()
             .iter()
+            .chain(self.legacy_accounts.keys_as_vector().iter())
             .skip(from)
             .take(length)
             .map(|account_id| SnapshotUser {
@@ -715,11 +1803,22 @@ impl NearxPool {
 
     pub fn get_accounts(&self, from_index: u64, limit: u64) -> Vec<HumanReadableAccount> {
         let keys = self.accounts.keys_as_vector();
-        (from_index..std::cmp::min(from_index + limit, keys.len()))
-            .map(|index| self.get_account(keys.get(index).unwrap()))
+        let legacy_keys = self.legacy_accounts.keys_as_vector();
+        (from_index..std::cmp::min(from_index + limit, keys.len() + legacy_keys.len()))
+            .map(|index| {
+                if index < keys.len() {
+                    self.get_account(keys.get(index).unwrap())
+                } else {
+                    self.get_account(legacy_keys.get(index - keys.len()).unwrap())
+                }
+            })
             .collect()
     }
 
+    pub fn get_number_of_legacy_accounts(&self) -> u64 {
+        self.legacy_accounts.len()
+    }
+
     pub fn get_storage_usage(&self) -> U64 {
         U64(env::storage_usage())
     }
@@ -760,6 +1859,58 @@ impl NearxPool {
         }
     }
 
+    /// Exchange rate snapshots taken once per epoch from `from_epoch` on, oldest first
+    pub fn get_price_history(&self, from_epoch: U64, limit: u64) -> Vec<PriceSnapshotResponse> {
+        self.internal_price_history()
+            .into_iter()
+            .filter(|snapshot| snapshot.epoch >= from_epoch.0)
+            .take(limit as usize)
+            .map(|snapshot| PriceSnapshotResponse {
+                epoch: U64(snapshot.epoch),
+                total_staked: U128(snapshot.total_staked),
+                total_stake_shares: U128(snapshot.total_stake_shares),
+                price: U128(
+                    if snapshot.total_staked == 0 || snapshot.total_stake_shares == 0 {
+                        ONE_NEAR
+                    } else {
+                        proportional(ONE_NEAR, snapshot.total_staked, snapshot.total_stake_shares)
+                    },
+                ),
+            })
+            .collect()
+    }
+
+    /// Yearly growth of the NearX price between the latest snapshot and the oldest one at most
+    /// `window_epochs` before it. None without two snapshots in the window, a falling price
+    /// gives 0.
+    pub fn get_apy(&self, window_epochs: U64) -> Option<ApyResponse> {
+        let history = self.internal_price_history();
+        let latest = history.last()?;
+        let oldest = history.iter().find(|snapshot| {
+            snapshot.epoch + window_epochs.0 >= latest.epoch
+                && snapshot.epoch < latest.epoch
+                && snapshot.total_staked > 0
+                && snapshot.total_stake_shares > 0
+        })?;
+        if latest.total_stake_shares == 0 {
+            return None;
+        }
+
+        // latest price / oldest price - 1, annualized over the epochs in between
+        let latest_value = U256::from(latest.total_staked) * U256::from(oldest.total_stake_shares);
+        let oldest_value = U256::from(oldest.total_staked) * U256::from(latest.total_stake_shares);
+        let apy_basis_points = latest_value.saturating_sub(oldest_value)
+            * U256::from(10_000u64)
+            * U256::from(EPOCHS_PER_YEAR)
+            / (oldest_value * U256::from(latest.epoch - oldest.epoch));
+
+        Some(ApyResponse {
+            from_epoch: U64(oldest.epoch),
+            to_epoch: U64(latest.epoch),
+            apy_basis_points: U64(std::cmp::min(apy_basis_points, U256::from(u64::MAX)).as_u64()),
+        })
+    }
+
     pub fn get_validator_info(&self, validator: AccountId) -> ValidatorInfoResponse {
         let wrapped_validator_info = if let Some(val_info) = self.validator_info_map.get(&validator)
         {
@@ -801,6 +1952,70 @@ impl NearxPool {
             .collect()
     }
 
+    pub fn get_validators_performance(&self) -> Vec<ValidatorPerformanceResponse> {
+        let average_performance_score = self.get_average_performance_score();
+        let total_effective_weight = self.get_total_effective_weight(average_performance_score);
+        self.validator_info_map
+            .values()
+            .map(|wrapped_validator| {
+                let validator_info = wrapped_validator.into_current();
+                ValidatorPerformanceResponse {
+                    account_id: validator_info.account_id.clone(),
+                    weight: validator_info.weight,
+                    performance_score: validator_info.performance_score.map(U128),
+                    effective_weight: U128(self.get_validator_effective_weight(
+                        &validator_info,
+                        average_performance_score,
+                    )),
+                    expected_stake: U128(self.internal_get_validator_expected_stake(
+                        &validator_info,
+                        average_performance_score,
+                        total_effective_weight,
+                    )),
+                    failed_epoch_actions: validator_info.failed_epoch_actions,
+                    zero_reward_epochs: validator_info.zero_reward_epochs,
+                }
+            })
+            .collect()
+    }
+
+    pub fn get_unstake_strategy(&self) -> UnstakeStrategy {
+        self.unstake_strategy
+    }
+
+    pub fn get_keeper_incentives(&self) -> KeeperIncentivesResponse {
+        let rewards_paid_in_epoch = if self.keeper_rewards_epoch == env::epoch_height() {
+            self.keeper_rewards_paid_in_epoch
+        } else {
+            0
+        };
+
+        KeeperIncentivesResponse {
+            reward_per_action: U128(self.keeper_reward_per_action),
+            max_rewards_per_epoch: U128(self.max_keeper_rewards_per_epoch),
+            rewards_fee_share: self.keeper_rewards_fee_share,
+            budget_shares: U128(self.keeper_budget_shares),
+            budget: U128(
+                self.staked_amount_from_num_shares_rounded_down(self.keeper_budget_shares),
+            ),
+            rewards_paid_in_epoch: U128(rewards_paid_in_epoch),
+        }
+    }
+
+    pub fn get_validator_delinquency_threshold(&self) -> u32 {
+        self.validator_delinquency_threshold
+    }
+
+    /// Validators paused for delinquency which still need drain_unstake/drain_withdraw
+    pub fn get_validators_to_drain(&self) -> Vec<AccountId> {
+        self.validators_to_drain.clone()
+    }
+
+    /// Amount unstaked from the validator by rebalance_epoch which is restaked once withdrawn
+    pub fn get_rebalance_unstaked_amount(&self, validator: AccountId) -> U128 {
+        U128(self.rebalance_unstakes.get(&validator).unwrap_or(0))
+    }
+
     pub fn get_total_validator_weight(&self) -> u16 {
         self.total_validator_weight
     }
@@ -828,6 +2043,157 @@ impl NearxPool {
         }
     }
 
+    pub fn get_instant_unstake_reserve(&self) -> InstantUnstakeReserveResponse {
+        let pending_releases: Vec<(U64, U128)> = self
+            .instant_unstake_reserve_pending
+            .iter()
+            .map(|(release_epoch, amount)| (U64(release_epoch), U128(amount)))
+            .collect();
+
+        InstantUnstakeReserveResponse {
+            reserve: U128(self.instant_unstake_reserve),
+            pending: U128(pending_releases.iter().map(|(_, amount)| amount.0).sum()),
+            pending_releases,
+            max_reserve: U128(self.max_instant_unstake_reserve),
+            min_fee: self.instant_unstake_min_fee,
+            max_fee: self.instant_unstake_max_fee,
+            accumulated_fees: U128(self.accumulated_instant_unstake_fees),
+        }
+    }
+
+    /// Fee charged right now for instantly unstaking the given amount of NEAR
+    pub fn get_instant_unstake_fee(&self, amount: U128) -> U128 {
+        U128(self.get_instant_unstake_fee_amount(amount.0))
+    }
+
+    pub fn get_withdrawal_ticket(&self, ticket_id: String) -> Option<WithdrawalTicketResponse> {
+        self.withdrawal_tickets
+            .get(&ticket_id)
+            .map(|ticket| WithdrawalTicketResponse {
+                ticket_id,
+                owner_id: ticket.owner_id,
+                amount: U128(ticket.amount),
+                withdrawable_epoch: U64(ticket.withdrawable_epoch_height),
+                can_claim: ticket.withdrawable_epoch_height <= env::epoch_height(),
+            })
+    }
+
+    pub fn get_proposal_delay(&self) -> U64 {
+        U64(self.proposal_delay_epochs)
+    }
+
+    /// Owner actions waiting in the proposal queue
+    pub fn get_proposals(&self) -> Vec<ProposalResponse> {
+        self.proposals
+            .iter()
+            .map(|(proposal_id, proposal)| ProposalResponse {
+                proposal_id: U64(proposal_id),
+                action: proposal.action,
+                proposer: proposal.proposer,
+                created_epoch: U64(proposal.created_epoch),
+                executable_epoch: U64(proposal.executable_epoch),
+                can_execute: proposal.executable_epoch <= env::epoch_height(),
+            })
+            .collect()
+    }
+
+    pub fn get_insurance_fund(&self) -> InsuranceFundResponse {
+        InsuranceFundResponse {
+            shares: U128(self.insurance_fund_shares),
+            amount: U128(
+                self.staked_amount_from_num_shares_rounded_down(self.insurance_fund_shares),
+            ),
+            coverage: self.insurance_fund_coverage,
+            fee_share: self.insurance_fee_share,
+            uncovered_losses: U128(self.uncovered_validator_losses),
+        }
+    }
+
+    /// Insurance fund records from `from_epoch` on, oldest first
+    pub fn get_insurance_fund_history(
+        &self,
+        from_epoch: U64,
+        limit: u64,
+    ) -> Vec<InsuranceFundRecordResponse> {
+        self.insurance_fund_history
+            .iter()
+            .filter(|record| record.epoch >= from_epoch.0)
+            .take(limit as usize)
+            .map(|record| InsuranceFundRecordResponse {
+                epoch: U64(record.epoch),
+                fee_shares: U128(record.fee_shares),
+                deposited_shares: U128(record.deposited_shares),
+                burnt_shares: U128(record.burnt_shares),
+                covered_amount: U128(record.covered_amount),
+                fund_shares: U128(record.fund_shares),
+            })
+            .collect()
+    }
+
+    pub fn get_exchange_rate_bounds(&self) -> Option<ExchangeRateBounds> {
+        self.exchange_rate_bounds
+    }
+
+    /// Validator balance reports waiting for the operator to confirm or reject them
+    pub fn get_quarantined_balances(&self) -> Vec<QuarantinedBalanceResponse> {
+        self.quarantined_balances
+            .iter()
+            .map(
+                |(validator_id, quarantined_balance)| QuarantinedBalanceResponse {
+                    validator_id,
+                    staked: U128(quarantined_balance.staked),
+                    reported: U128(quarantined_balance.reported),
+                    epoch: U64(quarantined_balance.epoch),
+                    reported_unstaked: quarantined_balance.reported_unstaked.map(U128),
+                },
+            )
+            .collect()
+    }
+
+    pub fn get_price_subscribers(&self) -> Vec<AccountId> {
+        self.price_subscribers.clone()
+    }
+
+    pub fn get_price_update_nonce(&self) -> U64 {
+        U64(self.price_update_nonce)
+    }
+
+    pub fn get_unstake_epoch_cap(&self) -> Option<UnstakeEpochCap> {
+        self.unstake_epoch_cap
+    }
+
+    /// Unstaked NEAR waiting for the unstake epoch cap
+    pub fn get_unstake_queue(&self) -> Vec<UnstakeQueueEntryResponse> {
+        self.unstake_queue
+            .iter()
+            .map(|(epoch, amount)| UnstakeQueueEntryResponse {
+                epoch: U64(epoch),
+                amount: U128(amount),
+            })
+            .collect()
+    }
+
+    pub fn get_council(&self) -> CouncilResponse {
+        CouncilResponse {
+            members: self.council_members.clone(),
+            threshold: self.council_threshold,
+            approval_ttl_epochs: U64(self.council_approval_ttl_epochs),
+        }
+    }
+
+    pub fn get_council_approvals(&self, action: CouncilAction) -> CouncilActionApprovalsResponse {
+        let approvals = self
+            .internal_get_council_approvals(&action)
+            .into_iter()
+            .map(|approval| CouncilApprovalResponse {
+                approver: approval.approver,
+                expires_epoch: U64(approval.expires_epoch),
+            })
+            .collect();
+
+        CouncilActionApprovalsResponse { action, approvals }
+    }
+
     pub fn get_near_from_nearx(&self, nearx_amount: U128) -> U128 {
         U128(self.staked_amount_from_num_shares_rounded_down(nearx_amount.0))
     }
@@ -1,45 +1,106 @@
+use crate::constants::MAX_UNSTAKE_REQUESTS;
 use crate::contract::*;
-use crate::state::Account;
+use crate::errors::ERROR_NOT_ENOUGH_STORAGE_DEPOSIT;
+use crate::state::{Account, UnstakeRequest};
 use near_contract_standards::storage_management::{
     StorageBalance, StorageBalanceBounds, StorageManagement,
 };
-use near_sdk::{assert_one_yocto, env, log, AccountId, Balance, Promise};
+use near_sdk::{assert_one_yocto, env, log, require, AccountId, Balance, Promise, StorageUsage};
 
-/// Fixed amount of storage bytes. The contract is light weight in terms of storage amount.
-/// The below storage spec should cover all storage needs of the contract
-const STORAGE_AMOUNT_BYTES: usize = 250;
+/// Storage bytes paid for at registration. They cover an account holding NearX with the max
+/// number of unstake requests, 250 bytes for two requests and 24 bytes for each further one
+const STORAGE_AMOUNT_BYTES: StorageUsage = 250 + 24 * (MAX_UNSTAKE_REQUESTS as u64 - 2);
+
+/// Bytes of a storage deposit record on top of the account id, the record is only kept for
+/// accounts which deposited more than the registration minimum
+const STORAGE_DEPOSIT_RECORD_BYTES: StorageUsage = 64;
 
 impl NearxPool {
     pub(crate) fn internal_storage_balance_of(
         &self,
         account_id: &AccountId,
     ) -> Option<StorageBalance> {
-        if self.accounts.get(account_id).is_some() {
-            Some(StorageBalance {
-                total: self.storage_balance_bounds().min,
-                available: 0.into(),
-            })
+        self.internal_get_account_option(account_id).map(|account| {
+            let total = self.internal_storage_deposit(account_id);
+            let used = self.internal_storage_usage(account_id, &account) as Balance
+                * env::storage_byte_cost();
+            StorageBalance {
+                total: total.into(),
+                available: total.saturating_sub(used).into(),
+            }
+        })
+    }
+
+    /// Storage deposit of the account, accounts without a record are covered by the minimum
+    pub(crate) fn internal_storage_deposit(&self, account_id: &AccountId) -> Balance {
+        self.storage_deposits
+            .get(account_id)
+            .unwrap_or_else(|| self.storage_balance_bounds().min.0)
+    }
+
+    fn internal_set_storage_deposit(&mut self, account_id: &AccountId, amount: Balance) {
+        if amount > self.storage_balance_bounds().min.0 {
+            self.storage_deposits.insert(account_id, &amount);
         } else {
-            None
+            self.storage_deposits.remove(account_id);
+        }
+    }
+
+    /// Storage bytes the account is paying for. Anything kept per account outside of the
+    /// registration layout needs to be counted here
+    pub(crate) fn internal_storage_usage(
+        &self,
+        account_id: &AccountId,
+        account: &Account,
+    ) -> StorageUsage {
+        let registration_layout = Account {
+            stake_shares: 0,
+            unstake_requests: vec![
+                UnstakeRequest {
+                    amount: 0,
+                    withdrawable_epoch_height: 0,
+                };
+                MAX_UNSTAKE_REQUESTS
+            ],
+        };
+        let account_bytes = account.try_to_vec().unwrap().len() as StorageUsage;
+        let registration_bytes = registration_layout.try_to_vec().unwrap().len() as StorageUsage;
+
+        let mut storage_usage =
+            STORAGE_AMOUNT_BYTES + account_bytes.saturating_sub(registration_bytes);
+        if self.storage_deposits.contains_key(account_id) {
+            storage_usage += STORAGE_DEPOSIT_RECORD_BYTES + account_id.as_str().len() as u64;
         }
+        storage_usage
+    }
+
+    /// Makes sure the account storage deposit still covers the account after it grew
+    pub(crate) fn assert_storage_deposit_covers(&self, account_id: &AccountId, account: &Account) {
+        require!(
+            self.internal_storage_deposit(account_id)
+                >= self.internal_storage_usage(account_id, account) as Balance
+                    * env::storage_byte_cost(),
+            ERROR_NOT_ENOUGH_STORAGE_DEPOSIT
+        );
     }
 
     pub(crate) fn internal_register_account(&mut self, account_id: &AccountId) {
-        if self
-            .accounts
-            .insert(account_id, &Account::default())
-            .is_some()
+        if self.legacy_accounts.get(account_id).is_some()
+            || self
+                .accounts
+                .insert(account_id, &Account::default())
+                .is_some()
         {
             env::panic_str("The account is already registered");
         }
     }
 }
 
-#[allow(unused_variables)]
 #[near_bindgen]
 impl StorageManagement for NearxPool {
-    // `registration_only` doesn't affect the implementation for vanilla fungible token.
-    #[allow(unused_variables)]
+    /// Registers the account with the minimum storage balance. Registered accounts can top up
+    /// their storage balance, unless `registration_only` is set in which case the whole deposit
+    /// is refunded.
     #[payable]
     fn storage_deposit(
         &mut self,
@@ -48,10 +109,18 @@ impl StorageManagement for NearxPool {
     ) -> StorageBalance {
         let amount: Balance = env::attached_deposit();
         let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
-        if let Some(account) = self.accounts.get(&account_id) {
-            log!("The account is already registered, refunding the deposit");
-            if amount > 0 {
-                Promise::new(env::predecessor_account_id()).transfer(amount);
+        if let Some(account) = self.internal_get_account_option(&account_id) {
+            if registration_only.unwrap_or(false) {
+                log!("The account is already registered, refunding the deposit");
+                if amount > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(amount);
+                }
+            } else if amount > 0 {
+                self.internal_set_storage_deposit(
+                    &account_id,
+                    self.internal_storage_deposit(&account_id) + amount,
+                );
+                self.assert_storage_deposit_covers(&account_id, &account);
             }
         } else {
             let min_balance = self.storage_balance_bounds().min.0;
@@ -68,23 +137,28 @@ impl StorageManagement for NearxPool {
         self.internal_storage_balance_of(&account_id).unwrap()
     }
 
-    /// While storage_withdraw normally allows the caller to retrieve `available` balance, the basic
-    /// Fungible Token implementation sets storage_balance_bounds.min == storage_balance_bounds.max,
-    /// which means available balance will always be 0. So this implementation:
-    /// * panics if `amount > 0`
-    /// * never transfers Ⓝ to caller
-    /// * returns a `storage_balance` struct if `amount` is 0
+    /// Withdraws the storage balance which is not used by the account, all of it if no amount
+    /// is given
     #[payable]
     fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
         assert_one_yocto();
         let predecessor_account_id = env::predecessor_account_id();
         if let Some(storage_balance) = self.internal_storage_balance_of(&predecessor_account_id) {
-            match amount {
-                Some(amount) if amount.0 > 0 => {
-                    env::panic_str("The amount is greater than the available storage balance");
-                }
-                _ => storage_balance,
+            let amount = amount.unwrap_or(storage_balance.available).0;
+            require!(
+                amount <= storage_balance.available.0,
+                "The amount is greater than the available storage balance"
+            );
+
+            if amount > 0 {
+                self.internal_set_storage_deposit(
+                    &predecessor_account_id,
+                    storage_balance.total.0 - amount,
+                );
+                Promise::new(predecessor_account_id.clone()).transfer(amount);
             }
+            self.internal_storage_balance_of(&predecessor_account_id)
+                .unwrap()
         } else {
             env::panic_str(
                 format!("The account {} is not registered", &predecessor_account_id).as_str(),
@@ -104,7 +178,7 @@ impl StorageManagement for NearxPool {
 
         let account_id = env::predecessor_account_id();
 
-        if self.accounts.get(&account_id).is_none() {
+        if self.internal_get_account_option(&account_id).is_none() {
             return false;
         }
 
@@ -112,7 +186,18 @@ impl StorageManagement for NearxPool {
 
         // if account registered check if amount staked and unstaked is 0
         if account.is_empty() {
-            self.accounts.remove(&account_id);
+            // accounts like the treasury are registered without paying the minimum, so only
+            // the recorded deposit on top of it is refunded
+            let refund = self
+                .storage_deposits
+                .remove(&account_id)
+                .map_or(0, |deposit| {
+                    deposit.saturating_sub(self.storage_balance_bounds().min.0)
+                });
+            self.internal_remove_account(&account_id);
+            if refund > 0 {
+                Promise::new(account_id).transfer(refund);
+            }
         } else {
             panic!("Account is not empty!");
         }
@@ -122,9 +207,10 @@ impl StorageManagement for NearxPool {
 
     fn storage_balance_bounds(&self) -> StorageBalanceBounds {
         let required_storage_balance = STORAGE_AMOUNT_BYTES as Balance * env::storage_byte_cost();
+        // covers the max number of unstake requests, so unstaking never needs a top up
         StorageBalanceBounds {
             min: required_storage_balance.into(),
-            max: Some(required_storage_balance.into()),
+            max: None,
         }
     }
 
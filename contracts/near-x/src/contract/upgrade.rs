@@ -1,5 +1,11 @@
-use crate::constants::NEW_VALIDATOR_MAP;
+use crate::constants::{
+    COUNCIL_APPROVALS_MAP, FT_METADATA, INSTANT_UNSTAKE_RESERVE_PENDING_MAP,
+    INSURANCE_FUND_HISTORY, NEW_ACCOUNTS_MAP, PRICE_HISTORY, PROPOSALS_MAP,
+    QUARANTINED_BALANCES_MAP, REBALANCE_UNSTAKES_MAP, ROLES_MAP, STORAGE_DEPOSITS_MAP,
+    UNSTAKE_QUEUE_MAP, WITHDRAWAL_TICKETS_MAP,
+};
 use crate::contract::*;
+use crate::fungible_token::default_ft_metadata;
 use near_sdk::*;
 
 #[near_bindgen]
@@ -13,19 +19,7 @@ impl NearxPool {
     #[private]
     pub fn migrate() -> Self {
         require!(env::state_exists());
-        let old_contract = env::state_read::<LegacyNearxPoolV3>().expect("ERR_NOT_INITIALIZED");
-
-        let mut new_validator_info_map = UnorderedMap::new(NEW_VALIDATOR_MAP.as_bytes());
-
-        for old_validator in old_contract.validator_info_map.values() {
-            let account_id = old_validator.account_id.clone();
-            let new_validator_info =
-                ValidatorInfoWrapper::LegacyValidatorInfo(old_validator).into_current();
-            new_validator_info_map.insert(
-                &account_id,
-                &ValidatorInfoWrapper::ValidatorInfo(new_validator_info),
-            );
-        }
+        let old_contract = env::state_read::<LegacyNearxPoolV4>().expect("ERR_NOT_INITIALIZED");
 
         NearxPool {
             owner_account_id: old_contract.owner_account_id,
@@ -37,8 +31,11 @@ impl NearxPool {
             reconciled_epoch_stake_amount: old_contract.reconciled_epoch_stake_amount,
             reconciled_epoch_unstake_amount: old_contract.reconciled_epoch_unstake_amount,
             last_reconcilation_epoch: old_contract.last_reconcilation_epoch,
-            accounts: old_contract.accounts,
-            validator_info_map: new_validator_info_map,
+            // accounts are moved to the new layout lazily, see migrate_accounts
+            accounts: UnorderedMap::new(NEW_ACCOUNTS_MAP.as_bytes()),
+            legacy_accounts: old_contract.accounts,
+            // baseline validators decode as LegacyValidatorInfoV2 and are upgraded when touched
+            validator_info_map: old_contract.validator_info_map,
             total_validator_weight: old_contract.total_validator_weight,
             min_deposit_amount: old_contract.min_deposit_amount,
             operator_account_id: old_contract.operator_account_id,
@@ -53,7 +50,7 @@ impl NearxPool {
             last_reward_fee_set_epoch: old_contract.last_reward_fee_set_epoch,
             operations_control: OperationControls {
                 stake_paused: old_contract.operations_control.stake_paused,
-                direct_stake_paused: false,
+                direct_stake_paused: old_contract.operations_control.direct_stake_paused,
                 unstaked_paused: old_contract.operations_control.unstaked_paused,
                 withdraw_paused: old_contract.operations_control.withdraw_paused,
                 staking_epoch_paused: old_contract.operations_control.staking_epoch_paused,
@@ -67,9 +64,75 @@ impl NearxPool {
                     .sync_validator_balance_paused,
                 ft_transfer_paused: old_contract.operations_control.ft_transfer_paused,
                 ft_transfer_call_paused: old_contract.operations_control.ft_transfer_call_paused,
+                instant_unstake_paused: false,
+                rebalance_epoch_paused: false,
             },
             min_storage_reserve: old_contract.min_storage_reserve,
+            instant_unstake_reserve: 0,
+            instant_unstake_reserve_pending: TreeMap::new(
+                INSTANT_UNSTAKE_RESERVE_PENDING_MAP.as_bytes(),
+            ),
+            max_instant_unstake_reserve: 0,
+            instant_unstake_min_fee: Fraction::new(3, 1000),
+            instant_unstake_max_fee: Fraction::new(3, 100),
+            accumulated_instant_unstake_fees: 0,
+            withdrawal_tickets: UnorderedMap::new(WITHDRAWAL_TICKETS_MAP.as_bytes()),
+            last_withdrawal_ticket_id: 0,
+            min_performance_multiplier: Fraction::new(1, 1),
+            max_performance_multiplier: Fraction::new(1, 1),
+            validator_delinquency_threshold: 0,
+            validators_to_drain: vec![],
+            run_epoch_cursor: RunEpochCursor::default(),
+            keeper_reward_per_action: 0,
+            max_keeper_rewards_per_epoch: 0,
+            keeper_rewards_fee_share: Fraction::new(0, 1),
+            keeper_budget_shares: 0,
+            keeper_rewards_epoch: 0,
+            keeper_rewards_paid_in_epoch: 0,
+            unstake_strategy: UnstakeStrategy::LargestFirst,
+            rebalance_unstakes: UnorderedMap::new(REBALANCE_UNSTAKES_MAP.as_bytes()),
+            storage_deposits: LookupMap::new(STORAGE_DEPOSITS_MAP.as_bytes()),
+            ft_metadata: LazyOption::new(FT_METADATA.as_bytes(), Some(&default_ft_metadata())),
+            proposals: UnorderedMap::new(PROPOSALS_MAP.as_bytes()),
+            last_proposal_id: 0,
+            proposal_delay_epochs: 0,
+            council_members: vec![],
+            council_threshold: 0,
+            council_approval_ttl_epochs: 0,
+            council_approvals: UnorderedMap::new(COUNCIL_APPROVALS_MAP.as_bytes()),
+            roles: UnorderedMap::new(ROLES_MAP.as_bytes()),
+            unstake_epoch_cap: None,
+            unstake_queue: TreeMap::new(UNSTAKE_QUEUE_MAP.as_bytes()),
+            unstake_queue_amount: 0,
+            price_history: Vector::new(PRICE_HISTORY.as_bytes()),
+            price_snapshots_taken: 0,
+            price_subscribers: vec![],
+            price_update_nonce: 0,
+            exchange_rate_bounds: None,
+            quarantined_balances: UnorderedMap::new(QUARANTINED_BALANCES_MAP.as_bytes()),
+            insurance_fund_shares: 0,
+            insurance_fund_coverage: Fraction::new(0, 1),
+            uncovered_validator_losses: 0,
+            insurance_fee_share: Fraction::new(0, 1),
+            insurance_fund_history: Vector::new(INSURANCE_FUND_HISTORY.as_bytes()),
+        }
+    }
+
+    /// Moves up to `limit` accounts from the legacy single unstake slot layout to the
+    /// unstake request queue. Returns the number of legacy accounts left.
+    pub fn migrate_accounts(&mut self, limit: u64) -> u64 {
+        self.assert_operator_or_owner();
+
+        let account_ids: Vec<AccountId> =
+            self.legacy_accounts.keys().take(limit as usize).collect();
+        for account_id in account_ids.iter() {
+            if let Some(legacy_account) = self.legacy_accounts.remove(account_id) {
+                self.accounts
+                    .insert(account_id, &legacy_account.into_current());
+            }
         }
+
+        self.legacy_accounts.len()
     }
 }
 
@@ -79,6 +142,8 @@ mod upgrade {
     use near_sys as sys;
 
     use super::*;
+    use crate::errors::ERROR_UPGRADE_NOT_PROPOSED;
+    use crate::events::Event;
 
     /// Gas for completing the upgrade call
     pub const GAS_FOR_COMPLETING_UPGRADE_CALL: Gas = Gas(10 * 1_000_000_000_000);
@@ -88,19 +153,60 @@ mod upgrade {
     /// Gas for calling `get_summary` method
     pub const GAS_FOR_GET_SUMMARY_CALL: Gas = Gas(15 * 1_000_000_000_000);
 
+    impl NearxPool {
+        /// Uses up the proposal to deploy the code with this hash once its delay has passed. Does
+        /// nothing while owner actions are not timelocked.
+        fn internal_use_upgrade_proposal(&mut self, code_hash: json_types::Base58CryptoHash) {
+            if self.proposal_delay_epochs == 0 {
+                return;
+            }
+
+            let (proposal_id, proposal) = self
+                .proposals
+                .iter()
+                .find(|(_, proposal)| {
+                    proposal.executable_epoch <= env::epoch_height()
+                        && matches!(
+                            proposal.action,
+                            ProposalAction::Upgrade { code_hash: proposed } if proposed == code_hash
+                        )
+                })
+                .unwrap_or_else(|| env::panic_str(ERROR_UPGRADE_NOT_PROPOSED));
+            self.proposals.remove(&proposal_id);
+
+            Event::ProposalExecuted {
+                proposal_id: U64(proposal_id),
+                action: proposal.action,
+            }
+            .emit();
+        }
+    }
+
     /// Self upgrade and call migrate, optimizes gas by not loading into memory the code.
     /// Takes as input non serialized set of bytes of the code.
     #[no_mangle]
     pub fn upgrade() {
         env::setup_panic_hook();
-        let contract: NearxPool = env::state_read().expect("ERR_CONTRACT_IS_NOT_INITIALIZED");
-        contract.assert_owner_calling();
+        let mut contract: NearxPool = env::state_read().expect("ERR_CONTRACT_IS_NOT_INITIALIZED");
+        contract.assert_role(Role::Upgrader);
         let current_id = env::current_account_id().as_bytes().to_vec();
         let migrate_method_name = b"migrate".to_vec();
         let get_summary_method_name = b"get_contract_summary".to_vec();
         unsafe {
-            // Load input (wasm code) into register 0.
+            // Load input (wasm code) into register 0 and its sha256 hash into register 1.
             sys::input(0);
+            sys::sha256(u64::MAX, 0, 1);
+        }
+        // The council has to approve the hash of the code being deployed
+        let mut code_hash = CryptoHash::default();
+        code_hash.copy_from_slice(&env::read_register(1).expect("ERR_CODE_HASH_NOT_COMPUTED"));
+        // once owner actions are timelocked, the upgrade has to be proposed as well
+        contract.internal_use_upgrade_proposal(code_hash.into());
+        contract.internal_use_council_approvals(CouncilAction::Upgrade {
+            code_hash: code_hash.into(),
+        });
+        env::state_write(&contract);
+        unsafe {
             // Create batch action promise for the current contract ID
             let promise_id =
                 sys::promise_batch_create(current_id.len() as _, current_id.as_ptr() as _);
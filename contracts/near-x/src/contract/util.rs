@@ -1,5 +1,6 @@
 use crate::contract::NearxPool;
 use crate::errors::*;
+use crate::state::Role;
 use near_sdk::{env, require};
 
 impl NearxPool {
@@ -19,6 +20,28 @@ impl NearxPool {
         );
     }
 
+    /// Asserts that the caller is the owner or holds the role, the operator holds the roles
+    /// it always had.
+    pub fn assert_role(&self, role: Role) {
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == self.owner_account_id
+                || (role.is_operator_role() && caller == self.operator_account_id)
+                || self.roles.get(&role).unwrap_or_default().contains(&caller),
+            ERROR_UNAUTHORIZED
+        );
+    }
+
+    /// Asserts that owner actions are not timelocked, once a proposal delay is set they have
+    /// to go through propose and execute_proposal. Pausing and draining validators and funding
+    /// the keeper budget stay immediate
+    pub fn assert_not_timelocked(&self) {
+        require!(
+            self.proposal_delay_epochs == 0,
+            ERROR_OWNER_ACTION_TIMELOCKED
+        );
+    }
+
     pub fn assert_min_deposit_amount(&self, amount: u128) {
         require!(amount >= self.min_deposit_amount, ERROR_MIN_DEPOSIT);
     }
@@ -44,7 +67,7 @@ impl NearxPool {
     pub fn assert_withdraw_not_paused(&self) {
         require!(
             !self.operations_control.withdraw_paused,
-            ERROR_UNSTAKING_PAUSED
+            ERROR_WITHDRAW_PAUSED
         );
     }
 
@@ -96,4 +119,18 @@ impl NearxPool {
             ERROR_FT_TRANSFER_CALL_PAUSED
         );
     }
+
+    pub fn assert_instant_unstake_not_paused(&self) {
+        require!(
+            !self.operations_control.instant_unstake_paused,
+            ERROR_INSTANT_UNSTAKE_PAUSED
+        );
+    }
+
+    pub fn assert_rebalance_epoch_not_paused(&self) {
+        require!(
+            !self.operations_control.rebalance_epoch_paused,
+            ERROR_REBALANCE_EPOCH_PAUSED
+        );
+    }
 }
@@ -11,12 +11,28 @@ pub const ERROR_NOT_ENOUGH_UNSTAKED_AMOUNT_TO_WITHDRAW: &str =
 pub const ERROR_UNSTAKED_AMOUNT_IN_UNBONDING_PERIOD: &str =
     "Unstaking amount still in unbonding period";
 pub const ERROR_NOT_ENOUGH_BALANCE_FOR_STORAGE: &str = "Not enough balance for storage staking";
+pub const ERROR_NOT_ENOUGH_STORAGE_DEPOSIT: &str =
+    "Storage deposit does not cover the account storage, top it up with storage_deposit";
+pub const ERROR_TOO_MANY_UNSTAKE_REQUESTS: &str =
+    "Too many pending unstake requests, withdraw or wait for them to mature";
 pub const ERROR_CANNOT_UNSTAKED_MORE_THAN_STAKED_AMOUNT: &str =
     "Cannot unstake more than staked amount";
 pub const ERROR_NOT_ENOUGH_STAKED_AMOUNT_TO_UNSTAKE: &str = "Not enough staked amount to unstake";
 pub const ERROR_NOT_ENOUGH_CONTRACT_STAKED_AMOUNT: &str = "Not enough staked amount in contract";
 pub const ERROR_NON_POSITIVE_STAKE_AMOUNT: &str = "Amount to stake must be greater than 0";
 pub const ERROR_NON_POSITIVE_STAKE_SHARES: &str = "nearx to be minted must be greater than 0";
+pub const ERROR_NOT_ENOUGH_INSTANT_UNSTAKE_LIQUIDITY: &str =
+    "Not enough liquidity in the instant unstake reserve";
+pub const ERROR_INSTANT_UNSTAKE_RESERVE_CAP_EXCEEDED: &str =
+    "Instant unstake reserve cannot go above the max reserve amount";
+pub const ERROR_NOT_ENOUGH_INSTANT_UNSTAKE_RESERVE: &str =
+    "Amount is above the instant unstake reserve";
+pub const ERROR_INVALID_INSTANT_UNSTAKE_FEE: &str =
+    "Instant unstake min fee must be lower than max fee and max fee at most 10%";
+pub const ERROR_INVALID_UNSTAKE_EPOCH_CAP: &str =
+    "Unstake epoch cap should be at least 1000 NEAR or a share of the total staked between 0 and 1";
+pub const ERROR_INVALID_KEEPER_REWARDS_FEE_SHARE: &str =
+    "Keeper share of the rewards fee should be a fraction between 0 and 1";
 
 /// Misc
 pub const ERROR_TEMP_OWNER_NOT_SET: &str = "Temp owner has not been set to any account";
@@ -30,6 +46,7 @@ pub const ERROR_MIN_BALANCE_FOR_CONTRACT_STORAGE: &str =
 pub const ERROR_CONTRACT_ALREADY_INITIALIZED: &str = "Contract has already been initialized";
 pub const ERROR_NO_STAKING_KEY: &str = "Staking key not present";
 pub const ERROR_NOT_ENOUGH_GAS: &str = "Not enough pre-paid gas";
+pub const ERROR_NON_POSITIVE_MAX_ACTIONS: &str = "Max actions should be greater than 0";
 pub const ERROR_REQUIRE_ONE_YOCTO_NEAR: &str = "Function requires at least one yocto near";
 pub const ERROR_EXPECT_RESULT_ON_CALLBACK: &str = "Callback expected result on callback";
 pub const ERROR_MIN_DEPOSIT_TOO_HIGH: &str = "Min deposit too high";
@@ -53,8 +70,64 @@ pub const ERROR_TREASURY_CURRENT_CONTRACT_SAME: &str =
     "Treasury and current contract cannot be the same";
 pub const ERROR_TREASURY_SAME: &str = "Treasury cannot be same as current treasury";
 pub const ERROR_SENDER_RECEIVER_SAME: &str = "Sender and receiver cannot be the same";
+pub const ERROR_TRANSFER_TO_POOL: &str = "NearX can only be sent to the pool with ft_transfer_call";
+pub const ERROR_ONLY_NEARX_ACCEPTED: &str = "The pool only accepts NearX";
 pub const ERROR_REQUIRE_AMOUNT_GT_0: &str = "Amount should be greater than 0";
 
+/// Withdrawal tickets
+pub const ERROR_WITHDRAWAL_TICKET_NOT_FOUND: &str = "Withdrawal ticket not found";
+pub const ERROR_NOT_WITHDRAWAL_TICKET_OWNER: &str = "Only the ticket owner can do this";
+pub const ERROR_NOT_ENOUGH_WITHDRAWAL_TICKET_STORAGE_DEPOSIT: &str =
+    "Not enough deposit attached to pay for the withdrawal ticket storage";
+
+/// Token metadata
+pub const ERROR_INVALID_FT_METADATA_SPEC: &str = "Metadata spec should be ft-1.0.0";
+pub const ERROR_FT_METADATA_DECIMALS_CHANGED: &str = "NearX decimals cannot be changed";
+pub const ERROR_INVALID_FT_METADATA_ICON: &str = "Icon should be a data URI of at most 10KB";
+pub const ERROR_INVALID_FT_METADATA_REFERENCE_HASH: &str = "Reference hash should be 32 bytes";
+
+/// Governance proposals
+pub const ERROR_OWNER_ACTION_TIMELOCKED: &str =
+    "Owner actions are timelocked, they need to go through a proposal";
+pub const ERROR_PROPOSAL_NOT_FOUND: &str = "Proposal not found";
+pub const ERROR_PROPOSAL_IN_DELAY_PERIOD: &str = "Proposal is still in its delay period";
+pub const ERROR_UPGRADE_NOT_PROPOSED: &str =
+    "Upgrade to this code is not proposed or still in its delay period";
+pub const ERROR_UPGRADE_PROPOSAL_RUNS_THROUGH_UPGRADE: &str =
+    "Upgrade proposals are executed by calling upgrade with the proposed code";
+
+/// Roles
+pub const ERROR_ROLE_ALREADY_GRANTED: &str = "Account already has the role";
+pub const ERROR_ROLE_NOT_GRANTED: &str = "Account does not have the role";
+
+/// Exchange rate guard
+pub const ERROR_INVALID_EXCHANGE_RATE_BOUNDS: &str =
+    "Exchange rate bounds should be non zero fractions of at most 1";
+pub const ERROR_NO_QUARANTINED_BALANCE: &str = "Validator has no quarantined balance";
+pub const ERROR_QUARANTINED_BALANCE_STALE: &str =
+    "Validator stake changed since the balance was quarantined";
+
+/// Insurance fund
+pub const ERROR_INVALID_INSURANCE_FUND_COVERAGE: &str =
+    "Insurance fund coverage should be a fraction of at most 1";
+pub const ERROR_INVALID_INSURANCE_FEE_SHARE: &str =
+    "Insurance fee share should be a fraction of at most 1";
+pub const ERROR_NOT_ENOUGH_INSURANCE_FUND: &str = "Insurance fund is too small to cover the amount";
+pub const ERROR_COVERAGE_ABOVE_UNCOVERED_LOSSES: &str =
+    "Amount is above the validator losses left uncovered";
+
+/// Price subscribers
+pub const ERROR_PRICE_SUBSCRIBER_ALREADY_ADDED: &str = "Account is already a price subscriber";
+pub const ERROR_PRICE_SUBSCRIBER_NOT_FOUND: &str = "Account is not a price subscriber";
+pub const ERROR_TOO_MANY_PRICE_SUBSCRIBERS: &str = "Too many price subscribers";
+
+/// Council
+pub const ERROR_NOT_COUNCIL_MEMBER: &str = "Only council members can do this";
+pub const ERROR_NOT_ENOUGH_COUNCIL_APPROVALS: &str =
+    "Action does not have enough council approvals";
+pub const ERROR_INVALID_COUNCIL: &str =
+    "Council members should be distinct with a threshold between 1 and their number, or empty with a threshold of 0";
+
 /// Validator related errors
 pub const ERROR_VALIDATOR_NOT_PAUSED: &str = "Validator not paused";
 pub const ERROR_INVALID_VALIDATOR_REMOVAL: &str = "Cannot remove this validator";
@@ -79,6 +152,9 @@ pub const ERROR_VALIDATOR_IS_PRIVATE: &str =
 pub const ERROR_AMOUNT_GREATER_THEN_MAX_UNSTAKABLE_LIMIT: &str =
     "Amount greater then max unstakable limit";
 
+pub const ERROR_INVALID_PERFORMANCE_MULTIPLIER_BOUNDS: &str =
+    "Performance multiplier bounds should satisfy min <= 1 <= max <= 10";
+
 /// Validator sync errors
 pub const ERROR_VALIDATOR_TOTAL_BALANCE_OUT_OF_SYNC: &str = "Total balance is out of sync";
 pub const ERROR_VALIDATOR_STAKED_BALANCE_OUT_OF_SYNC: &str =
@@ -98,3 +174,7 @@ pub const ERROR_AUTOCOMPOUNDING_EPOCH_PAUSED: &str = "Autocompounding epoch paus
 pub const ERROR_SYNC_VALIDATOR_BALANCE_PAUSED: &str = "Sync validator balance paused";
 pub const ERROR_FT_TRANSFER_PAUSED: &str = "ft_transfer paused";
 pub const ERROR_FT_TRANSFER_CALL_PAUSED: &str = "ft_transfer_call paused";
+pub const ERROR_INSTANT_UNSTAKE_PAUSED: &str = "Instant unstake paused";
+pub const ERROR_REBALANCE_EPOCH_PAUSED: &str = "Rebalance epoch paused";
+pub const ERROR_UNPAUSE_ONE_GROUP_AT_A_TIME: &str =
+    "Operations are resumed one group at a time: deposits, withdrawals, epoch actions or transfers";
@@ -1,6 +1,15 @@
 use crate::contract::OperationControls;
-use crate::state::Fraction;
-use near_sdk::{json_types::U128, log, serde::Serialize, serde_json::json, AccountId};
+use crate::state::{
+    CouncilAction, ExchangeRateBounds, Fraction, ProposalAction, Role, UnstakeEpochCap,
+    UnstakeStrategy,
+};
+use near_sdk::{
+    json_types::{Base64VecU8, U128, U64},
+    log,
+    serde::Serialize,
+    serde_json::json,
+    AccountId,
+};
 
 const EVENT_STANDARD: &str = "nearx";
 const EVENT_STANDARD_VERSION: &str = "1.0.0";
@@ -35,6 +44,22 @@ pub enum Event {
         validator_id: AccountId,
         amount: U128,
     },
+    RebalanceEpochAttempt {
+        validator_id: AccountId,
+        amount: U128,
+    },
+    RebalanceEpochCallbackSuccess {
+        validator_id: AccountId,
+        amount: U128,
+    },
+    RebalanceEpochCallbackFailed {
+        validator_id: AccountId,
+        amount: U128,
+    },
+    RebalanceRestake {
+        validator_id: AccountId,
+        amount: U128,
+    },
     WithdrawEpochAttempt {
         validator_id: AccountId,
         amount: U128,
@@ -107,6 +132,27 @@ pub enum Event {
         new_stake_shares: U128,
         unstaked_available_epoch_height: u64,
     },
+    InstantUnstake {
+        account_id: AccountId,
+        amount: U128,
+        fee: U128,
+        fee_shares: U128,
+        burnt_stake_shares: U128,
+        new_stake_shares: U128,
+    },
+    InstantUnstakeReserveFunded {
+        amount_added: U128,
+        new_reserve: U128,
+    },
+    InstantUnstakeReserveRefilled {
+        amount: U128,
+        new_reserve: U128,
+    },
+    InstantUnstakeReserveWithdrawn {
+        account_id: AccountId,
+        amount: U128,
+        new_reserve: U128,
+    },
     // Validators
     ValidatorAdded {
         account_id: AccountId,
@@ -123,6 +169,12 @@ pub enum Event {
         account_id: AccountId,
         old_weight: u16,
     },
+    ValidatorAutoPaused {
+        account_id: AccountId,
+        old_weight: u16,
+        failed_epoch_actions: u32,
+        zero_reward_epochs: u32,
+    },
     MakeValidatorPrivate {
         validator_id: AccountId,
     },
@@ -212,6 +264,181 @@ pub enum Event {
         old_min_deposit: U128,
         new_min_deposit: U128,
     },
+    WithdrawalTicketMint {
+        ticket_id: String,
+        owner_id: AccountId,
+        amount: U128,
+        burnt_stake_shares: U128,
+        new_stake_shares: U128,
+        withdrawable_epoch_height: u64,
+    },
+    WithdrawalTicketTransfer {
+        ticket_id: String,
+        old_owner_id: AccountId,
+        new_owner_id: AccountId,
+    },
+    WithdrawalTicketClaim {
+        ticket_id: String,
+        owner_id: AccountId,
+        amount: U128,
+    },
+    SetInstantUnstakeFee {
+        min_fee: Fraction,
+        max_fee: Fraction,
+    },
+    SetPerformanceMultiplierBounds {
+        min: Fraction,
+        max: Fraction,
+    },
+    ProposalCreated {
+        proposal_id: U64,
+        action: ProposalAction,
+        proposer: AccountId,
+        executable_epoch: U64,
+    },
+    ProposalCancelled {
+        proposal_id: U64,
+    },
+    ProposalExecuted {
+        proposal_id: U64,
+        action: ProposalAction,
+    },
+    SetCouncil {
+        members: Vec<AccountId>,
+        threshold: u32,
+        approval_ttl_epochs: U64,
+    },
+    CouncilActionApproved {
+        action: CouncilAction,
+        approver: AccountId,
+        expires_epoch: U64,
+    },
+    CouncilApprovalRevoked {
+        action: CouncilAction,
+        approver: AccountId,
+    },
+    CouncilActionExecuted {
+        action: CouncilAction,
+        approvers: Vec<AccountId>,
+    },
+    EmergencyPause {
+        caller: AccountId,
+    },
+    RoleGranted {
+        role: Role,
+        account_id: AccountId,
+    },
+    RoleRevoked {
+        role: Role,
+        account_id: AccountId,
+    },
+    SetExchangeRateBounds {
+        old_bounds: Option<ExchangeRateBounds>,
+        new_bounds: Option<ExchangeRateBounds>,
+    },
+    ValidatorBalanceQuarantined {
+        validator_id: AccountId,
+        staked: U128,
+        reported: U128,
+        max_change: U128,
+    },
+    QuarantinedBalanceConfirmed {
+        validator_id: AccountId,
+        staked: U128,
+        reported: U128,
+    },
+    QuarantinedBalanceRejected {
+        validator_id: AccountId,
+        staked: U128,
+        reported: U128,
+    },
+    ValidatorLoss {
+        validator_id: AccountId,
+        old_balance: U128,
+        new_balance: U128,
+        loss: U128,
+        covered_by_rewards_buffer: U128,
+        covered_by_insurance_fund: U128,
+        insurance_fund_shares_burnt: U128,
+        socialized: U128,
+        old_price: U128,
+        new_price: U128,
+    },
+    InsuranceFundFunded {
+        account_id: AccountId,
+        amount: U128,
+        minted_stake_shares: U128,
+        new_fund_shares: U128,
+    },
+    InsuranceFundLossCovered {
+        amount: U128,
+        burnt_shares: U128,
+        old_price: U128,
+        new_price: U128,
+    },
+    SetInsuranceFeeShare {
+        old_fee_share: Fraction,
+        new_fee_share: Fraction,
+    },
+    SetInsuranceFundCoverage {
+        old_coverage: Fraction,
+        new_coverage: Fraction,
+    },
+    PriceSubscriberAdded {
+        account_id: AccountId,
+    },
+    PriceSubscriberRemoved {
+        account_id: AccountId,
+    },
+    NearxPriceUpdatePushed {
+        price: U128,
+        epoch: U64,
+        nonce: U64,
+        subscribers: Vec<AccountId>,
+        skipped_subscribers: Vec<AccountId>,
+    },
+    SetProposalDelay {
+        old_delay: U64,
+        new_delay: U64,
+    },
+    SetUnstakeEpochCap {
+        old_cap: Option<UnstakeEpochCap>,
+        new_cap: Option<UnstakeEpochCap>,
+    },
+    SetValidatorDelinquencyThreshold {
+        old_threshold: u32,
+        new_threshold: u32,
+    },
+    UpdateFtMetadata {
+        name: String,
+        symbol: String,
+        reference: Option<String>,
+        reference_hash: Option<Base64VecU8>,
+    },
+    SetUnstakeStrategy {
+        old_strategy: UnstakeStrategy,
+        new_strategy: UnstakeStrategy,
+    },
+    SetKeeperIncentives {
+        reward_per_action: U128,
+        max_rewards_per_epoch: U128,
+        rewards_fee_share: Fraction,
+    },
+    KeeperBudgetFunded {
+        amount: U128,
+        minted_stake_shares: U128,
+        new_budget_shares: U128,
+    },
+    KeeperRewarded {
+        keeper_id: AccountId,
+        num_actions: u32,
+        amount: U128,
+        shares: U128,
+    },
+    SetMaxInstantUnstakeReserve {
+        old_max_reserve: U128,
+        new_max_reserve: U128,
+    },
     UpdateRewardBuffer {
         amount_added: U128,
         new_reward_buffer: U128,
@@ -1,3 +1,6 @@
 mod metadata;
 mod nearx_internal;
+mod nearx_receiver;
 mod nearx_token;
+
+pub(crate) use metadata::default_ft_metadata;
@@ -1,22 +1,76 @@
+use crate::constants::MAX_FT_ICON_LENGTH;
 use crate::contract::*;
+use crate::errors::*;
+use crate::events::Event;
 use near_contract_standards::fungible_token::metadata::{
     FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
 };
-use near_sdk::near_bindgen;
+use near_sdk::{assert_one_yocto, near_bindgen, require};
 
 const NEARX_LOGO: &str = "data:image/svg+xml,%3Csvg width='24' height='24' viewBox='0 0 24 24' fill='none' xmlns='http://www.w3.org/2000/svg'%3E%3Cpath fill-rule='evenodd' clip-rule='evenodd' d='M24 12C24 18.6274 18.6274 24 12 24C5.37258 24 0 18.6274 0 12C0 5.37258 5.37258 0 12 0C18.6274 0 24 5.37258 24 12ZM17.1421 21.3847L21.3848 17.1421L19.3669 15.1243C18.5576 17.0302 17.0303 18.5576 15.1243 19.3669L17.1421 21.3847ZM9.4329 19.5792C7.45318 18.9089 5.82112 17.4847 4.87847 15.6484L3.24263 17.2842L7.48527 21.5269L9.4329 19.5792ZM4.63307 8.87568C5.4424 6.96973 6.96977 5.44237 8.87573 4.63305L7.24263 2.99995L2.99999 7.24259L4.63307 8.87568ZM15.6484 4.87847L17.3848 3.14209L21.6274 7.38473L19.5792 9.4329C18.9089 7.45318 17.4847 5.82112 15.6484 4.87847Z' fill='%232FA8AF'/%3E%3Cpath d='M8.00119 11.9921C8.00119 11.0268 8.00119 10.0615 8.00119 9.09612C8.00119 8.50505 8.16497 8.1743 8.56285 8.05781C8.97778 7.93628 9.40619 7.97917 9.79565 8.45228C11.3533 10.3447 12.9343 12.218 14.5092 14.0959C14.5692 14.1676 14.6117 14.3122 14.734 14.2626C14.8496 14.2157 14.7858 14.0805 14.786 13.9878C14.7904 12.6326 14.7898 11.2774 14.7868 9.92226C14.7868 9.83668 14.838 9.7162 14.7271 9.67372C14.6401 9.64029 14.5824 9.74185 14.522 9.79358C14.0492 10.1981 13.5804 10.6073 13.1088 11.0133C13.0208 11.089 12.9332 11.2118 12.8002 11.1012C12.6657 10.9894 12.7696 10.8872 12.8364 10.7881C13.3547 10.0186 13.8728 9.24898 14.3909 8.47919C14.6656 8.07085 15.0079 7.92282 15.4056 8.04141C15.786 8.15475 15.9986 8.48592 15.9992 8.97668C16.0007 10.0905 15.9996 11.2045 15.9996 12.3183C15.9996 13.2094 16.0003 14.1005 15.9994 14.9916C15.999 15.4826 15.8137 15.798 15.4512 15.9324C15.0256 16.0901 14.5767 15.9683 14.2734 15.6058C12.6823 13.705 11.0924 11.8029 9.50198 9.90144C9.46029 9.85161 9.42303 9.7961 9.37377 9.75489C9.28998 9.68487 9.21967 9.68108 9.21314 9.81797C9.20999 9.88273 9.21188 9.94791 9.21188 10.0129C9.21188 11.3496 9.21062 12.6862 9.2142 14.0227C9.21441 14.1186 9.15609 14.2557 9.26114 14.3038C9.38093 14.3587 9.45061 14.2178 9.52998 14.1503C9.98259 13.7651 10.4291 13.3728 10.8805 12.9859C10.9729 12.9066 11.0626 12.7651 11.208 12.8973C11.3402 13.0176 11.2213 13.1196 11.159 13.2127C10.6323 13.9989 10.1032 14.7837 9.57145 15.5663C9.32788 15.9248 8.9881 16.056 8.61085 15.9488C8.23739 15.8428 8.00498 15.5246 8.00287 15.0827C7.99761 14.0523 8.00119 13.022 8.00119 11.9917V11.9921Z' fill='white'/%3E%3C/svg%3E%0A";
 
+/// Metadata the contract is deployed with, kept in state from then on
+pub(crate) fn default_ft_metadata() -> FungibleTokenMetadata {
+    FungibleTokenMetadata {
+        spec: FT_METADATA_SPEC.to_string(),
+        name: "NearX".to_string(),
+        symbol: "NearX".to_string(),
+        icon: Some(NEARX_LOGO.to_string()),
+        reference: Some("https://near.staderlabs.com".into()),
+        reference_hash: None,
+        decimals: 24,
+    }
+}
+
 #[near_bindgen]
 impl FungibleTokenMetadataProvider for NearxPool {
     fn ft_metadata(&self) -> FungibleTokenMetadata {
-        FungibleTokenMetadata {
-            spec: FT_METADATA_SPEC.to_string(),
-            name: "NearX".to_string(),
-            symbol: "NearX".to_string(),
-            icon: Some(NEARX_LOGO.to_string()),
-            reference: Some("https://near.staderlabs.com".into()),
-            reference_hash: None,
-            decimals: 24,
+        self.ft_metadata.get().unwrap()
+    }
+}
+
+#[near_bindgen]
+impl NearxPool {
+    /// Replaces the NearX metadata, the decimals cannot change
+    #[payable]
+    pub fn update_ft_metadata(&mut self, metadata: FungibleTokenMetadata) {
+        self.assert_owner_calling();
+        assert_one_yocto();
+        self.assert_not_timelocked();
+
+        self.internal_update_ft_metadata(metadata);
+    }
+
+    pub(crate) fn internal_update_ft_metadata(&mut self, metadata: FungibleTokenMetadata) {
+        require!(
+            metadata.spec == FT_METADATA_SPEC,
+            ERROR_INVALID_FT_METADATA_SPEC
+        );
+        require!(
+            metadata.decimals == self.ft_metadata.get().unwrap().decimals,
+            ERROR_FT_METADATA_DECIMALS_CHANGED
+        );
+        if let Some(icon) = &metadata.icon {
+            require!(
+                icon.starts_with("data:") && icon.len() <= MAX_FT_ICON_LENGTH,
+                ERROR_INVALID_FT_METADATA_ICON
+            );
+        }
+        if let Some(reference_hash) = &metadata.reference_hash {
+            require!(
+                reference_hash.0.len() == 32,
+                ERROR_INVALID_FT_METADATA_REFERENCE_HASH
+            );
+        }
+
+        self.ft_metadata.set(&metadata);
+
+        Event::UpdateFtMetadata {
+            name: metadata.name,
+            symbol: metadata.symbol,
+            reference: metadata.reference,
+            reference_hash: metadata.reference_hash,
         }
+        .emit();
     }
 }
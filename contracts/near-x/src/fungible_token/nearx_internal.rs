@@ -1,5 +1,7 @@
 use crate::contract::NearxPool;
 use crate::errors::{ERROR_REQUIRE_AMOUNT_GT_0, ERROR_SENDER_RECEIVER_SAME};
+use crate::state::Account;
+use near_contract_standards::fungible_token::events as nep141;
 use near_sdk::json_types::U128;
 use near_sdk::{env, log, require, AccountId, Balance, PromiseResult};
 
@@ -9,6 +11,7 @@ impl NearxPool {
         sender_id: &AccountId,
         receiver_id: &AccountId,
         amount: u128,
+        memo: Option<&str>,
     ) {
         require!(sender_id != receiver_id, ERROR_SENDER_RECEIVER_SAME);
         require!(amount > 0, ERROR_REQUIRE_AMOUNT_GT_0);
@@ -24,9 +27,27 @@ impl NearxPool {
         sender_acc.stake_shares -= amount;
         self.internal_update_account(sender_id, &sender_acc);
 
-        let mut receiver_acc = self.internal_get_account_unwrap(receiver_id);
+        let mut receiver_acc = self.internal_get_nearx_holder(receiver_id);
         receiver_acc.stake_shares += amount;
         self.internal_update_account(receiver_id, &receiver_acc);
+
+        nep141::FtTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            amount: &U128(amount),
+            memo,
+        }
+        .emit();
+    }
+
+    /// The pool holds the NearX sent to it with ft_transfer_call without being registered,
+    /// until its ft_on_transfer handles it
+    fn internal_get_nearx_holder(&self, account_id: &AccountId) -> Account {
+        if account_id == &env::current_account_id() {
+            self.internal_get_account(account_id)
+        } else {
+            self.internal_get_account_unwrap(account_id)
+        }
     }
 
     pub fn int_ft_resolve_transfer(
@@ -52,7 +73,7 @@ impl NearxPool {
         };
 
         if unused_amount > 0 {
-            let mut receiver_acc = self.internal_get_account_unwrap(&receiver_id);
+            let mut receiver_acc = self.internal_get_nearx_holder(&receiver_id);
             let receiver_balance = receiver_acc.stake_shares;
             if receiver_balance > 0 {
                 let refund_amount = std::cmp::min(receiver_balance, unused_amount);
@@ -63,6 +84,14 @@ impl NearxPool {
                 sender_acc.stake_shares += refund_amount;
                 self.internal_update_account(sender_id, &sender_acc);
 
+                nep141::FtTransfer {
+                    old_owner_id: &receiver_id,
+                    new_owner_id: sender_id,
+                    amount: &U128(refund_amount),
+                    memo: Some("refund"),
+                }
+                .emit();
+
                 log!(
                     "Refund {} from {} to {}",
                     refund_amount,
@@ -0,0 +1,71 @@
+use crate::contract::*;
+use crate::errors::{ERROR_NOT_ENOUGH_STAKED_AMOUNT_TO_UNSTAKE, ERROR_ONLY_NEARX_ACCEPTED};
+use near_contract_standards::fungible_token::events as nep141;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_sdk::json_types::U128;
+use near_sdk::serde::Deserialize;
+use near_sdk::{env, log, near_bindgen, require, serde_json, AccountId, PromiseOrValue};
+
+/// Actions run by sending NearX to the pool with ft_transfer_call
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum NearxReceiverMsg {
+    Unstake,
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for NearxPool {
+    /// Handles NearX sent to the pool itself. `{"action":"unstake"}` unstakes the NearX for the
+    /// sender as `unstake` would, any other message gets the NearX refunded.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        require!(
+            env::predecessor_account_id() == env::current_account_id(),
+            ERROR_ONLY_NEARX_ACCEPTED
+        );
+
+        match serde_json::from_str::<NearxReceiverMsg>(&msg) {
+            Ok(NearxReceiverMsg::Unstake) => {
+                // hand the NearX back to the sender and unstake it from there
+                let pool_id = env::current_account_id();
+                let mut pool_account = self.internal_get_account(&pool_id);
+                require!(
+                    pool_account.stake_shares >= amount.0,
+                    ERROR_NOT_ENOUGH_STAKED_AMOUNT_TO_UNSTAKE
+                );
+                pool_account.stake_shares -= amount.0;
+                if pool_account.is_empty() {
+                    self.internal_remove_account(&pool_id);
+                } else {
+                    self.internal_update_account(&pool_id, &pool_account);
+                }
+
+                let mut sender_account = self.internal_get_account_unwrap(&sender_id);
+                sender_account.stake_shares += amount.0;
+                self.internal_update_account(&sender_id, &sender_account);
+
+                nep141::FtTransfer {
+                    old_owner_id: &pool_id,
+                    new_owner_id: &sender_id,
+                    amount: &amount,
+                    memo: Some("unstake"),
+                }
+                .emit();
+
+                let unstake_amount = self.staked_amount_from_num_shares_rounded_down(amount.0);
+                self.internal_unstake(sender_id, unstake_amount);
+
+                PromiseOrValue::Value(U128(0))
+            }
+            Err(_) => {
+                log!("Unknown message {}, refunding the NearX", msg);
+                PromiseOrValue::Value(amount)
+            }
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use crate::constants::{gas, NO_DEPOSIT};
 use crate::contract::*;
+use crate::errors::ERROR_TRANSFER_TO_POOL;
 use crate::events::Event;
 use near_contract_standards::fungible_token::core::FungibleTokenCore;
 use near_sdk::{
-    assert_one_yocto, env, ext_contract, json_types::U128, log, near_bindgen, AccountId,
-    PromiseOrValue,
+    assert_one_yocto, env, ext_contract, json_types::U128, log, near_bindgen, require, AccountId,
+    Promise, PromiseOrValue,
 };
 
 #[ext_contract(ext_ft_receiver)]
@@ -31,21 +32,25 @@ trait FungibleTokenResolver {
 impl FungibleTokenCore for NearxPool {
     /// NEP-141 for NEARX
     #[payable]
-    fn ft_transfer(
-        &mut self,
-        receiver_id: AccountId,
-        amount: U128,
-        #[allow(unused)] memo: Option<String>,
-    ) {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
         assert_one_yocto();
         self.assert_ft_transfer_not_paused();
+        require!(
+            receiver_id != env::current_account_id(),
+            ERROR_TRANSFER_TO_POOL
+        );
         Event::FtTransfer {
             receiver_id: receiver_id.clone(),
             sender_id: env::predecessor_account_id(),
             amount,
         }
         .emit();
-        self.internal_nearx_transfer(&env::predecessor_account_id(), &receiver_id, amount.0);
+        self.internal_nearx_transfer(
+            &env::predecessor_account_id(),
+            &receiver_id,
+            amount.0,
+            memo.as_deref(),
+        );
     }
 
     #[payable]
@@ -53,17 +58,11 @@ impl FungibleTokenCore for NearxPool {
         &mut self,
         receiver_id: AccountId,
         amount: U128,
-        #[allow(unused)] memo: Option<String>,
+        memo: Option<String>,
         msg: String,
     ) -> PromiseOrValue<U128> {
         assert_one_yocto();
         self.assert_ft_transfer_call_not_paused();
-        let min_gas = gas::FT_TRANSFER + gas::FT_TRANSFER_RESOLVE;
-        assert!(
-            env::prepaid_gas() > min_gas,
-            "require at least {:?} gas",
-            min_gas
-        );
 
         Event::FtTransferCall {
             receiver_id: receiver_id.clone(),
@@ -73,18 +72,14 @@ impl FungibleTokenCore for NearxPool {
         }
         .emit();
 
-        self.internal_nearx_transfer(&env::predecessor_account_id(), &receiver_id, amount.0);
+        self.internal_nearx_transfer(
+            &env::predecessor_account_id(),
+            &receiver_id,
+            amount.0,
+            memo.as_deref(),
+        );
 
-        ext_ft_receiver::ext(receiver_id.clone())
-            .with_attached_deposit(NO_DEPOSIT)
-            .with_static_gas(env::prepaid_gas() - gas::FT_TRANSFER - gas::FT_TRANSFER_RESOLVE)
-            .ft_on_transfer(env::predecessor_account_id(), amount, msg)
-            .then(
-                ext_self::ext(env::current_account_id())
-                    .with_attached_deposit(NO_DEPOSIT)
-                    .with_static_gas(gas::FT_TRANSFER_RESOLVE)
-                    .ft_resolve_transfer(env::predecessor_account_id(), receiver_id, amount),
-            )
+        self.internal_ft_transfer_call(env::predecessor_account_id(), receiver_id, amount, msg)
             .into()
     }
 
@@ -93,7 +88,42 @@ impl FungibleTokenCore for NearxPool {
     }
 
     fn ft_balance_of(&self, account_id: AccountId) -> U128 {
-        self.internal_get_account(&account_id).stake_shares.into()
+        let mut balance = self.internal_get_account(&account_id).stake_shares;
+        // the keeper budget and the insurance fund are held by the pool itself
+        if account_id == env::current_account_id() {
+            balance += self.keeper_budget_shares + self.insurance_fund_shares;
+        }
+        balance.into()
+    }
+}
+
+impl NearxPool {
+    /// Hands the NearX already credited to the receiver over to its `ft_on_transfer`, the unused
+    /// amount is refunded to the sender in `ft_resolve_transfer`
+    pub(crate) fn internal_ft_transfer_call(
+        &self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> Promise {
+        let min_gas = gas::FT_TRANSFER + gas::FT_TRANSFER_RESOLVE;
+        assert!(
+            env::prepaid_gas() > min_gas,
+            "require at least {:?} gas",
+            min_gas
+        );
+
+        ext_ft_receiver::ext(receiver_id.clone())
+            .with_attached_deposit(NO_DEPOSIT)
+            .with_static_gas(env::prepaid_gas() - gas::FT_TRANSFER - gas::FT_TRANSFER_RESOLVE)
+            .ft_on_transfer(sender_id.clone(), amount, msg)
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(gas::FT_TRANSFER_RESOLVE)
+                    .ft_resolve_transfer(sender_id, receiver_id, amount),
+            )
     }
 }
 
@@ -7,3 +7,4 @@ pub mod events;
 pub mod fungible_token;
 pub mod state;
 pub mod utils;
+pub mod withdrawal_ticket;
@@ -1,11 +1,19 @@
-use crate::constants::NUM_EPOCHS_TO_UNLOCK;
+use crate::constants::{
+    MAX_UNSTAKE_REQUESTS, NUM_EPOCHS_TO_UNLOCK, PERFORMANCE_SCORE_PRECISION,
+    PERFORMANCE_SCORE_SMOOTHING_EPOCHS,
+};
+use crate::contract::OperationControls;
+use crate::errors::ERROR_TOO_MANY_UNSTAKE_REQUESTS;
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadata;
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     env,
-    json_types::{U128, U64},
+    json_types::{Base58CryptoHash, U128, U64},
+    require,
     serde::{Deserialize, Serialize},
     AccountId, Balance, EpochHeight,
 };
+use std::fmt;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(crate = "near_sdk::serde")]
@@ -13,9 +21,28 @@ pub struct AccountResponse {
     pub account_id: AccountId,
     pub unstaked_balance: U128,
     pub staked_balance: U128,
+    /// Epoch at which the whole unstaked balance can be withdrawn
+    pub withdrawable_epoch: U64,
+    pub unstake_requests: Vec<UnstakeRequestResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UnstakeRequestResponse {
+    pub amount: U128,
     pub withdrawable_epoch: U64,
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WithdrawalTicketResponse {
+    pub ticket_id: String,
+    pub owner_id: AccountId,
+    pub amount: U128,
+    pub withdrawable_epoch: U64,
+    pub can_claim: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(crate = "near_sdk::serde")]
 #[serde(rename_all = "camelCase")]
@@ -31,6 +58,20 @@ pub enum ValidatorType {
     PRIVATE,
 }
 
+/// How unstaking_epoch picks the validator to unstake from and how much to unstake
+#[derive(
+    BorshDeserialize, BorshSerialize, Debug, Serialize, Deserialize, PartialEq, Clone, Copy,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum UnstakeStrategy {
+    /// unstake from the validator with the largest unstakable amount
+    LargestFirst,
+    /// unstake from the validator that is the furthest above its expected stake
+    WeightDeviationFirst,
+    /// unstake from every validator in proportion to its unstakable amount
+    ProRata,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct NearxPoolStateResponse {
@@ -83,6 +124,7 @@ pub struct NearxPoolStateResponse {
 #[serde(rename_all = "camelCase")]
 pub enum ValidatorInfoWrapper {
     LegacyValidatorInfo(LegacyValidatorInfoV1),
+    LegacyValidatorInfoV2(LegacyValidatorInfoV2),
     ValidatorInfo(ValidatorInfo),
 }
 
@@ -90,6 +132,7 @@ impl ValidatorInfoWrapper {
     pub fn into_current(self) -> ValidatorInfo {
         match self {
             ValidatorInfoWrapper::LegacyValidatorInfo(account) => account.into_current(),
+            ValidatorInfoWrapper::LegacyValidatorInfoV2(account) => account.into_current(),
             ValidatorInfoWrapper::ValidatorInfo(account) => account,
         }
     }
@@ -131,6 +174,19 @@ pub struct ValidatorInfoResponse {
     pub validator_type: ValidatorType,
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidatorPerformanceResponse {
+    pub account_id: AccountId,
+    pub weight: u16,
+    pub performance_score: Option<U128>,
+    /// weight adjusted by the performance score, scaled by PERFORMANCE_MULTIPLIER_PRECISION
+    pub effective_weight: U128,
+    pub expected_stake: U128,
+    pub failed_epoch_actions: u32,
+    pub zero_reward_epochs: u32,
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct LegacyValidatorInfoV1 {
@@ -161,6 +217,50 @@ impl LegacyValidatorInfoV1 {
             last_unstake_start_epoch: self.last_unstake_start_epoch,
             max_unstakable_limit: self.staked,
             validator_type: ValidatorType::PUBLIC,
+            performance_score: None,
+            failed_epoch_actions: 0,
+            zero_reward_epochs: 0,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LegacyValidatorInfoV2 {
+    pub account_id: AccountId,
+
+    pub staked: u128,
+
+    pub weight: u16,
+
+    pub last_redeemed_rewards_epoch: EpochHeight,
+
+    pub unstaked_amount: Balance,
+
+    pub unstake_start_epoch: EpochHeight,
+
+    pub last_unstake_start_epoch: EpochHeight,
+
+    pub max_unstakable_limit: u128,
+
+    pub validator_type: ValidatorType,
+}
+
+impl LegacyValidatorInfoV2 {
+    pub fn into_current(self) -> ValidatorInfo {
+        ValidatorInfo {
+            account_id: self.account_id,
+            staked: self.staked,
+            weight: self.weight,
+            last_redeemed_rewards_epoch: self.last_redeemed_rewards_epoch,
+            unstaked_amount: self.unstaked_amount,
+            unstake_start_epoch: self.unstake_start_epoch,
+            last_unstake_start_epoch: self.last_unstake_start_epoch,
+            max_unstakable_limit: self.max_unstakable_limit,
+            validator_type: self.validator_type,
+            performance_score: None,
+            failed_epoch_actions: 0,
+            zero_reward_epochs: 0,
         }
     }
 }
@@ -189,6 +289,16 @@ pub struct ValidatorInfo {
     pub max_unstakable_limit: u128,
 
     pub validator_type: ValidatorType,
+
+    // rewards per staked NEAR per epoch (scaled by PERFORMANCE_SCORE_PRECISION), smoothed
+    // over PERFORMANCE_SCORE_SMOOTHING_EPOCHS. None until the first rewards are observed
+    pub performance_score: Option<u128>,
+
+    // consecutive staking/unstaking epoch calls to the validator that failed
+    pub failed_epoch_actions: u32,
+
+    // consecutive autocompounding epochs in which the validator reported no rewards
+    pub zero_reward_epochs: u32,
 }
 
 impl ValidatorInfo {
@@ -210,6 +320,9 @@ impl ValidatorInfo {
             last_unstake_start_epoch: 0,
             max_unstakable_limit: 0,
             validator_type: ValidatorType::PUBLIC,
+            performance_score: None,
+            failed_epoch_actions: 0,
+            zero_reward_epochs: 0,
         }
     }
 
@@ -217,10 +330,37 @@ impl ValidatorInfo {
         self.staked + self.unstaked_amount
     }
 
+    /// Folds the rewards earned over `num_epochs` into the smoothed performance score
+    pub fn update_performance_score(&mut self, rewards: u128, num_epochs: u64) {
+        if self.staked == 0 || num_epochs == 0 {
+            return;
+        }
+
+        let epoch_yield = crate::utils::proportional(
+            rewards,
+            PERFORMANCE_SCORE_PRECISION,
+            self.staked * num_epochs as u128,
+        );
+
+        self.performance_score = Some(match self.performance_score {
+            Some(score) => {
+                (score * (PERFORMANCE_SCORE_SMOOTHING_EPOCHS - 1) + epoch_yield)
+                    / PERFORMANCE_SCORE_SMOOTHING_EPOCHS
+            }
+            None => epoch_yield,
+        });
+    }
+
     pub fn paused(&self) -> bool {
         self.weight == 0
     }
 
+    /// whether either delinquency counter has reached `threshold`. A threshold of 0 disables the check
+    pub fn is_delinquent(&self, threshold: u32) -> bool {
+        threshold > 0
+            && (self.failed_epoch_actions >= threshold || self.zero_reward_epochs >= threshold)
+    }
+
     /// whether the validator is in unstake releasing period.
     pub fn pending_unstake_release(&self) -> bool {
         env::epoch_height() >= self.unstake_start_epoch
@@ -228,19 +368,197 @@ impl ValidatorInfo {
     }
 }
 
+/// Progress of run_epoch through the validators in an epoch
+#[derive(Default, BorshDeserialize, BorshSerialize, Debug, PartialEq, Clone)]
+pub struct RunEpochCursor {
+    pub epoch_height: EpochHeight,
+
+    // validators taken at the first call of the epoch, so adding or removing a validator does not
+    // shift the steps. Validators added later in the epoch are picked up in the next one
+    pub validator_ids: Vec<AccountId>,
+
+    // validator steps run so far: first the autocompounding of every validator, then the withdraw
+    pub next_step: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum EpochAction {
+    Autocompounding,
+    Withdraw,
+    Stake,
+    Unstake,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EpochActionResponse {
+    pub action: EpochAction,
+    pub validator_id: AccountId,
+    pub amount: Option<U128>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RunEpochSummary {
+    pub epoch_height: U64,
+    pub actions: Vec<EpochActionResponse>,
+    pub validator_steps_left: U64,
+    /// true once there is nothing left to schedule in this epoch
+    pub done: bool,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UnstakeRequest {
+    pub amount: Balance,
+
+    pub withdrawable_epoch_height: EpochHeight,
+}
+
 #[derive(Default, BorshDeserialize, BorshSerialize, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Account {
     pub stake_shares: u128, //nearx this account owns
 
+    // every unstake keeps its own release epoch, so a new unstake never locks up matured funds
+    pub unstake_requests: Vec<UnstakeRequest>,
+}
+
+/// Unstaked NEAR held by a transferable NEP-171 token, claimable by whoever owns it
+/// once `withdrawable_epoch_height` is reached
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct WithdrawalTicket {
+    pub owner_id: AccountId,
+
+    pub amount: Balance,
+
+    pub withdrawable_epoch_height: EpochHeight,
+
+    // storage deposit paid when the ticket was minted, refunded on claim
+    pub storage_deposit: Balance,
+}
+
+#[derive(Default, BorshDeserialize, BorshSerialize, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LegacyAccountV1 {
+    pub stake_shares: u128,
+
     pub unstaked_amount: Balance,
 
     pub withdrawable_epoch_height: EpochHeight,
 }
 
+impl LegacyAccountV1 {
+    pub fn into_current(self) -> Account {
+        let mut unstake_requests = vec![];
+        if self.unstaked_amount > 0 {
+            unstake_requests.push(UnstakeRequest {
+                amount: self.unstaked_amount,
+                withdrawable_epoch_height: self.withdrawable_epoch_height,
+            });
+        }
+
+        Account {
+            stake_shares: self.stake_shares,
+            unstake_requests,
+        }
+    }
+}
+
 impl Account {
     pub fn is_empty(&self) -> bool {
-        self.stake_shares == 0 && self.unstaked_amount == 0
+        self.stake_shares == 0 && self.unstake_requests.is_empty()
+    }
+
+    /// Total unstaked amount, matured or not
+    pub fn unstaked_amount(&self) -> Balance {
+        self.unstake_requests
+            .iter()
+            .map(|request| request.amount)
+            .sum()
+    }
+
+    /// Unstaked amount which can be withdrawn at the given epoch
+    pub fn withdrawable_amount(&self, epoch_height: EpochHeight) -> Balance {
+        self.unstake_requests
+            .iter()
+            .filter(|request| request.withdrawable_epoch_height <= epoch_height)
+            .map(|request| request.amount)
+            .sum()
+    }
+
+    /// Epoch at which the whole unstaked amount can be withdrawn
+    pub fn withdrawable_epoch_height(&self) -> EpochHeight {
+        self.unstake_requests
+            .iter()
+            .map(|request| request.withdrawable_epoch_height)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn add_unstake_request(
+        &mut self,
+        amount: Balance,
+        withdrawable_epoch_height: EpochHeight,
+        current_epoch_height: EpochHeight,
+    ) {
+        // matured requests are all equivalent, fold them into a single entry to keep the queue short
+        let (matured, mut pending): (Vec<UnstakeRequest>, Vec<UnstakeRequest>) = self
+            .unstake_requests
+            .drain(..)
+            .partition(|request| request.withdrawable_epoch_height <= current_epoch_height);
+        if let Some(last_matured) = matured.iter().max_by_key(|r| r.withdrawable_epoch_height) {
+            pending.insert(
+                0,
+                UnstakeRequest {
+                    amount: matured.iter().map(|request| request.amount).sum(),
+                    withdrawable_epoch_height: last_matured.withdrawable_epoch_height,
+                },
+            );
+        }
+        self.unstake_requests = pending;
+
+        if let Some(request) = self
+            .unstake_requests
+            .iter_mut()
+            .find(|request| request.withdrawable_epoch_height == withdrawable_epoch_height)
+        {
+            request.amount += amount;
+            return;
+        }
+
+        require!(
+            self.unstake_requests.len() < MAX_UNSTAKE_REQUESTS,
+            ERROR_TOO_MANY_UNSTAKE_REQUESTS
+        );
+        self.unstake_requests.push(UnstakeRequest {
+            amount,
+            withdrawable_epoch_height,
+        });
+    }
+
+    /// Takes the given amount out of the matured requests, oldest first
+    pub fn sub_withdrawable_amount(&mut self, amount: Balance, current_epoch_height: EpochHeight) {
+        assert!(
+            self.withdrawable_amount(current_epoch_height) >= amount,
+            "sub_withdrawable_amount withdrawable amount < amount {}",
+            amount
+        );
+
+        let mut remaining = amount;
+        for request in self.unstake_requests.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+            if request.withdrawable_epoch_height <= current_epoch_height {
+                let taken = std::cmp::min(request.amount, remaining);
+                request.amount -= taken;
+                remaining -= taken;
+            }
+        }
+
+        self.unstake_requests.retain(|request| request.amount > 0);
     }
 
     pub fn add_stake_shares(&mut self, num_shares: u128) {
@@ -258,6 +576,318 @@ impl Account {
     }
 }
 
+/// Exchange rate at the start of an epoch
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PriceSnapshot {
+    pub epoch: EpochHeight,
+    pub total_staked: Balance,
+    pub total_stake_shares: Balance,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceSnapshotResponse {
+    pub epoch: U64,
+    pub total_staked: U128,
+    pub total_stake_shares: U128,
+    /// NEAR per NearX scaled by 10^24
+    pub price: U128,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ApyResponse {
+    pub from_epoch: U64,
+    pub to_epoch: U64,
+    /// Yearly growth of the NearX price over the window, without compounding
+    pub apy_basis_points: U64,
+}
+
+/// Most NEAR the unstake requests of a single epoch take out of the validators, unstakes
+/// above it are queued into later epochs
+#[derive(BorshDeserialize, BorshSerialize, Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub enum UnstakeEpochCap {
+    Absolute { amount: U128 },
+    ShareOfTotalStaked { share: Fraction },
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UnstakeQueueEntryResponse {
+    /// Epoch whose reconciliation takes the last of the amount
+    pub epoch: U64,
+    pub amount: U128,
+}
+
+/// Largest change of the exchange rate a single validator balance report may cause per epoch,
+/// as a share of the total staked. Reports outside of the bounds are quarantined
+#[derive(BorshDeserialize, BorshSerialize, Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExchangeRateBounds {
+    pub max_increase_per_epoch: Fraction,
+    pub max_decrease_per_epoch: Fraction,
+}
+
+/// Validator balance report held back by the exchange rate guard until the operator confirms it
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct QuarantinedBalance {
+    // validator stake when the balance was reported
+    pub staked: Balance,
+
+    pub reported: Balance,
+
+    pub epoch: EpochHeight,
+
+    // validator unstaked amount when the balance was reported
+    pub unstaked: Balance,
+
+    // only balance syncs report the unstaked balance along with the staked one
+    pub reported_unstaked: Option<Balance>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct QuarantinedBalanceResponse {
+    pub validator_id: AccountId,
+    pub staked: U128,
+    pub reported: U128,
+    pub epoch: U64,
+    pub reported_unstaked: Option<U128>,
+}
+
+/// Owner action queued by `propose`, carrying the arguments of the owner method it runs
+#[derive(BorshDeserialize, BorshSerialize, Debug, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalAction {
+    SetOwner {
+        new_owner: AccountId,
+    },
+    SetOperator {
+        new_operator_account_id: AccountId,
+    },
+    SetTreasury {
+        new_treasury_account_id: AccountId,
+    },
+    UpdateOperationsControl {
+        update_operations_control_request: OperationsControlUpdateRequest,
+    },
+    SetRewardFee {
+        numerator: u32,
+        denominator: u32,
+    },
+    SetMinDeposit {
+        min_deposit: U128,
+    },
+    AddValidator {
+        validator: AccountId,
+        weight: u16,
+    },
+    SetInstantUnstakeFee {
+        min_fee: Fraction,
+        max_fee: Fraction,
+    },
+    SetMaxInstantUnstakeReserve {
+        max_reserve: U128,
+    },
+    WithdrawInstantUnstakeReserve {
+        amount: U128,
+    },
+    SetPerformanceMultiplierBounds {
+        min: Fraction,
+        max: Fraction,
+    },
+    SetUnstakeStrategy {
+        strategy: UnstakeStrategy,
+    },
+    SetKeeperIncentives {
+        reward_per_action: U128,
+        max_rewards_per_epoch: U128,
+        rewards_fee_share: Fraction,
+    },
+    SetValidatorDelinquencyThreshold {
+        threshold: u32,
+    },
+    SetProposalDelay {
+        epochs: EpochHeight,
+    },
+    SetCouncil {
+        members: Vec<AccountId>,
+        threshold: u32,
+        approval_ttl_epochs: EpochHeight,
+    },
+    SetUnstakeEpochCap {
+        cap: Option<UnstakeEpochCap>,
+    },
+    GrantRole {
+        role: Role,
+        account_id: AccountId,
+    },
+    RevokeRole {
+        role: Role,
+        account_id: AccountId,
+    },
+    AddPriceSubscriber {
+        account_id: AccountId,
+    },
+    RemovePriceSubscriber {
+        account_id: AccountId,
+    },
+    SetExchangeRateBounds {
+        bounds: Option<ExchangeRateBounds>,
+    },
+    SetInsuranceFundCoverage {
+        coverage: Fraction,
+    },
+    SetInsuranceFeeShare {
+        fee_share: Fraction,
+    },
+    CoverLossFromInsuranceFund {
+        amount: U128,
+    },
+    UpdateValidator {
+        validator: AccountId,
+        weight: u16,
+    },
+    RemoveValidator {
+        validator: AccountId,
+    },
+    UpdateFtMetadata {
+        metadata: ProposedFtMetadata,
+    },
+    /// Runs through `upgrade` with the code of this sha256 hash, once the delay has passed
+    Upgrade {
+        code_hash: Base58CryptoHash,
+    },
+}
+
+/// NearX metadata carried by an UpdateFtMetadata proposal
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposedFtMetadata(pub FungibleTokenMetadata);
+
+impl fmt::Debug for ProposedFtMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FungibleTokenMetadata")
+            .field("name", &self.0.name)
+            .field("symbol", &self.0.symbol)
+            .field("reference", &self.0.reference)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Proposal {
+    pub action: ProposalAction,
+
+    pub proposer: AccountId,
+
+    pub created_epoch: EpochHeight,
+
+    // the action can be executed from this epoch on
+    pub executable_epoch: EpochHeight,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalResponse {
+    pub proposal_id: U64,
+    pub action: ProposalAction,
+    pub proposer: AccountId,
+    pub created_epoch: U64,
+    pub executable_epoch: U64,
+    pub can_execute: bool,
+}
+
+/// Admin role the owner can grant to accounts on top of the owner and operator
+#[derive(
+    BorshDeserialize, BorshSerialize, Debug, Serialize, Deserialize, PartialEq, Clone, Copy,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    /// pauses operations, resuming them stays with the owner
+    Pauser,
+    /// adds, updates, pauses and drains validators, and confirms their quarantined balances
+    ValidatorManager,
+    RewardsBufferDepositor,
+    /// deploys new contract code through upgrade
+    Upgrader,
+    /// pauses every operation at once with emergency_pause
+    Guardian,
+}
+
+impl Role {
+    /// Roles the operator holds without being granted them
+    pub fn is_operator_role(&self) -> bool {
+        matches!(self, Role::ValidatorManager | Role::RewardsBufferDepositor)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoleResponse {
+    pub role: Role,
+    pub account_ids: Vec<AccountId>,
+}
+
+/// Critical action which needs approvals from the council before it runs, once the owner
+/// has set one up
+#[derive(BorshDeserialize, BorshSerialize, Debug, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum CouncilAction {
+    /// Deploy of the code with this sha256 hash through upgrade
+    Upgrade {
+        code_hash: Base58CryptoHash,
+    },
+    CommitRewardFee {
+        numerator: u32,
+        denominator: u32,
+    },
+    SetOwner {
+        new_owner: AccountId,
+    },
+    /// Operations control update which unpauses at least one operation
+    Unpause {
+        update_operations_control_request: OperationsControlUpdateRequest,
+    },
+    SetCouncil {
+        members: Vec<AccountId>,
+        threshold: u32,
+        approval_ttl_epochs: EpochHeight,
+    },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CouncilApproval {
+    pub approver: AccountId,
+
+    // the approval no longer counts after this epoch
+    pub expires_epoch: EpochHeight,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CouncilResponse {
+    pub members: Vec<AccountId>,
+    pub threshold: u32,
+    pub approval_ttl_epochs: U64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CouncilApprovalResponse {
+    pub approver: AccountId,
+    pub expires_epoch: U64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CouncilActionApprovalsResponse {
+    pub action: CouncilAction,
+    /// Approvals from current council members which have not expired
+    pub approvals: Vec<CouncilApprovalResponse>,
+}
+
 /// Rewards fee fraction structure for the staking pool contract.
 #[derive(Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy)]
 #[serde(crate = "near_sdk::serde")]
@@ -304,9 +934,11 @@ pub struct RolesResponse {
     pub temp_owner: Option<AccountId>,
     pub temp_operator: Option<AccountId>,
     pub temp_treasury: Option<AccountId>,
+    /// Accounts granted each role by the owner
+    pub granted_roles: Vec<RoleResponse>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(BorshDeserialize, BorshSerialize, Debug, Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct OperationsControlUpdateRequest {
     pub stake_paused: Option<bool>,
@@ -320,6 +952,134 @@ pub struct OperationsControlUpdateRequest {
     pub sync_validator_balance_paused: Option<bool>,
     pub ft_transfer_paused: Option<bool>,
     pub ft_transfer_call_paused: Option<bool>,
+    pub instant_unstake_paused: Option<bool>,
+    pub rebalance_epoch_paused: Option<bool>,
+}
+
+impl OperationsControlUpdateRequest {
+    /// Whether the request resumes any operation paused in `current`
+    pub fn has_unpause(&self, current: &OperationControls) -> bool {
+        self.unpaused_group_count(current) > 0
+    }
+
+    /// Number of operation groups the request resumes operations paused in `current` in. The
+    /// groups are deposits, withdrawals, epoch actions and NearX transfers.
+    pub fn unpaused_group_count(&self, current: &OperationControls) -> usize {
+        let groups = [
+            vec![
+                (self.stake_paused, current.stake_paused),
+                (self.direct_stake_paused, current.direct_stake_paused),
+            ],
+            vec![
+                (self.unstake_paused, current.unstaked_paused),
+                (self.withdraw_paused, current.withdraw_paused),
+                (self.instant_unstake_paused, current.instant_unstake_paused),
+            ],
+            vec![
+                (self.staking_epoch_paused, current.staking_epoch_paused),
+                (self.unstaking_epoch_paused, current.unstaking_epoch_paused),
+                (self.withdraw_epoch_paused, current.withdraw_epoch_paused),
+                (
+                    self.autocompounding_epoch_paused,
+                    current.autocompounding_epoch_paused,
+                ),
+                (
+                    self.sync_validator_balance_paused,
+                    current.sync_validator_balance_paused,
+                ),
+                (self.rebalance_epoch_paused, current.rebalance_epoch_paused),
+            ],
+            vec![
+                (self.ft_transfer_paused, current.ft_transfer_paused),
+                (
+                    self.ft_transfer_call_paused,
+                    current.ft_transfer_call_paused,
+                ),
+            ],
+        ];
+        groups
+            .iter()
+            .filter(|group| {
+                group
+                    .iter()
+                    .any(|(requested, paused)| *paused && *requested == Some(false))
+            })
+            .count()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InstantUnstakeReserveResponse {
+    /// NEAR available right now to pay out instant unstakes
+    pub reserve: U128,
+    /// NEAR owed back to the reserve once it is done unbonding
+    pub pending: U128,
+    /// Pending NEAR by the epoch it is due at, it is released back into the reserve as the
+    /// validators withdraw it
+    pub pending_releases: Vec<(U64, U128)>,
+    pub max_reserve: U128,
+    pub min_fee: Fraction,
+    pub max_fee: Fraction,
+    pub accumulated_fees: U128,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct KeeperIncentivesResponse {
+    pub reward_per_action: U128,
+    pub max_rewards_per_epoch: U128,
+    pub rewards_fee_share: Fraction,
+    /// NearX set aside to pay the keepers
+    pub budget_shares: U128,
+    /// NEAR worth of the keeper budget at the current exchange rate
+    pub budget: U128,
+    pub rewards_paid_in_epoch: U128,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InsuranceFundResponse {
+    /// NearX held to cover validator losses
+    pub shares: U128,
+    /// NEAR worth of the insurance fund at the current exchange rate
+    pub amount: U128,
+    pub coverage: Fraction,
+    pub fee_share: Fraction,
+    /// Validator losses taken by NearX holders that the insurance fund has not covered yet
+    pub uncovered_losses: U128,
+}
+
+/// Changes of the insurance fund within an epoch
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct InsuranceFundRecord {
+    pub epoch: EpochHeight,
+
+    // NearX minted into the fund out of the rewards fee
+    pub fee_shares: u128,
+
+    // NearX minted for the NEAR deposited into the fund
+    pub deposited_shares: u128,
+
+    // NearX burnt to cover validator losses
+    pub burnt_shares: u128,
+
+    // NEAR worth of the burnt shares
+    pub covered_amount: Balance,
+
+    // fund shares at the end of the epoch, or now for the current epoch
+    pub fund_shares: u128,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InsuranceFundRecordResponse {
+    pub epoch: U64,
+    pub fee_shares: U128,
+    pub deposited_shares: U128,
+    pub burnt_shares: U128,
+    pub covered_amount: U128,
+    pub fund_shares: U128,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1,5 +1,5 @@
 use crate::{constants::*, errors::*};
-use near_sdk::{env, require, PromiseResult};
+use near_sdk::{env, require, Gas, PromiseResult};
 
 pub fn is_promise_success() -> bool {
     require!(
@@ -10,6 +10,11 @@ pub fn is_promise_success() -> bool {
     matches!(env::promise_result(0), PromiseResult::Successful(_))
 }
 
+/// Whether the call still has `gas` left to spend
+pub(crate) fn has_gas_left(gas: Gas) -> bool {
+    env::prepaid_gas() >= env::used_gas() + gas
+}
+
 pub(crate) fn abs_diff_eq(left: u128, right: u128, epsilon: u128) -> bool {
     left <= right + epsilon && right <= left + epsilon
 }
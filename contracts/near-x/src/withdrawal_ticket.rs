@@ -0,0 +1,3 @@
+mod metadata;
+mod ticket_internal;
+mod ticket_token;
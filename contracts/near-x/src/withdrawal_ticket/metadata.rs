@@ -0,0 +1,20 @@
+use crate::contract::*;
+use near_contract_standards::non_fungible_token::metadata::{
+    NFTContractMetadata, NonFungibleTokenMetadataProvider, NFT_METADATA_SPEC,
+};
+use near_sdk::near_bindgen;
+
+#[near_bindgen]
+impl NonFungibleTokenMetadataProvider for NearxPool {
+    fn nft_metadata(&self) -> NFTContractMetadata {
+        NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "NearX withdrawal ticket".to_string(),
+            symbol: "NEARXWT".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: Some("https://near.staderlabs.com".into()),
+            reference_hash: None,
+        }
+    }
+}
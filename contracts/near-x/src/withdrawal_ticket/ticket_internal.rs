@@ -0,0 +1,234 @@
+use crate::constants::WITHDRAWAL_TICKET_STORAGE_DEPOSIT;
+use crate::contract::NearxPool;
+use crate::errors::*;
+use crate::events::Event;
+use crate::state::WithdrawalTicket;
+use near_contract_standards::fungible_token::events as nep141;
+use near_contract_standards::non_fungible_token::events as nep171;
+use near_contract_standards::storage_management::StorageManagement;
+use near_sdk::json_types::U128;
+use near_sdk::{env, log, require, AccountId, Balance, Promise, PromiseResult};
+
+impl NearxPool {
+    pub(crate) fn internal_unstake_to_ticket(&mut self, amount: Balance) -> String {
+        self.assert_unstaking_not_paused();
+
+        let storage_deposit = env::attached_deposit();
+        require!(
+            storage_deposit >= WITHDRAWAL_TICKET_STORAGE_DEPOSIT,
+            ERROR_NOT_ENOUGH_WITHDRAWAL_TICKET_STORAGE_DEPOSIT
+        );
+
+        require!(amount > 0, ERROR_NON_POSITIVE_UNSTAKE_AMOUNT);
+
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_get_account(&account_id);
+
+        require!(
+            self.total_staked > 0,
+            ERROR_NOT_ENOUGH_CONTRACT_STAKED_AMOUNT
+        );
+
+        let mut num_shares = self.num_shares_from_staked_amount_rounded_up(amount);
+        require!(num_shares > 0, ERROR_NON_POSITIVE_UNSTAKING_SHARES);
+        require!(
+            account.stake_shares >= num_shares,
+            ERROR_NOT_ENOUGH_STAKED_AMOUNT_TO_UNSTAKE
+        );
+
+        let mut receive_amount = self.staked_amount_from_num_shares_rounded_up(num_shares);
+        require!(
+            receive_amount > 0,
+            ERROR_NON_POSITIVE_UNSTAKE_RECEVIE_AMOUNT
+        );
+
+        account.stake_shares -= num_shares;
+
+        let remaining_amount =
+            self.staked_amount_from_num_shares_rounded_down(account.stake_shares);
+
+        // same as unstake, sweep the dust left on the account into the ticket
+        if remaining_amount <= self.storage_balance_bounds().min.0 {
+            receive_amount += remaining_amount;
+            num_shares += account.stake_shares;
+
+            account.stake_shares = 0;
+        }
+
+        // every ticket keeps its own release epoch, so minting a new one never delays older ones
+        let withdrawable_epoch_height = self.internal_schedule_unstake(receive_amount);
+
+        self.internal_update_account(&account_id, &account);
+
+        self.total_staked -= receive_amount;
+        self.total_stake_shares -= num_shares;
+
+        self.last_withdrawal_ticket_id += 1;
+        let ticket_id = self.last_withdrawal_ticket_id.to_string();
+        self.withdrawal_tickets.insert(
+            &ticket_id,
+            &WithdrawalTicket {
+                owner_id: account_id.clone(),
+                amount: receive_amount,
+                withdrawable_epoch_height,
+                storage_deposit: WITHDRAWAL_TICKET_STORAGE_DEPOSIT,
+            },
+        );
+
+        Event::WithdrawalTicketMint {
+            ticket_id: ticket_id.clone(),
+            owner_id: account_id.clone(),
+            amount: U128(receive_amount),
+            burnt_stake_shares: U128(num_shares),
+            new_stake_shares: U128(account.stake_shares),
+            withdrawable_epoch_height,
+        }
+        .emit();
+
+        nep171::NftMint {
+            owner_id: &account_id,
+            token_ids: &[ticket_id.as_str()],
+            memo: None,
+        }
+        .emit();
+
+        nep141::FtBurn {
+            owner_id: &account_id,
+            amount: &U128(num_shares),
+            memo: Some("unstake_to_ticket"),
+        }
+        .emit();
+
+        Event::FtBurn {
+            account_id: account_id.clone(),
+            amount: U128(num_shares),
+        }
+        .emit();
+
+        let refund = storage_deposit - WITHDRAWAL_TICKET_STORAGE_DEPOSIT;
+        if refund > 0 {
+            Promise::new(account_id).transfer(refund);
+        }
+
+        ticket_id
+    }
+
+    pub(crate) fn internal_claim(&mut self, ticket_id: String) {
+        self.assert_withdraw_not_paused();
+
+        let account_id = env::predecessor_account_id();
+        let ticket = self
+            .withdrawal_tickets
+            .get(&ticket_id)
+            .expect(ERROR_WITHDRAWAL_TICKET_NOT_FOUND);
+
+        require!(
+            ticket.owner_id == account_id,
+            ERROR_NOT_WITHDRAWAL_TICKET_OWNER
+        );
+        require!(
+            ticket.withdrawable_epoch_height <= env::epoch_height(),
+            ERROR_UNSTAKED_AMOUNT_IN_UNBONDING_PERIOD
+        );
+
+        let amount_to_send = ticket.amount + ticket.storage_deposit;
+        require!(
+            env::account_balance()
+                .saturating_sub(self.min_storage_reserve + self.instant_unstake_reserve)
+                >= amount_to_send,
+            ERROR_NOT_ENOUGH_BALANCE_FOR_STORAGE
+        );
+
+        self.withdrawal_tickets.remove(&ticket_id);
+
+        Event::WithdrawalTicketClaim {
+            ticket_id: ticket_id.clone(),
+            owner_id: account_id.clone(),
+            amount: U128(ticket.amount),
+        }
+        .emit();
+
+        nep171::NftBurn {
+            owner_id: &account_id,
+            token_ids: &[ticket_id.as_str()],
+            authorized_id: None,
+            memo: Some("claim"),
+        }
+        .emit();
+
+        Promise::new(account_id).transfer(amount_to_send);
+    }
+
+    pub fn internal_ticket_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        ticket_id: &String,
+    ) {
+        require!(sender_id != receiver_id, ERROR_SENDER_RECEIVER_SAME);
+
+        let mut ticket = self
+            .withdrawal_tickets
+            .get(ticket_id)
+            .expect(ERROR_WITHDRAWAL_TICKET_NOT_FOUND);
+        require!(
+            &ticket.owner_id == sender_id,
+            ERROR_NOT_WITHDRAWAL_TICKET_OWNER
+        );
+
+        ticket.owner_id = receiver_id.clone();
+        self.withdrawal_tickets.insert(ticket_id, &ticket);
+
+        Event::WithdrawalTicketTransfer {
+            ticket_id: ticket_id.clone(),
+            old_owner_id: sender_id.clone(),
+            new_owner_id: receiver_id.clone(),
+        }
+        .emit();
+
+        nep171::NftTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            token_ids: &[ticket_id.as_str()],
+            authorized_id: None,
+            memo: None,
+        }
+        .emit();
+    }
+
+    /// Returns true if the ticket stays with the receiver
+    pub fn int_nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: &AccountId,
+        receiver_id: &AccountId,
+        ticket_id: &String,
+    ) -> bool {
+        // Whether the receiver asked for the ticket to be returned
+        let must_revert = match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            PromiseResult::Failed => true,
+        };
+
+        if !must_revert {
+            return true;
+        }
+
+        // the receiver may have already claimed or passed on the ticket
+        match self.withdrawal_tickets.get(ticket_id) {
+            Some(ticket) if &ticket.owner_id == receiver_id => {
+                self.internal_ticket_transfer(receiver_id, previous_owner_id, ticket_id);
+                log!(
+                    "Return ticket {} from {} to {}",
+                    ticket_id,
+                    receiver_id,
+                    previous_owner_id
+                );
+                false
+            }
+            _ => true,
+        }
+    }
+}
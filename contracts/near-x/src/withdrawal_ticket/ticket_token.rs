@@ -0,0 +1,126 @@
+use crate::constants::{gas, NO_DEPOSIT};
+use crate::contract::*;
+use crate::state::WithdrawalTicket;
+use near_contract_standards::non_fungible_token::core::{
+    NonFungibleTokenCore, NonFungibleTokenResolver,
+};
+use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
+use near_contract_standards::non_fungible_token::{Token, TokenId};
+use near_sdk::serde_json::json;
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, Promise, PromiseOrValue};
+use std::collections::HashMap;
+
+#[near_bindgen]
+impl NonFungibleTokenCore for NearxPool {
+    /// NEP-171 for withdrawal tickets, approvals are not supported
+    #[payable]
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        #[allow(unused)] approval_id: Option<u64>,
+        #[allow(unused)] memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.internal_ticket_transfer(&env::predecessor_account_id(), &receiver_id, &token_id);
+    }
+
+    #[payable]
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        #[allow(unused)] approval_id: Option<u64>,
+        #[allow(unused)] memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        assert_one_yocto();
+        let min_gas = gas::NFT_TRANSFER + gas::NFT_TRANSFER_RESOLVE;
+        assert!(
+            env::prepaid_gas() > min_gas,
+            "require at least {:?} gas",
+            min_gas
+        );
+
+        self.internal_ticket_transfer(&env::predecessor_account_id(), &receiver_id, &token_id);
+
+        let args = json!({
+            "sender_id": env::predecessor_account_id(),
+            "previous_owner_id": env::predecessor_account_id(),
+            "token_id": token_id,
+            "msg": msg,
+        });
+        Promise::new(receiver_id.clone())
+            .function_call(
+                "nft_on_transfer".to_string(),
+                args.to_string().into_bytes(),
+                NO_DEPOSIT,
+                env::prepaid_gas() - gas::NFT_TRANSFER - gas::NFT_TRANSFER_RESOLVE,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(gas::NFT_TRANSFER_RESOLVE)
+                    .nft_resolve_transfer(
+                        env::predecessor_account_id(),
+                        receiver_id,
+                        token_id,
+                        None,
+                    ),
+            )
+            .into()
+    }
+
+    fn nft_token(&self, token_id: TokenId) -> Option<Token> {
+        self.withdrawal_tickets
+            .get(&token_id)
+            .map(|ticket| ticket_to_token(token_id, ticket))
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for NearxPool {
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        #[allow(unused)] approved_account_ids: Option<HashMap<AccountId, u64>>,
+    ) -> bool {
+        self.int_nft_resolve_transfer(&previous_owner_id, &receiver_id, &token_id)
+    }
+}
+
+fn ticket_to_token(token_id: TokenId, ticket: WithdrawalTicket) -> Token {
+    let metadata = TokenMetadata {
+        title: Some(format!("NearX withdrawal ticket #{}", token_id)),
+        description: Some(format!(
+            "{} yoctoNEAR claimable from epoch {}",
+            ticket.amount, ticket.withdrawable_epoch_height
+        )),
+        media: None,
+        media_hash: None,
+        copies: Some(1),
+        issued_at: None,
+        expires_at: None,
+        starts_at: None,
+        updated_at: None,
+        extra: Some(
+            near_sdk::serde_json::json!({
+                "amount": ticket.amount.to_string(),
+                "withdrawable_epoch_height": ticket.withdrawable_epoch_height,
+            })
+            .to_string(),
+        ),
+        reference: None,
+        reference_hash: None,
+    };
+
+    Token {
+        token_id,
+        owner_id: ticket.owner_id,
+        metadata: Some(metadata),
+        approved_account_ids: None,
+    }
+}
@@ -3,15 +3,24 @@ mod helpers;
 use crate::helpers::abs_diff_eq;
 use helpers::ntoy;
 use near_contract_standards::fungible_token::core::FungibleTokenCore;
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadataProvider;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenCore;
 use near_contract_standards::storage_management::StorageManagement;
 use near_sdk::json_types::{U128, U64};
-use near_sdk::test_utils::testing_env_with_promise_results;
-use near_sdk::{testing_env, AccountId, Gas, PromiseResult, PublicKey, VMContext};
-use near_x::constants::NUM_EPOCHS_TO_UNLOCK;
+use near_sdk::test_utils::{get_created_receipts, get_logs, testing_env_with_promise_results};
+use near_sdk::{testing_env, AccountId, Gas, PromiseOrValue, PromiseResult, PublicKey, VMContext};
+use near_x::constants::{
+    EPOCHS_PER_YEAR, MAX_PRICE_SUBSCRIBERS, MAX_UNSTAKE_REQUESTS, NUM_EPOCHS_TO_UNLOCK,
+    PRICE_HISTORY_LENGTH, WITHDRAWAL_TICKET_STORAGE_DEPOSIT,
+};
 use near_x::contract::{NearxPool, OperationControls};
 use near_x::state::{
-    Account, Fraction, HumanReadableAccount, OperationsControlUpdateRequest, ValidatorInfo,
-    ValidatorInfoResponse, ValidatorInfoWrapper, ValidatorType,
+    Account, ApyResponse, CouncilAction, EpochAction, EpochActionResponse, ExchangeRateBounds,
+    Fraction, HumanReadableAccount, InsuranceFundRecordResponse, LegacyAccountV1,
+    OperationsControlUpdateRequest, PriceSnapshotResponse, ProposalAction, ProposedFtMetadata,
+    QuarantinedBalanceResponse, Role, UnstakeEpochCap, UnstakeQueueEntryResponse, UnstakeRequest,
+    UnstakeStrategy, ValidatorInfo, ValidatorInfoResponse, ValidatorInfoWrapper, ValidatorType,
 };
 use std::{convert::TryFrom, str::FromStr};
 
@@ -161,6 +170,8 @@ fn test_non_owner_calling_update_operations_control() {
         sync_validator_balance_paused: None,
         ft_transfer_paused: None,
         ft_transfer_call_paused: None,
+        instant_unstake_paused: None,
+        rebalance_epoch_paused: None,
     });
 }
 
@@ -186,6 +197,8 @@ fn test_update_operations_control_success() {
         sync_validator_balance_paused: Some(true),
         ft_transfer_paused: Some(true),
         ft_transfer_call_paused: Some(true),
+        instant_unstake_paused: None,
+        rebalance_epoch_paused: None,
     });
 
     let operations_control = contract.get_operations_control();
@@ -202,7 +215,9 @@ fn test_update_operations_control_success() {
             autocompounding_epoch_paused: false,
             sync_validator_balance_paused: true,
             ft_transfer_paused: true,
-            ft_transfer_call_paused: true
+            ft_transfer_call_paused: true,
+            instant_unstake_paused: false,
+            rebalance_epoch_paused: false,
         }
     );
 }
@@ -1768,8 +1783,11 @@ fn test_on_get_sp_staked_balance_for_rewards() {
     contract.total_staked = ntoy(100);
     contract.total_stake_shares = ntoy(100);
 
-    let _res =
-        contract.on_get_sp_staked_balance_for_rewards(validator1.account_id, U128::from(ntoy(150)));
+    let _res = contract.on_get_sp_staked_balance_for_rewards(
+        validator1.account_id,
+        None,
+        U128::from(ntoy(150)),
+    );
 
     let validator1 = get_validator(&contract, stake_public_key_1.clone());
     assert_eq!(validator1.staked, ntoy(150));
@@ -1801,6 +1819,87 @@ fn test_deposit_and_stake_fail_min_deposit() {
     contract.deposit_and_stake();
 }
 
+#[test]
+fn test_deposit_and_stake_for_registers_receiver() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+    let receiver = AccountId::from_str("defi_contract").unwrap();
+    let min_balance = contract.storage_balance_bounds().min.0;
+
+    contract.min_deposit_amount = ntoy(1);
+    contract.total_staked = ntoy(10);
+    contract.total_stake_shares = ntoy(10);
+
+    context.predecessor_account_id = user1.clone();
+    context.attached_deposit = ntoy(10) + min_balance;
+    testing_env!(context);
+
+    contract.deposit_and_stake_for(receiver.clone(), None);
+
+    let receiver_account = contract.get_account(receiver.clone());
+    assert_eq!(receiver_account.staked_balance, U128(ntoy(10)));
+    assert_eq!(contract.ft_balance_of(receiver), U128(ntoy(10)));
+    assert!(contract.storage_balance_of(user1).is_none());
+
+    assert_eq!(contract.total_staked, ntoy(20));
+    assert_eq!(contract.user_amount_to_stake_in_epoch, ntoy(10));
+}
+
+#[test]
+fn test_deposit_and_stake_for_with_msg() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+    let receiver = AccountId::from_str("defi_contract").unwrap();
+
+    contract.min_deposit_amount = ntoy(1);
+
+    context.predecessor_account_id = user1.clone();
+    context.attached_deposit = ntoy(1);
+    testing_env!(context.clone());
+    contract.storage_deposit(None, None);
+    contract.storage_deposit(Some(receiver.clone()), None);
+
+    context.attached_deposit = ntoy(10);
+    testing_env!(context);
+    contract.deposit_and_stake_for(receiver.clone(), Some("deposit".to_string()));
+
+    // the receiver holds the NearX until ft_resolve_transfer refunds what it did not use
+    assert_eq!(contract.ft_balance_of(receiver), U128(ntoy(10)));
+    assert_eq!(contract.ft_balance_of(user1), U128(0));
+}
+
+#[test]
+#[should_panic]
+fn test_deposit_and_stake_for_with_msg_unregistered_sender() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let receiver = AccountId::from_str("defi_contract").unwrap();
+
+    context.predecessor_account_id = AccountId::from_str("user1").unwrap();
+    context.attached_deposit = ntoy(10);
+    testing_env!(context);
+
+    contract.deposit_and_stake_for(receiver, Some("deposit".to_string()));
+}
+
+#[test]
+#[should_panic(expected = "Not enough balance for storage staking")]
+fn test_deposit_and_stake_for_not_enough_for_storage() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = AccountId::from_str("user1").unwrap();
+    context.attached_deposit = contract.storage_balance_bounds().min.0;
+    testing_env!(context);
+
+    contract.deposit_and_stake_for(AccountId::from_str("defi_contract").unwrap(), None);
+}
+
 #[test]
 #[should_panic]
 fn test_deposit_and_stake_fail_zero_amount() {
@@ -1828,7 +1927,7 @@ fn test_deposit_and_stake_success() {
     contract.total_stake_shares = ntoy(10);
     contract.user_amount_to_stake_in_epoch = ntoy(10);
 
-    context.attached_deposit = 3000000000000000000000;
+    context.attached_deposit = 4000000000000000000000;
     testing_env!(context.clone());
     contract.storage_deposit(None, None);
 
@@ -2016,7 +2115,7 @@ fn test_unstake_paused() {
 }
 
 #[test]
-#[should_panic]
+#[should_panic(expected = "Withdraw paused")]
 fn test_withdraw_paused() {
     let (mut _context, mut contract) =
         contract_setup(owner_account(), operator_account(), treasury_account());
@@ -2135,7 +2234,7 @@ fn test_on_validator_deposit_and_stake_failed() {
 
     testing_env_with_promise_results(context.clone(), PromiseResult::Failed);
 
-    contract.on_stake_pool_deposit_and_stake(validator1.clone(), ntoy(10));
+    contract.on_stake_pool_deposit_and_stake(validator1.clone(), ntoy(10), None);
 
     assert_eq!(contract.reconciled_epoch_stake_amount, ntoy(20));
 }
@@ -2165,7 +2264,7 @@ fn test_on_validator_unstake_success() {
 
     testing_env_with_promise_results(context.clone(), PromiseResult::Successful(Vec::default()));
 
-    contract.on_stake_pool_unstake(val1_info.account_id, ntoy(10));
+    contract.on_stake_pool_unstake(val1_info.account_id, ntoy(10), None);
 
     let val1_info = get_validator(&contract, validator1.clone());
     assert_eq!(val1_info.staked, ntoy(90));
@@ -2201,7 +2300,7 @@ fn test_on_validator_unstake_fail() {
 
     testing_env_with_promise_results(context.clone(), PromiseResult::Failed);
 
-    contract.on_stake_pool_unstake(val1_info.account_id, ntoy(10));
+    contract.on_stake_pool_unstake(val1_info.account_id, ntoy(10), None);
 
     let val1_info = get_validator(&contract, validator1.clone());
     assert_eq!(val1_info.staked, ntoy(100));
@@ -2235,7 +2334,7 @@ fn test_on_validator_deposit_and_stake_success() {
 
     testing_env_with_promise_results(context.clone(), PromiseResult::Successful(Vec::default()));
 
-    contract.on_stake_pool_deposit_and_stake(validator1.clone(), ntoy(10));
+    contract.on_stake_pool_deposit_and_stake(validator1.clone(), ntoy(10), None);
 
     let val1_info = get_validator(&contract, validator1.clone());
     assert_eq!(val1_info.staked, ntoy(110));
@@ -2317,7 +2416,10 @@ fn test_withdraw_fail_not_enough_amount() {
     let user1 = AccountId::from_str("user1").unwrap();
 
     let mut user1_account = Account::default();
-    user1_account.unstaked_amount += ntoy(100);
+    user1_account.unstake_requests = vec![UnstakeRequest {
+        amount: ntoy(100),
+        withdrawable_epoch_height: 0,
+    }];
     update_account(&mut contract, user1.clone(), &user1_account);
 
     context.predecessor_account_id = user1;
@@ -2335,8 +2437,10 @@ fn test_withdraw_fail_before_withdrawable_epoch() {
     let user1 = AccountId::from_str("user1").unwrap();
 
     let mut user1_account = Account::default();
-    user1_account.unstaked_amount = ntoy(300);
-    user1_account.withdrawable_epoch_height = 10;
+    user1_account.unstake_requests = vec![UnstakeRequest {
+        amount: ntoy(300),
+        withdrawable_epoch_height: 10,
+    }];
     update_account(&mut contract, user1.clone(), &user1_account);
 
     context.epoch_height = 8;
@@ -2355,8 +2459,10 @@ fn test_withdraw_fail_not_enough_storage_balance() {
     let user1 = AccountId::from_str("user1").unwrap();
 
     let mut user1_account = Account::default();
-    user1_account.unstaked_amount = ntoy(200);
-    user1_account.withdrawable_epoch_height = 10;
+    user1_account.unstake_requests = vec![UnstakeRequest {
+        amount: ntoy(200),
+        withdrawable_epoch_height: 10,
+    }];
     update_account(&mut contract, user1.clone(), &user1_account);
 
     context.epoch_height = 12;
@@ -2376,8 +2482,10 @@ fn test_withdraw_success() {
     let user1 = AccountId::from_str("user1").unwrap();
 
     let mut user1_account = Account::default();
-    user1_account.unstaked_amount += ntoy(300);
-    user1_account.withdrawable_epoch_height = 10;
+    user1_account.unstake_requests = vec![UnstakeRequest {
+        amount: ntoy(300),
+        withdrawable_epoch_height: 10,
+    }];
     update_account(&mut contract, user1.clone(), &user1_account);
 
     context.epoch_height = 12;
@@ -2388,7 +2496,7 @@ fn test_withdraw_success() {
     contract.withdraw(U128(ntoy(200)));
 
     let user1_account = get_account(&contract, user1.clone());
-    assert_eq!(user1_account.unstaked_amount, ntoy(100));
+    assert_eq!(user1_account.unstaked_amount(), ntoy(100));
 }
 
 #[test]
@@ -2399,8 +2507,10 @@ fn test_withdraw_success_with_storage_balance_with_no_staked_amount() {
     let user1 = AccountId::from_str("user1").unwrap();
 
     let mut user1_account = Account::default();
-    user1_account.unstaked_amount += ntoy(300);
-    user1_account.withdrawable_epoch_height = 10;
+    user1_account.unstake_requests = vec![UnstakeRequest {
+        amount: ntoy(300),
+        withdrawable_epoch_height: 10,
+    }];
     update_account(&mut contract, user1.clone(), &user1_account);
 
     contract.min_storage_reserve = ntoy(50);
@@ -2413,7 +2523,7 @@ fn test_withdraw_success_with_storage_balance_with_no_staked_amount() {
     contract.withdraw(U128(299999900000000000000000000));
 
     let user1_account = get_account(&contract, user1.clone());
-    assert_eq!(user1_account.unstaked_amount, 0);
+    assert_eq!(user1_account.unstaked_amount(), 0);
     assert_eq!(user1_account.stake_shares, 0);
 }
 
@@ -2425,9 +2535,11 @@ fn test_withdraw_success_with_storage_balance_with_staked_amount() {
     let user1 = AccountId::from_str("user1").unwrap();
 
     let mut user1_account = Account::default();
-    user1_account.unstaked_amount += ntoy(300);
     user1_account.stake_shares = ntoy(10);
-    user1_account.withdrawable_epoch_height = 10;
+    user1_account.unstake_requests = vec![UnstakeRequest {
+        amount: ntoy(300),
+        withdrawable_epoch_height: 10,
+    }];
     update_account(&mut contract, user1.clone(), &user1_account);
 
     contract.min_storage_reserve = ntoy(50);
@@ -2441,7 +2553,7 @@ fn test_withdraw_success_with_storage_balance_with_staked_amount() {
 
     let user1_account = get_account(&contract, user1.clone());
     assert_eq!(user1_account.stake_shares, ntoy(10));
-    assert_eq!(user1_account.unstaked_amount, ntoy(0));
+    assert_eq!(user1_account.unstaked_amount(), ntoy(0));
 }
 
 #[test]
@@ -2545,7 +2657,7 @@ fn test_on_stake_pool_withdraw_all_fail() {
 
     testing_env_with_promise_results(context.clone(), PromiseResult::Failed);
 
-    contract.on_stake_pool_withdraw_all(val1_info.account_id, ntoy(100));
+    contract.on_stake_pool_withdraw_all(val1_info.account_id, ntoy(100), None);
 
     let val1_info = get_validator(&contract, validator1.clone());
     assert_eq!(val1_info.unstaked_amount, ntoy(100));
@@ -2598,7 +2710,6 @@ fn test_unstake_success_remaining_amount_less_than_storage_deposit() {
 
     let mut user1_account = Account::default();
     user1_account.stake_shares = ntoy(50);
-    user1_account.unstaked_amount = ntoy(0);
     update_account(&mut contract, user1.clone(), &user1_account);
 
     context.predecessor_account_id = user1.clone();
@@ -2608,8 +2719,8 @@ fn test_unstake_success_remaining_amount_less_than_storage_deposit() {
 
     let user1_account = get_account(&contract, user1.clone());
     assert_eq!(user1_account.stake_shares, ntoy(0));
-    assert_eq!(user1_account.unstaked_amount, ntoy(50));
-    assert_eq!(user1_account.withdrawable_epoch_height, 14);
+    assert_eq!(user1_account.unstaked_amount(), ntoy(50));
+    assert_eq!(user1_account.withdrawable_epoch_height(), 14);
 
     assert_eq!(contract.total_staked, ntoy(450));
     assert_eq!(contract.total_stake_shares, ntoy(450));
@@ -2645,7 +2756,10 @@ fn test_unstake_success_diff_epoch_than_reconcilation_epoch() {
 
     let mut user1_account = Account::default();
     user1_account.stake_shares = ntoy(50);
-    user1_account.unstaked_amount = ntoy(10);
+    user1_account.unstake_requests = vec![UnstakeRequest {
+        amount: ntoy(10),
+        withdrawable_epoch_height: 0,
+    }];
     update_account(&mut contract, user1.clone(), &user1_account);
 
     context.predecessor_account_id = user1.clone();
@@ -2655,8 +2769,8 @@ fn test_unstake_success_diff_epoch_than_reconcilation_epoch() {
 
     let user1_account = get_account(&contract, user1.clone());
     assert_eq!(user1_account.stake_shares, ntoy(40));
-    assert_eq!(user1_account.unstaked_amount, ntoy(20));
-    assert_eq!(user1_account.withdrawable_epoch_height, 14);
+    assert_eq!(user1_account.unstaked_amount(), ntoy(20));
+    assert_eq!(user1_account.withdrawable_epoch_height(), 14);
 
     assert_eq!(contract.total_staked, ntoy(90));
     assert_eq!(contract.total_stake_shares, ntoy(90));
@@ -2692,7 +2806,10 @@ fn test_unstake_success_same_epoch_as_reconcilation_epoch() {
 
     let mut user1_account = Account::default();
     user1_account.stake_shares = ntoy(50);
-    user1_account.unstaked_amount = ntoy(10);
+    user1_account.unstake_requests = vec![UnstakeRequest {
+        amount: ntoy(10),
+        withdrawable_epoch_height: 0,
+    }];
     update_account(&mut contract, user1.clone(), &user1_account);
 
     context.predecessor_account_id = user1.clone();
@@ -2702,8 +2819,8 @@ fn test_unstake_success_same_epoch_as_reconcilation_epoch() {
 
     let user1_account = get_account(&contract, user1.clone());
     assert_eq!(user1_account.stake_shares, ntoy(40));
-    assert_eq!(user1_account.unstaked_amount, ntoy(20));
-    assert_eq!(user1_account.withdrawable_epoch_height, 15);
+    assert_eq!(user1_account.unstaked_amount(), ntoy(20));
+    assert_eq!(user1_account.withdrawable_epoch_height(), 15);
 
     assert_eq!(contract.total_staked, ntoy(90));
     assert_eq!(contract.total_stake_shares, ntoy(90));
@@ -2747,7 +2864,10 @@ fn test_unstake_success_without_validators_to_unstake_from() {
 
     let mut user1_account = Account::default();
     user1_account.stake_shares = ntoy(150);
-    user1_account.unstaked_amount = ntoy(10);
+    user1_account.unstake_requests = vec![UnstakeRequest {
+        amount: ntoy(10),
+        withdrawable_epoch_height: 0,
+    }];
     update_account(&mut contract, user1.clone(), &user1_account);
 
     context.predecessor_account_id = user1.clone();
@@ -2757,8 +2877,8 @@ fn test_unstake_success_without_validators_to_unstake_from() {
 
     let user1_account = get_account(&contract, user1.clone());
     assert_eq!(user1_account.stake_shares, ntoy(70));
-    assert_eq!(user1_account.unstaked_amount, ntoy(90));
-    assert_eq!(user1_account.withdrawable_epoch_height, 14);
+    assert_eq!(user1_account.unstaked_amount(), ntoy(90));
+    assert_eq!(user1_account.withdrawable_epoch_height(), 14);
 
     assert_eq!(contract.total_staked, ntoy(120));
     assert_eq!(contract.total_stake_shares, ntoy(120));
@@ -2766,14 +2886,38 @@ fn test_unstake_success_without_validators_to_unstake_from() {
 
     context.epoch_height = 11;
     context.predecessor_account_id = user1.clone();
+    // a third unstake request goes beyond what the registration storage covers
+    context.attached_deposit = ntoy(1) / 100;
+    testing_env!(context.clone());
+    contract.storage_deposit(None, None);
+
+    context.attached_deposit = 0;
     testing_env!(context.clone());
 
     contract.unstake(U128(ntoy(40)));
 
     let user1_account = get_account(&contract, user1.clone());
     assert_eq!(user1_account.stake_shares, ntoy(30));
-    assert_eq!(user1_account.unstaked_amount, ntoy(130));
-    assert_eq!(user1_account.withdrawable_epoch_height, 19);
+    assert_eq!(user1_account.unstaked_amount(), ntoy(130));
+    // the second unstake gets its own release epoch and does not push back the first one
+    assert_eq!(user1_account.withdrawable_epoch_height(), 15);
+    assert_eq!(
+        user1_account.unstake_requests,
+        vec![
+            UnstakeRequest {
+                amount: ntoy(10),
+                withdrawable_epoch_height: 0,
+            },
+            UnstakeRequest {
+                amount: ntoy(80),
+                withdrawable_epoch_height: 14,
+            },
+            UnstakeRequest {
+                amount: ntoy(40),
+                withdrawable_epoch_height: 15,
+            },
+        ]
+    );
 
     assert_eq!(contract.total_staked, ntoy(80));
     assert_eq!(contract.total_stake_shares, ntoy(80));
@@ -3819,8 +3963,7 @@ fn test_ft_transfer_same_user() {
 
     let user1_account = Account {
         stake_shares: ntoy(10),
-        unstaked_amount: 0,
-        withdrawable_epoch_height: 0,
+        unstake_requests: vec![],
     };
 
     update_account(&mut contract, user1_account_id.clone(), &user1_account);
@@ -3842,8 +3985,7 @@ fn test_ft_transfer() {
 
     let user1_account = Account {
         stake_shares: ntoy(10),
-        unstaked_amount: 0,
-        withdrawable_epoch_height: 0,
+        unstake_requests: vec![],
     };
 
     update_account(&mut contract, user1_account_id.clone(), &user1_account);
@@ -3868,8 +4010,7 @@ fn test_ft_transfer() {
         user1_account,
         Account {
             stake_shares: ntoy(5),
-            unstaked_amount: 0,
-            withdrawable_epoch_height: 0
+            unstake_requests: vec![]
         }
     );
 
@@ -3878,12 +4019,262 @@ fn test_ft_transfer() {
         user2_account,
         Account {
             stake_shares: ntoy(5),
-            unstaked_amount: 0,
-            withdrawable_epoch_height: 0
+            unstake_requests: vec![]
         }
     );
 }
 
+#[test]
+fn test_nep141_events() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+    let user2 = AccountId::from_str("user2").unwrap();
+
+    context.predecessor_account_id = user1.clone();
+    context.attached_deposit = ntoy(1);
+    testing_env!(context.clone());
+    contract.storage_deposit(Some(user1.clone()), None);
+    contract.storage_deposit(Some(user2.clone()), None);
+
+    context.attached_deposit = ntoy(10);
+    testing_env!(context.clone());
+    contract.deposit_and_stake();
+
+    assert!(get_logs().contains(
+        &r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[{"owner_id":"user1","amount":"10000000000000000000000000","memo":"deposit_and_stake"}]}"#
+            .to_string()
+    ));
+
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.ft_transfer(user2.clone(), U128(ntoy(4)), Some("gift".to_string()));
+
+    assert!(get_logs().contains(
+        &r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"user1","new_owner_id":"user2","amount":"4000000000000000000000000","memo":"gift"}]}"#
+            .to_string()
+    ));
+
+    context.attached_deposit = 0;
+    testing_env!(context.clone());
+    contract.unstake(U128(ntoy(2)));
+
+    assert!(get_logs().contains(
+        &r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{"owner_id":"user1","amount":"2000000000000000000000000","memo":"unstake"}]}"#
+            .to_string()
+    ));
+}
+
+#[test]
+fn test_storage_balance_tracks_account_size() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+    let min_balance = contract.storage_balance_bounds().min.0;
+    assert!(contract.storage_balance_bounds().max.is_none());
+
+    context.predecessor_account_id = user1.clone();
+    context.attached_deposit = ntoy(1);
+    testing_env!(context.clone());
+    contract.storage_deposit(None, None);
+
+    let storage_balance = contract.storage_balance_of(user1.clone()).unwrap();
+    assert_eq!(storage_balance.total, U128(min_balance));
+    assert_eq!(storage_balance.available, U128(0));
+
+    // registered accounts can top up their storage balance
+    context.attached_deposit = ntoy(1) / 100;
+    testing_env!(context.clone());
+    let storage_balance = contract.storage_deposit(None, None);
+    assert_eq!(storage_balance.total, U128(min_balance + ntoy(1) / 100));
+    let available = storage_balance.available.0;
+    assert!(available > 0 && available < ntoy(1) / 100);
+
+    // the registration minimum already pays for the max number of unstake requests
+    let mut user1_account = get_account(&contract, user1.clone());
+    user1_account.unstake_requests = (0..MAX_UNSTAKE_REQUESTS as u64)
+        .map(|i| UnstakeRequest {
+            amount: ntoy(1),
+            withdrawable_epoch_height: 20 + i,
+        })
+        .collect();
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    let storage_balance = contract.storage_balance_of(user1.clone()).unwrap();
+    assert_eq!(storage_balance.available, U128(available));
+
+    // only the available balance can be withdrawn
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    let storage_balance = contract.storage_withdraw(None);
+    assert_eq!(storage_balance.available, U128(0));
+    assert_eq!(
+        storage_balance.total,
+        U128(min_balance + ntoy(1) / 100 - available)
+    );
+}
+
+#[test]
+#[should_panic(expected = "The amount is greater than the available storage balance")]
+fn test_storage_withdraw_more_than_available() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+
+    context.predecessor_account_id = user1;
+    context.attached_deposit = ntoy(1);
+    testing_env!(context.clone());
+    contract.storage_deposit(None, None);
+
+    context.attached_deposit = 1;
+    testing_env!(context);
+    contract.storage_withdraw(Some(U128(1)));
+}
+
+#[test]
+fn test_unstake_max_requests_with_min_storage_deposit() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+
+    context.predecessor_account_id = user1.clone();
+    context.attached_deposit = contract.storage_balance_bounds().min.0;
+    testing_env!(context.clone());
+    contract.storage_deposit(None, None);
+
+    contract.total_staked = ntoy(10_000);
+    contract.total_stake_shares = ntoy(10_000);
+    contract.unstake_epoch_cap = Some(UnstakeEpochCap::Absolute {
+        amount: U128(ntoy(1000)),
+    });
+
+    let mut user1_account = get_account(&contract, user1.clone());
+    user1_account.stake_shares = ntoy(9000);
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    // every unstake is queued to a later epoch and takes a request of its own
+    context.epoch_height = 10;
+    context.attached_deposit = 0;
+    testing_env!(context);
+    for _ in 0..MAX_UNSTAKE_REQUESTS {
+        contract.unstake(U128(ntoy(1000)));
+    }
+
+    let user1_account = get_account(&contract, user1.clone());
+    assert_eq!(user1_account.unstake_requests.len(), MAX_UNSTAKE_REQUESTS);
+    assert_eq!(
+        contract.storage_balance_of(user1).unwrap().available,
+        U128(0)
+    );
+}
+
+fn unstake_by_transfer_setup() -> (VMContext, NearxPool, AccountId) {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    context.predecessor_account_id = user1.clone();
+    context.attached_deposit = ntoy(1);
+    testing_env!(context.clone());
+    contract.storage_deposit(None, None);
+
+    let mut user1_account = get_account(&contract, user1.clone());
+    user1_account.stake_shares = ntoy(50);
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    // the pool holds the NearX until its ft_on_transfer runs
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.ft_transfer_call(
+        contract_account(),
+        U128(ntoy(20)),
+        None,
+        r#"{"action":"unstake"}"#.to_string(),
+    );
+
+    assert_eq!(contract.ft_balance_of(user1.clone()), U128(ntoy(30)));
+    assert_eq!(contract.ft_balance_of(contract_account()), U128(ntoy(20)));
+
+    context.predecessor_account_id = contract_account();
+    context.attached_deposit = 0;
+    testing_env!(context.clone());
+
+    (context, contract, user1)
+}
+
+#[test]
+fn test_ft_on_transfer_unstake() {
+    let (_context, mut contract, user1) = unstake_by_transfer_setup();
+
+    let result = contract.ft_on_transfer(
+        user1.clone(),
+        U128(ntoy(20)),
+        r#"{"action":"unstake"}"#.to_string(),
+    );
+    assert!(matches!(result, PromiseOrValue::Value(U128(0))));
+
+    let user1_account = get_account(&contract, user1);
+    assert_eq!(user1_account.stake_shares, ntoy(30));
+    assert_eq!(user1_account.unstaked_amount(), ntoy(20));
+    assert!(contract.storage_balance_of(contract_account()).is_none());
+
+    assert_eq!(contract.total_staked, ntoy(80));
+    assert_eq!(contract.total_stake_shares, ntoy(80));
+    assert_eq!(contract.user_amount_to_unstake_in_epoch, ntoy(20));
+}
+
+#[test]
+fn test_ft_on_transfer_unknown_msg_is_refunded() {
+    let (_context, mut contract, user1) = unstake_by_transfer_setup();
+
+    let result = contract.ft_on_transfer(
+        user1.clone(),
+        U128(ntoy(20)),
+        r#"{"action":"stake"}"#.to_string(),
+    );
+    assert!(matches!(result, PromiseOrValue::Value(U128(amount)) if amount == ntoy(20)));
+
+    // nothing is unstaked, ft_resolve_transfer hands the NearX back
+    assert_eq!(get_account(&contract, user1).unstaked_amount(), 0);
+    assert_eq!(contract.total_staked, ntoy(100));
+}
+
+#[test]
+#[should_panic(expected = "The pool only accepts NearX")]
+fn test_ft_on_transfer_other_token() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = AccountId::from_str("other_token").unwrap();
+    testing_env!(context);
+
+    contract.ft_on_transfer(
+        AccountId::from_str("user1").unwrap(),
+        U128(ntoy(20)),
+        r#"{"action":"unstake"}"#.to_string(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "NearX can only be sent to the pool with ft_transfer_call")]
+fn test_ft_transfer_to_pool() {
+    let (mut context, mut contract, user1) = unstake_by_transfer_setup();
+
+    context.predecessor_account_id = user1;
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.ft_transfer(contract_account(), U128(ntoy(5)), None);
+}
+
 #[test]
 #[should_panic]
 fn test_ft_transfer_without_storage_deposit() {
@@ -3895,8 +4286,7 @@ fn test_ft_transfer_without_storage_deposit() {
 
     let user1_account = Account {
         stake_shares: ntoy(10),
-        unstaked_amount: 0,
-        withdrawable_epoch_height: 0,
+        unstake_requests: vec![],
     };
 
     update_account(&mut contract, user1_account_id.clone(), &user1_account);
@@ -3912,8 +4302,7 @@ fn test_ft_transfer_without_storage_deposit() {
         user1_account,
         Account {
             stake_shares: ntoy(5),
-            unstaked_amount: 0,
-            withdrawable_epoch_height: 0
+            unstake_requests: vec![]
         }
     );
 
@@ -3922,8 +4311,7 @@ fn test_ft_transfer_without_storage_deposit() {
         user2_account,
         Account {
             stake_shares: ntoy(5),
-            unstaked_amount: 0,
-            withdrawable_epoch_height: 0
+            unstake_requests: vec![]
         }
     );
 }
@@ -3959,8 +4347,10 @@ fn test_storage_unregister_account_non_empty_account() {
         user1_account_id.clone(),
         &Account {
             stake_shares: ntoy(10),
-            unstaked_amount: ntoy(10),
-            withdrawable_epoch_height: 100,
+            unstake_requests: vec![UnstakeRequest {
+                amount: ntoy(10),
+                withdrawable_epoch_height: 100,
+            }],
         },
     );
 
@@ -3983,8 +4373,7 @@ fn test_storage_unregister_account_success() {
         user1_account_id.clone(),
         &Account {
             stake_shares: ntoy(0),
-            unstaked_amount: ntoy(0),
-            withdrawable_epoch_height: 100,
+            unstake_requests: vec![],
         },
     );
 
@@ -3992,6 +4381,39 @@ fn test_storage_unregister_account_success() {
 
     let user1_account = get_account_option(&contract, user1_account_id);
     assert!(user1_account.is_none());
+    // the registration minimum was never recorded, nothing to refund
+    assert!(get_created_receipts().is_empty());
+}
+
+#[test]
+fn test_storage_unregister_refunds_recorded_deposit() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+    let min_balance = contract.storage_balance_bounds().min.0;
+
+    context.predecessor_account_id = user1.clone();
+    context.attached_deposit = min_balance;
+    testing_env!(context.clone());
+    contract.storage_deposit(None, None);
+
+    context.attached_deposit = ntoy(1) / 100;
+    testing_env!(context.clone());
+    contract.storage_deposit(None, None);
+
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    assert!(contract.storage_unregister(None));
+
+    assert!(get_account_option(&contract, user1.clone()).is_none());
+    let receipts = get_created_receipts();
+    assert_eq!(receipts.len(), 1);
+    assert_eq!(receipts[0].receiver_id, user1);
+    assert!(matches!(
+        receipts[0].actions[..],
+        [near_sdk::mock::VmAction::Transfer { deposit }] if deposit == ntoy(1) / 100
+    ));
 }
 
 #[test]
@@ -4150,3 +4572,3051 @@ fn test_on_stake_pool_direct_deposit_and_stake_success() {
     let user1 = get_account(&contract, user1_account_id);
     assert_eq!(user1.stake_shares, ntoy(10));
 }
+
+#[test]
+#[should_panic]
+fn test_instant_unstake_paused() {
+    let (mut _context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    contract.operations_control.instant_unstake_paused = true;
+
+    contract.instant_unstake(U128(ntoy(10)));
+}
+
+#[test]
+#[should_panic]
+fn test_fund_instant_unstake_reserve_above_max_reserve() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = ntoy(20);
+    testing_env!(context);
+
+    contract.max_instant_unstake_reserve = ntoy(10);
+
+    contract.fund_instant_unstake_reserve();
+}
+
+#[test]
+fn test_fund_instant_unstake_reserve_success() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = ntoy(20);
+    testing_env!(context);
+
+    contract.max_instant_unstake_reserve = ntoy(40);
+
+    contract.fund_instant_unstake_reserve();
+
+    assert_eq!(contract.instant_unstake_reserve, ntoy(20));
+    assert_eq!(
+        contract.get_instant_unstake_reserve().reserve,
+        U128(ntoy(20))
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_set_instant_unstake_fee_min_greater_than_max() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.set_instant_unstake_fee(Fraction::new(2, 100), Fraction::new(1, 100));
+}
+
+#[test]
+#[should_panic]
+fn test_set_instant_unstake_fee_above_ten_percent() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.set_instant_unstake_fee(Fraction::new(1, 100), Fraction::new(11, 100));
+}
+
+#[test]
+fn test_get_instant_unstake_fee() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.set_instant_unstake_fee(Fraction::new(1, 100), Fraction::new(3, 100));
+    contract.max_instant_unstake_reserve = ntoy(100);
+
+    // empty reserve pays max fee
+    contract.instant_unstake_reserve = 0;
+    assert_eq!(
+        contract.get_instant_unstake_fee(U128(ntoy(100))),
+        U128(ntoy(3))
+    );
+
+    // half full reserve pays half way between min and max fee
+    contract.instant_unstake_reserve = ntoy(50);
+    assert_eq!(
+        contract.get_instant_unstake_fee(U128(ntoy(100))),
+        U128(ntoy(2))
+    );
+
+    // full reserve pays min fee
+    contract.instant_unstake_reserve = ntoy(100);
+    assert_eq!(
+        contract.get_instant_unstake_fee(U128(ntoy(100))),
+        U128(ntoy(1))
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_instant_unstake_not_enough_liquidity() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+    contract.max_instant_unstake_reserve = ntoy(40);
+    contract.instant_unstake_reserve = ntoy(5);
+
+    let mut user1_account = Account::default();
+    user1_account.stake_shares = ntoy(50);
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    context.predecessor_account_id = user1;
+    testing_env!(context);
+
+    contract.instant_unstake(U128(ntoy(10)));
+}
+
+#[test]
+fn test_instant_unstake_success() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.epoch_height = 10;
+    context.predecessor_account_id = owner_account();
+    context.signer_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.add_validator(validator1.clone(), 10);
+    contract.set_instant_unstake_fee(Fraction::new(3, 1000), Fraction::new(3, 100));
+
+    let mut val1_info = get_validator(&contract, validator1.clone());
+    val1_info.staked = ntoy(300);
+    val1_info.unstake_start_epoch = 3;
+    update_validator(&mut contract, validator1.clone(), &val1_info);
+
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+    contract.last_reconcilation_epoch = 8;
+    contract.max_instant_unstake_reserve = ntoy(40);
+    contract.instant_unstake_reserve = ntoy(20);
+
+    let mut user1_account = Account::default();
+    user1_account.stake_shares = ntoy(50);
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    context.predecessor_account_id = user1.clone();
+    context.attached_deposit = 0;
+    testing_env!(context.clone());
+
+    contract.instant_unstake(U128(ntoy(10)));
+
+    // fee is 0.3 - (0.3 - 0.03) * 20 / 40 = 0.165 NEAR
+    let fee = 165000000000000000000000;
+
+    let user1_account = get_account(&contract, user1.clone());
+    assert_eq!(user1_account.stake_shares, ntoy(40));
+    assert_eq!(user1_account.unstaked_amount(), ntoy(0));
+
+    // the fee stays staked as NearX of the treasury
+    assert_eq!(get_account(&contract, treasury_account()).stake_shares, fee);
+    assert_eq!(contract.total_staked, ntoy(90) + fee);
+    assert_eq!(contract.total_stake_shares, ntoy(90) + fee);
+    assert_eq!(contract.user_amount_to_unstake_in_epoch, ntoy(10) - fee);
+    assert_eq!(contract.instant_unstake_reserve, ntoy(10) + fee);
+    assert_eq!(
+        contract.instant_unstake_reserve_pending.to_vec(),
+        vec![(14, ntoy(10) - fee)]
+    );
+    assert_eq!(contract.accumulated_instant_unstake_fees, fee);
+}
+
+#[test]
+fn test_instant_unstake_queued_by_epoch_cap() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.epoch_height = 10;
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.add_validator(validator1.clone(), 10);
+    contract.set_unstake_epoch_cap(Some(UnstakeEpochCap::Absolute {
+        amount: U128(ntoy(1000)),
+    }));
+
+    let mut val1_info = get_validator(&contract, validator1.clone());
+    val1_info.staked = ntoy(5000);
+    val1_info.unstake_start_epoch = 3;
+    update_validator(&mut contract, validator1, &val1_info);
+
+    contract.total_staked = ntoy(5000);
+    contract.total_stake_shares = ntoy(5000);
+    contract.last_reconcilation_epoch = 8;
+    contract.user_amount_to_unstake_in_epoch = ntoy(900);
+    contract.max_instant_unstake_reserve = ntoy(400);
+    contract.instant_unstake_reserve = ntoy(400);
+
+    let mut user1_account = Account::default();
+    user1_account.stake_shares = ntoy(500);
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    context.predecessor_account_id = user1;
+    context.attached_deposit = 0;
+    context.account_balance = ntoy(1000);
+    testing_env!(context);
+
+    contract.instant_unstake(U128(ntoy(200)));
+
+    // the full reserve charges the min fee of 0.3%, which stays staked
+    let unstaked_amount = ntoy(200) - ntoy(200) * 3 / 1000;
+
+    // the current epoch only has room for 100 NEAR, so the reserve gets it back one epoch later
+    assert_eq!(contract.user_amount_to_unstake_in_epoch, ntoy(900));
+    assert_eq!(
+        contract.get_unstake_queue(),
+        vec![UnstakeQueueEntryResponse {
+            epoch: U64(11),
+            amount: U128(unstaked_amount),
+        }]
+    );
+    assert_eq!(
+        contract.instant_unstake_reserve_pending.to_vec(),
+        vec![(11 + NUM_EPOCHS_TO_UNLOCK, unstaked_amount)]
+    );
+}
+
+#[test]
+fn test_withdraw_all_refills_instant_unstake_reserve() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.add_validator(validator1.clone(), 10);
+
+    contract.total_staked = ntoy(100);
+    contract.max_instant_unstake_reserve = ntoy(40);
+    contract.instant_unstake_reserve = ntoy(30);
+    contract
+        .instant_unstake_reserve_pending
+        .insert(&14, &ntoy(2));
+    contract
+        .instant_unstake_reserve_pending
+        .insert(&15, &ntoy(10));
+
+    // nothing is released before the validators withdraw it, even past the release epoch
+    context.predecessor_account_id = contract_account();
+    context.attached_deposit = 0;
+    context.epoch_height = 14;
+    testing_env!(context.clone());
+    contract.epoch_reconcilation();
+    assert_eq!(contract.instant_unstake_reserve, ntoy(30));
+
+    // only what is due is taken out of the withdrawn amount
+    testing_env_with_promise_results(context.clone(), PromiseResult::Successful(Vec::default()));
+    contract.on_stake_pool_withdraw_all(validator1.clone(), ntoy(5), None);
+
+    assert_eq!(contract.instant_unstake_reserve, ntoy(32));
+    assert_eq!(
+        contract.instant_unstake_reserve_pending.to_vec(),
+        vec![(15, ntoy(10))]
+    );
+
+    // a withdrawal short of what is due releases part of it
+    context.epoch_height = 15;
+    testing_env_with_promise_results(context.clone(), PromiseResult::Successful(Vec::default()));
+    contract.on_stake_pool_withdraw_all(validator1.clone(), ntoy(4), None);
+
+    assert_eq!(contract.instant_unstake_reserve, ntoy(36));
+    assert_eq!(
+        contract.instant_unstake_reserve_pending.to_vec(),
+        vec![(15, ntoy(6))]
+    );
+
+    testing_env_with_promise_results(context, PromiseResult::Successful(Vec::default()));
+    contract.on_stake_pool_withdraw_all(validator1, ntoy(20), None);
+
+    // the reserve gets all of it back, even above the max reserve
+    assert_eq!(contract.instant_unstake_reserve, ntoy(42));
+    assert!(contract.instant_unstake_reserve_pending.is_empty());
+    assert_eq!(contract.rewards_buffer, 0);
+    assert_eq!(contract.total_staked, ntoy(100));
+}
+
+#[test]
+fn test_withdraw_instant_unstake_reserve() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    contract.instant_unstake_reserve = ntoy(30);
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+    contract.withdraw_instant_unstake_reserve(U128(ntoy(10)));
+
+    assert_eq!(
+        contract.get_instant_unstake_reserve().reserve,
+        U128(ntoy(20))
+    );
+    let receipts = get_created_receipts();
+    assert_eq!(receipts.len(), 1);
+    assert_eq!(receipts[0].receiver_id, owner_account());
+}
+
+#[test]
+#[should_panic(expected = "Amount is above the instant unstake reserve")]
+fn test_withdraw_instant_unstake_reserve_above_reserve() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    contract.instant_unstake_reserve = ntoy(30);
+    contract
+        .instant_unstake_reserve_pending
+        .insert(&14, &ntoy(10));
+
+    // the pending NEAR is not in the reserve yet
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+    contract.withdraw_instant_unstake_reserve(U128(ntoy(40)));
+}
+
+#[test]
+#[should_panic]
+fn test_withdraw_instant_unstake_reserve_unauthorized() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    contract.instant_unstake_reserve = ntoy(30);
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+    contract.withdraw_instant_unstake_reserve(U128(ntoy(10)));
+}
+
+fn withdrawal_ticket_setup() -> (VMContext, NearxPool, AccountId) {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.epoch_height = 10;
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.add_validator(validator1.clone(), 10);
+
+    let mut val1_info = get_validator(&contract, validator1.clone());
+    val1_info.staked = ntoy(100);
+    update_validator(&mut contract, validator1, &val1_info);
+
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    let mut user1_account = Account::default();
+    user1_account.stake_shares = ntoy(50);
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    context.predecessor_account_id = user1.clone();
+    context.attached_deposit = WITHDRAWAL_TICKET_STORAGE_DEPOSIT;
+    testing_env!(context.clone());
+
+    (context, contract, user1)
+}
+
+#[test]
+#[should_panic(expected = "Not enough deposit attached to pay for the withdrawal ticket storage")]
+fn test_unstake_to_ticket_fail_no_storage_deposit() {
+    let (mut context, mut contract, _) = withdrawal_ticket_setup();
+
+    context.attached_deposit = 0;
+    testing_env!(context);
+
+    contract.unstake_to_ticket(U128(ntoy(10)));
+}
+
+#[test]
+fn test_unstake_to_ticket_success() {
+    let (mut context, mut contract, user1) = withdrawal_ticket_setup();
+
+    let ticket_id = contract.unstake_to_ticket(U128(ntoy(10)));
+    assert_eq!(ticket_id, "1".to_string());
+    assert!(get_logs().contains(
+        &r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"user1","token_ids":["1"]}]}"#
+            .to_string()
+    ));
+
+    let user1_account = get_account(&contract, user1.clone());
+    assert_eq!(user1_account.stake_shares, ntoy(40));
+    assert_eq!(user1_account.unstaked_amount(), 0);
+
+    assert_eq!(contract.total_staked, ntoy(90));
+    assert_eq!(contract.total_stake_shares, ntoy(90));
+    assert_eq!(contract.user_amount_to_unstake_in_epoch, ntoy(10));
+
+    let ticket = contract.get_withdrawal_ticket(ticket_id.clone()).unwrap();
+    assert_eq!(ticket.owner_id, user1);
+    assert_eq!(ticket.amount, U128(ntoy(10)));
+    assert_eq!(ticket.withdrawable_epoch, U64(10 + NUM_EPOCHS_TO_UNLOCK));
+    assert!(!ticket.can_claim);
+
+    let token = contract.nft_token(ticket_id).unwrap();
+    assert_eq!(token.owner_id, user1);
+
+    // a later ticket does not push back the first one
+    context.epoch_height = 12;
+    testing_env!(context);
+
+    let second_ticket_id = contract.unstake_to_ticket(U128(ntoy(5)));
+    assert_eq!(second_ticket_id, "2".to_string());
+
+    let first_ticket = contract.get_withdrawal_ticket("1".to_string()).unwrap();
+    assert_eq!(
+        first_ticket.withdrawable_epoch,
+        U64(10 + NUM_EPOCHS_TO_UNLOCK)
+    );
+    let second_ticket = contract.get_withdrawal_ticket(second_ticket_id).unwrap();
+    assert_eq!(
+        second_ticket.withdrawable_epoch,
+        U64(12 + NUM_EPOCHS_TO_UNLOCK)
+    );
+}
+
+#[test]
+#[should_panic(expected = "Unstaking amount still in unbonding period")]
+fn test_claim_fail_before_withdrawable_epoch() {
+    let (mut context, mut contract, _) = withdrawal_ticket_setup();
+
+    let ticket_id = contract.unstake_to_ticket(U128(ntoy(10)));
+
+    context.attached_deposit = 0;
+    context.epoch_height += NUM_EPOCHS_TO_UNLOCK - 1;
+    testing_env!(context);
+
+    contract.claim(ticket_id);
+}
+
+#[test]
+#[should_panic(expected = "Only the ticket owner can do this")]
+fn test_claim_fail_after_transfer() {
+    let (mut context, mut contract, _) = withdrawal_ticket_setup();
+
+    let user2 = AccountId::from_str("user2").unwrap();
+
+    let ticket_id = contract.unstake_to_ticket(U128(ntoy(10)));
+
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.nft_transfer(user2.clone(), ticket_id.clone(), None, None);
+    assert_eq!(
+        contract.nft_token(ticket_id.clone()).unwrap().owner_id,
+        user2
+    );
+
+    context.attached_deposit = 0;
+    context.epoch_height += NUM_EPOCHS_TO_UNLOCK;
+    testing_env!(context);
+
+    contract.claim(ticket_id);
+}
+
+#[test]
+fn test_claim_success() {
+    let (mut context, mut contract, _) = withdrawal_ticket_setup();
+
+    let user2 = AccountId::from_str("user2").unwrap();
+
+    let ticket_id = contract.unstake_to_ticket(U128(ntoy(10)));
+
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.nft_transfer(user2.clone(), ticket_id.clone(), None, None);
+    assert!(get_logs().contains(
+        &r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"user1","new_owner_id":"user2","token_ids":["1"]}]}"#
+            .to_string()
+    ));
+
+    context.predecessor_account_id = user2;
+    context.attached_deposit = 0;
+    context.epoch_height += NUM_EPOCHS_TO_UNLOCK;
+    testing_env!(context);
+
+    contract.claim(ticket_id.clone());
+    assert!(get_logs().contains(
+        &r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_burn","data":[{"owner_id":"user2","token_ids":["1"],"memo":"claim"}]}"#
+            .to_string()
+    ));
+
+    assert!(contract.get_withdrawal_ticket(ticket_id.clone()).is_none());
+    assert!(contract.nft_token(ticket_id).is_none());
+}
+
+#[test]
+fn test_withdraw_only_matured_unstake_requests() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+
+    let mut user1_account = Account::default();
+    user1_account.unstake_requests = vec![
+        UnstakeRequest {
+            amount: ntoy(100),
+            withdrawable_epoch_height: 10,
+        },
+        UnstakeRequest {
+            amount: ntoy(50),
+            withdrawable_epoch_height: 11,
+        },
+        UnstakeRequest {
+            amount: ntoy(200),
+            withdrawable_epoch_height: 14,
+        },
+    ];
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    context.epoch_height = 12;
+    context.predecessor_account_id = user1.clone();
+    context.account_balance = ntoy(400);
+    testing_env!(context.clone());
+
+    let user1_response = contract.get_user_account(user1.clone());
+    assert_eq!(user1_response.unstaked_balance, U128(ntoy(350)));
+    assert_eq!(user1_response.withdrawable_epoch, U64(14));
+    assert_eq!(user1_response.unstake_requests.len(), 3);
+
+    contract.withdraw(U128(ntoy(120)));
+
+    let user1_account = get_account(&contract, user1.clone());
+    assert_eq!(
+        user1_account.unstake_requests,
+        vec![
+            UnstakeRequest {
+                amount: ntoy(30),
+                withdrawable_epoch_height: 11,
+            },
+            UnstakeRequest {
+                amount: ntoy(200),
+                withdrawable_epoch_height: 14,
+            },
+        ]
+    );
+
+    contract.withdraw_all();
+
+    let user1_account = get_account(&contract, user1);
+    assert_eq!(
+        user1_account.unstake_requests,
+        vec![UnstakeRequest {
+            amount: ntoy(200),
+            withdrawable_epoch_height: 14,
+        }]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Unstaking amount still in unbonding period")]
+fn test_withdraw_fail_more_than_matured_unstake_requests() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+
+    let mut user1_account = Account::default();
+    user1_account.unstake_requests = vec![
+        UnstakeRequest {
+            amount: ntoy(100),
+            withdrawable_epoch_height: 10,
+        },
+        UnstakeRequest {
+            amount: ntoy(200),
+            withdrawable_epoch_height: 14,
+        },
+    ];
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    context.epoch_height = 12;
+    context.predecessor_account_id = user1;
+    context.account_balance = ntoy(400);
+    testing_env!(context);
+
+    contract.withdraw(U128(ntoy(150)));
+}
+
+#[test]
+#[should_panic(expected = "Too many pending unstake requests")]
+fn test_unstake_fail_too_many_unstake_requests() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    let mut user1_account = Account::default();
+    user1_account.stake_shares = ntoy(50);
+    user1_account.unstake_requests = (0..MAX_UNSTAKE_REQUESTS as u64)
+        .map(|i| UnstakeRequest {
+            amount: ntoy(1),
+            withdrawable_epoch_height: 20 + i,
+        })
+        .collect();
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    context.epoch_height = 10;
+    context.predecessor_account_id = user1;
+    testing_env!(context);
+
+    contract.unstake(U128(ntoy(10)));
+}
+
+#[test]
+fn test_legacy_account_is_moved_on_update() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let user1 = AccountId::from_str("user1").unwrap();
+    let user2 = AccountId::from_str("user2").unwrap();
+
+    contract.legacy_accounts.insert(
+        &user1,
+        &LegacyAccountV1 {
+            stake_shares: ntoy(10),
+            unstaked_amount: ntoy(5),
+            withdrawable_epoch_height: 8,
+        },
+    );
+    contract.legacy_accounts.insert(
+        &user2,
+        &LegacyAccountV1 {
+            stake_shares: ntoy(10),
+            unstaked_amount: 0,
+            withdrawable_epoch_height: 0,
+        },
+    );
+
+    assert_eq!(contract.get_number_of_accounts(), 2);
+    let user1_response = contract.get_user_account(user1.clone());
+    assert_eq!(user1_response.unstaked_balance, U128(ntoy(5)));
+    assert_eq!(user1_response.withdrawable_epoch, U64(8));
+
+    context.predecessor_account_id = user1.clone();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.ft_transfer(user2.clone(), U128(ntoy(5)), None);
+
+    assert_eq!(contract.get_number_of_legacy_accounts(), 0);
+    assert_eq!(
+        get_account(&contract, user1),
+        Account {
+            stake_shares: ntoy(5),
+            unstake_requests: vec![UnstakeRequest {
+                amount: ntoy(5),
+                withdrawable_epoch_height: 8,
+            }],
+        }
+    );
+    assert_eq!(
+        get_account(&contract, user2),
+        Account {
+            stake_shares: ntoy(15),
+            unstake_requests: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_migrate_accounts() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    for i in 0..3 {
+        contract.legacy_accounts.insert(
+            &AccountId::from_str(&format!("user{}", i)).unwrap(),
+            &LegacyAccountV1 {
+                stake_shares: ntoy(10),
+                unstaked_amount: ntoy(1),
+                withdrawable_epoch_height: 5,
+            },
+        );
+    }
+
+    context.predecessor_account_id = operator_account();
+    testing_env!(context);
+
+    assert_eq!(contract.migrate_accounts(2), 1);
+    assert_eq!(contract.migrate_accounts(2), 0);
+
+    assert_eq!(contract.get_number_of_accounts(), 3);
+    assert_eq!(contract.accounts.len(), 3);
+    assert_eq!(
+        get_account(&contract, AccountId::from_str("user2").unwrap()),
+        Account {
+            stake_shares: ntoy(10),
+            unstake_requests: vec![UnstakeRequest {
+                amount: ntoy(1),
+                withdrawable_epoch_height: 5,
+            }],
+        }
+    );
+}
+
+#[test]
+fn test_on_get_sp_staked_balance_for_rewards_updates_performance_score() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.add_validator(validator1.clone(), 10);
+
+    context.predecessor_account_id = contract_account();
+    context.epoch_height = 7;
+    testing_env!(context.clone());
+
+    let mut val1_info = get_validator(&contract, validator1.clone());
+    val1_info.staked = ntoy(100);
+    val1_info.last_redeemed_rewards_epoch = 5;
+    update_validator(&mut contract, validator1.clone(), &val1_info);
+
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    // 1 NEAR over 2 epochs on 100 NEAR
+    contract.on_get_sp_staked_balance_for_rewards(validator1.clone(), None, U128(ntoy(101)));
+
+    let val1_info = get_validator(&contract, validator1.clone());
+    assert_eq!(val1_info.performance_score, Some(5_000_000_000_000_000));
+
+    context.epoch_height = 8;
+    testing_env!(context.clone());
+
+    // no rewards in the next epoch
+    contract.on_get_sp_staked_balance_for_rewards(validator1.clone(), None, U128(ntoy(101)));
+
+    let val1_info = get_validator(&contract, validator1);
+    assert_eq!(
+        val1_info.performance_score,
+        Some(5_000_000_000_000_000 * 5 / 6)
+    );
+}
+
+#[test]
+fn test_validator_expected_stake_with_performance_score() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+    let validator2 = AccountId::from_str("stake_public_key_2").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.add_validator(validator1.clone(), 10);
+    contract.add_validator(validator2.clone(), 10);
+
+    let mut val1_info = get_validator(&contract, validator1.clone());
+    val1_info.performance_score = Some(300);
+    update_validator(&mut contract, validator1.clone(), &val1_info);
+
+    let mut val2_info = get_validator(&contract, validator2.clone());
+    val2_info.performance_score = Some(100);
+    update_validator(&mut contract, validator2.clone(), &val2_info);
+
+    contract.total_staked = ntoy(100);
+
+    // scoring is off by default
+    let performance = contract.get_validators_performance();
+    assert!(performance
+        .iter()
+        .all(|validator| validator.expected_stake == U128(ntoy(50))));
+
+    // multipliers would be 1.5 and 0.5, but are kept within 0.8 and 1.2
+    contract.set_performance_multiplier_bounds(Fraction::new(4, 5), Fraction::new(6, 5));
+
+    let performance = contract.get_validators_performance();
+    let val1_performance = performance
+        .iter()
+        .find(|validator| validator.account_id == validator1)
+        .unwrap();
+    let val2_performance = performance
+        .iter()
+        .find(|validator| validator.account_id == validator2)
+        .unwrap();
+    assert_eq!(val1_performance.effective_weight, U128(12_000_000));
+    assert_eq!(val1_performance.expected_stake, U128(ntoy(60)));
+    assert_eq!(val2_performance.effective_weight, U128(8_000_000));
+    assert_eq!(val2_performance.expected_stake, U128(ntoy(40)));
+
+    let (selected_validator, amount) = contract.get_validator_to_stake(ntoy(20));
+    assert_eq!(selected_validator.unwrap().account_id, validator1);
+    assert_eq!(amount, ntoy(20));
+}
+
+#[test]
+#[should_panic(expected = "Performance multiplier bounds should satisfy")]
+fn test_set_performance_multiplier_bounds_fail_above_max() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.set_performance_multiplier_bounds(Fraction::new(1, 2), Fraction::new(11, 1));
+}
+
+#[test]
+fn test_validator_auto_paused_after_failed_epoch_actions() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.epoch_height = 100;
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+    let validator2 = AccountId::from_str("stake_public_key_2").unwrap();
+
+    contract.add_validator(validator1.clone(), 10);
+    contract.add_validator(validator2.clone(), 10);
+    contract.set_validator_delinquency_threshold(2);
+
+    context.predecessor_account_id = contract_account();
+    testing_env_with_promise_results(context.clone(), PromiseResult::Failed);
+
+    contract.on_stake_pool_deposit_and_stake(validator1.clone(), ntoy(10), None);
+
+    let val1_info = get_validator(&contract, validator1.clone());
+    assert_eq!(val1_info.failed_epoch_actions, 1);
+    assert_eq!(val1_info.weight, 10);
+
+    contract.on_stake_pool_unstake(validator1.clone(), ntoy(10), None);
+
+    let val1_info = get_validator(&contract, validator1.clone());
+    assert_eq!(val1_info.failed_epoch_actions, 2);
+    assert!(val1_info.paused());
+    assert_eq!(contract.total_validator_weight, 10);
+    assert_eq!(contract.get_validators_to_drain(), vec![validator1.clone()]);
+
+    // a success in between resets the count
+    contract.on_stake_pool_deposit_and_stake(validator2.clone(), ntoy(10), None);
+    testing_env_with_promise_results(context.clone(), PromiseResult::Successful(Vec::default()));
+    contract.on_stake_pool_deposit_and_stake(validator2.clone(), ntoy(10), None);
+    testing_env_with_promise_results(context.clone(), PromiseResult::Failed);
+    contract.on_stake_pool_deposit_and_stake(validator2.clone(), ntoy(10), None);
+
+    let val2_info = get_validator(&contract, validator2.clone());
+    assert_eq!(val2_info.failed_epoch_actions, 1);
+    assert!(!val2_info.paused());
+
+    // bringing the validator back clears its record and the drain queue
+    context.predecessor_account_id = owner_account();
+    testing_env!(context);
+    contract.update_validator(validator1.clone(), 10);
+
+    let val1_info = get_validator(&contract, validator1);
+    assert_eq!(val1_info.failed_epoch_actions, 0);
+    assert_eq!(contract.total_validator_weight, 20);
+    assert!(contract.get_validators_to_drain().is_empty());
+}
+
+#[test]
+fn test_validator_auto_paused_after_zero_reward_epochs() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.add_validator(validator1.clone(), 10);
+
+    context.predecessor_account_id = contract_account();
+    context.epoch_height = 10;
+    testing_env!(context.clone());
+
+    let mut val1_info = get_validator(&contract, validator1.clone());
+    val1_info.staked = ntoy(100);
+    update_validator(&mut contract, validator1.clone(), &val1_info);
+
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    // the auto pause is off by default
+    for _ in 0..3 {
+        contract.on_get_sp_staked_balance_for_rewards(validator1.clone(), None, U128(ntoy(100)));
+    }
+
+    let val1_info = get_validator(&contract, validator1.clone());
+    assert_eq!(val1_info.zero_reward_epochs, 3);
+    assert!(!val1_info.paused());
+
+    context.predecessor_account_id = owner_account();
+    testing_env!(context.clone());
+    contract.set_validator_delinquency_threshold(4);
+
+    context.predecessor_account_id = contract_account();
+    testing_env!(context);
+    contract.on_get_sp_staked_balance_for_rewards(validator1.clone(), None, U128(ntoy(100)));
+
+    let val1_info = get_validator(&contract, validator1.clone());
+    assert!(val1_info.paused());
+    assert_eq!(contract.total_validator_weight, 0);
+    assert_eq!(contract.get_validators_to_drain(), vec![validator1]);
+}
+
+#[test]
+#[should_panic]
+fn test_set_validator_delinquency_threshold_unauthorized() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.set_validator_delinquency_threshold(3);
+}
+
+#[test]
+fn test_run_epoch_carries_on_from_cursor() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    context.account_balance = ntoy(1000);
+    testing_env!(context.clone());
+
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+    let validator2 = AccountId::from_str("stake_public_key_2").unwrap();
+    let validator3 = AccountId::from_str("stake_public_key_3").unwrap();
+
+    contract.add_validator(validator1.clone(), 10);
+    contract.add_validator(validator2.clone(), 10);
+    contract.add_validator(validator3.clone(), 10);
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 0;
+    context.epoch_height = 20;
+    testing_env!(context.clone());
+
+    for validator in [validator1.clone(), validator2.clone(), validator3.clone()] {
+        let mut validator_info = get_validator(&contract, validator.clone());
+        validator_info.staked = ntoy(100);
+        update_validator(&mut contract, validator, &validator_info);
+    }
+
+    let mut val2_info = get_validator(&contract, validator2.clone());
+    val2_info.unstaked_amount = ntoy(5);
+    val2_info.unstake_start_epoch = 1;
+    update_validator(&mut contract, validator2.clone(), &val2_info);
+
+    contract.total_staked = ntoy(330);
+    contract.total_stake_shares = ntoy(330);
+    contract.user_amount_to_stake_in_epoch = ntoy(30);
+
+    let summary = contract.run_epoch(2);
+    assert_eq!(summary.epoch_height, U64(20));
+    assert_eq!(
+        summary.actions,
+        vec![
+            EpochActionResponse {
+                action: EpochAction::Autocompounding,
+                validator_id: validator1.clone(),
+                amount: None,
+            },
+            EpochActionResponse {
+                action: EpochAction::Autocompounding,
+                validator_id: validator2.clone(),
+                amount: None,
+            },
+        ]
+    );
+    assert_eq!(summary.validator_steps_left, U64(4));
+    assert!(!summary.done);
+
+    // two actions left for staking, so only two of the three under-staked validators get stake
+    let summary = contract.run_epoch(4);
+    assert_eq!(
+        summary.actions,
+        vec![
+            EpochActionResponse {
+                action: EpochAction::Autocompounding,
+                validator_id: validator3,
+                amount: None,
+            },
+            EpochActionResponse {
+                action: EpochAction::Withdraw,
+                validator_id: validator2.clone(),
+                amount: Some(U128(ntoy(5))),
+            },
+            EpochActionResponse {
+                action: EpochAction::Stake,
+                validator_id: validator1,
+                amount: Some(U128(ntoy(10))),
+            },
+            EpochActionResponse {
+                action: EpochAction::Stake,
+                validator_id: validator2.clone(),
+                amount: Some(U128(ntoy(10))),
+            },
+        ]
+    );
+    assert_eq!(summary.validator_steps_left, U64(0));
+    assert!(!summary.done);
+    assert_eq!(contract.reconciled_epoch_stake_amount, ntoy(10));
+    assert_eq!(get_validator(&contract, validator2).unstaked_amount, 0);
+
+    // the cursor starts over in the next epoch
+    context.epoch_height = 21;
+    testing_env!(context);
+
+    let summary = contract.run_epoch(1);
+    assert_eq!(summary.epoch_height, U64(21));
+    assert_eq!(summary.actions.len(), 1);
+    assert_eq!(summary.actions[0].action, EpochAction::Autocompounding);
+    assert_eq!(summary.validator_steps_left, U64(5));
+}
+
+#[test]
+fn test_run_epoch_cursor_survives_validator_removal() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+    let validator2 = AccountId::from_str("stake_public_key_2").unwrap();
+    let validator3 = AccountId::from_str("stake_public_key_3").unwrap();
+
+    contract.add_validator(validator1.clone(), 10);
+    contract.add_validator(validator2.clone(), 10);
+    contract.add_validator(validator3.clone(), 10);
+
+    for validator in [validator2.clone(), validator3.clone()] {
+        let mut validator_info = get_validator(&contract, validator.clone());
+        validator_info.staked = ntoy(100);
+        update_validator(&mut contract, validator, &validator_info);
+    }
+    contract.total_staked = ntoy(200);
+    contract.total_stake_shares = ntoy(200);
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 0;
+    context.epoch_height = 20;
+    testing_env!(context.clone());
+
+    let summary = contract.run_epoch(1);
+    assert_eq!(summary.actions.len(), 1);
+    assert_eq!(summary.actions[0].validator_id, validator2);
+
+    // removing the first validator moves the last one into its place in the validator map
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.pause_validator(validator1.clone());
+    contract.remove_validator(validator1);
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 0;
+    testing_env!(context);
+
+    let summary = contract.run_epoch(1);
+    assert_eq!(
+        summary.actions,
+        vec![EpochActionResponse {
+            action: EpochAction::Autocompounding,
+            validator_id: validator3,
+            amount: None,
+        }]
+    );
+    assert_eq!(summary.validator_steps_left, U64(0));
+}
+
+#[test]
+fn test_run_epoch_done_when_nothing_to_schedule() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+    contract.add_validator(validator1, 10);
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 0;
+    testing_env!(context);
+
+    let summary = contract.run_epoch(5);
+    assert!(summary.actions.is_empty());
+    assert_eq!(summary.validator_steps_left, U64(0));
+    assert!(summary.done);
+}
+
+#[test]
+fn test_keeper_rewarded_for_useful_epoch_actions() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let keeper = AccountId::from_str("keeper").unwrap();
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+    let validator2 = AccountId::from_str("stake_public_key_2").unwrap();
+    let validator3 = AccountId::from_str("stake_public_key_3").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    context.epoch_height = 20;
+    testing_env!(context.clone());
+
+    contract.set_keeper_incentives(U128(ntoy(1)), U128(ntoy(2)), Fraction::new(0, 1));
+    for validator in [validator1.clone(), validator2.clone(), validator3.clone()] {
+        contract.add_validator(validator.clone(), 10);
+
+        let mut validator_info = get_validator(&contract, validator.clone());
+        validator_info.unstaked_amount = ntoy(5);
+        validator_info.unstake_start_epoch = 1;
+        update_validator(&mut contract, validator, &validator_info);
+    }
+
+    context.attached_deposit = ntoy(10);
+    testing_env!(context.clone());
+    contract.fund_keeper_budget();
+
+    assert_eq!(contract.keeper_budget_shares, ntoy(10));
+    assert_eq!(contract.ft_balance_of(contract_account()), U128(ntoy(10)));
+    assert_eq!(contract.total_staked, ntoy(10));
+    assert_eq!(contract.user_amount_to_stake_in_epoch, ntoy(10));
+
+    update_account(&mut contract, keeper.clone(), &Account::default());
+
+    context.predecessor_account_id = keeper.clone();
+    context.attached_deposit = 0;
+    testing_env!(context.clone());
+
+    contract.withdraw_epoch(validator1.clone());
+    contract.withdraw_epoch(validator2.clone());
+    contract.withdraw_epoch(validator3.clone());
+
+    // nothing is paid before the withdrawals succeed
+    assert_eq!(get_account(&contract, keeper.clone()).stake_shares, 0);
+    assert_eq!(contract.keeper_budget_shares, ntoy(10));
+
+    context.predecessor_account_id = contract_account();
+    testing_env_with_promise_results(context.clone(), PromiseResult::Failed);
+    contract.on_stake_pool_withdraw_all(validator1.clone(), ntoy(5), Some(keeper.clone()));
+
+    assert_eq!(get_account(&contract, keeper.clone()).stake_shares, 0);
+
+    testing_env_with_promise_results(context.clone(), PromiseResult::Successful(Vec::default()));
+    contract.on_stake_pool_withdraw_all(validator2, ntoy(5), Some(keeper.clone()));
+    contract.on_stake_pool_withdraw_all(validator3.clone(), ntoy(5), Some(keeper.clone()));
+    // over the per epoch cap
+    contract.on_stake_pool_withdraw_all(validator1, ntoy(5), Some(keeper.clone()));
+
+    assert_eq!(get_account(&contract, keeper.clone()).stake_shares, ntoy(2));
+    assert_eq!(contract.keeper_budget_shares, ntoy(8));
+    assert_eq!(
+        contract.get_keeper_incentives().rewards_paid_in_epoch,
+        U128(ntoy(2))
+    );
+
+    // the cap starts over in the next epoch
+    context.epoch_height = 21;
+    testing_env_with_promise_results(context, PromiseResult::Successful(Vec::default()));
+    contract.on_stake_pool_withdraw_all(validator3, ntoy(5), Some(keeper.clone()));
+
+    assert_eq!(get_account(&contract, keeper).stake_shares, ntoy(3));
+    assert_eq!(contract.keeper_budget_shares, ntoy(7));
+    assert_eq!(contract.ft_balance_of(contract_account()), U128(ntoy(7)));
+}
+
+#[test]
+fn test_keeper_budget_from_rewards_fee_share() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let keeper = AccountId::from_str("keeper").unwrap();
+    let validator1 = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.add_validator(validator1.clone(), 10);
+    contract.set_keeper_incentives(U128(ntoy(1)), U128(ntoy(10)), Fraction::new(1, 2));
+
+    update_account(&mut contract, keeper.clone(), &Account::default());
+
+    context.predecessor_account_id = contract_account();
+    context.epoch_height = 100;
+    testing_env!(context.clone());
+
+    let mut val1_info = get_validator(&contract, validator1.clone());
+    val1_info.staked = ntoy(100);
+    val1_info.last_redeemed_rewards_epoch = 99;
+    update_validator(&mut contract, validator1.clone(), &val1_info);
+
+    contract.rewards_fee = Fraction::new(10, 100);
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    contract.on_get_sp_staked_balance_for_rewards(
+        validator1.clone(),
+        Some(keeper.clone()),
+        U128(ntoy(150)),
+    );
+
+    // the fee shares are split between the treasury and the keeper budget
+    assert_eq!(contract.total_stake_shares, 103333333333333333333333333);
+    let treasury_shares = get_account(&contract, treasury_account()).stake_shares;
+    let keeper_shares = get_account(&contract, keeper.clone()).stake_shares;
+    assert_eq!(treasury_shares, 1666666666666666666666667);
+    assert_eq!(
+        keeper_shares + contract.keeper_budget_shares,
+        1666666666666666666666666
+    );
+    assert!(abs_diff_eq(
+        contract.get_account(keeper.clone()).staked_balance.0,
+        ntoy(1),
+        10
+    ));
+
+    // a second autocompounding in the same epoch is not rewarded
+    contract.on_get_sp_staked_balance_for_rewards(
+        validator1,
+        Some(keeper.clone()),
+        U128(ntoy(151)),
+    );
+    assert_eq!(get_account(&contract, keeper).stake_shares, keeper_shares);
+}
+
+#[test]
+#[should_panic(expected = "Keeper share of the rewards fee should be a fraction between 0 and 1")]
+fn test_set_keeper_incentives_fail_fee_share_above_one() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.set_keeper_incentives(U128(ntoy(1)), U128(ntoy(10)), Fraction::new(3, 2));
+}
+
+#[test]
+fn test_epoch_stake_splits_over_under_staked_validators() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.epoch_height = 100;
+    context.attached_deposit = 1;
+    context.account_balance = ntoy(1000);
+    context.predecessor_account_id = owner_account();
+    testing_env!(context);
+
+    let validators: Vec<AccountId> = (1..=6)
+        .map(|i| AccountId::from_str(&format!("stake_public_key_{}", i)).unwrap())
+        .collect();
+    let staked = [
+        ntoy(100),
+        ntoy(150),
+        ntoy(300),
+        ntoy(170),
+        ntoy(190),
+        ntoy(180),
+    ];
+
+    for (validator, staked) in validators.iter().zip(staked) {
+        contract.add_validator(validator.clone(), 10);
+
+        let mut validator_info = get_validator(&contract, validator.clone());
+        validator_info.staked = staked;
+        update_validator(&mut contract, validator.clone(), &validator_info);
+    }
+
+    // every validator should have 1200 / 6 = 200
+    contract.last_reconcilation_epoch = 100;
+    contract.total_staked = ntoy(1200);
+    contract.reconciled_epoch_stake_amount = ntoy(200);
+
+    // 100 + 50 + 30 + 20 over the four most under-staked validators
+    assert!(contract.staking_epoch());
+    assert_eq!(contract.reconciled_epoch_stake_amount, 0);
+
+    // the stake only lands in the callbacks, so the same four are picked again and
+    // the validator that is 10 short has to wait for the next call
+    contract.reconciled_epoch_stake_amount = ntoy(230);
+    assert!(contract.staking_epoch());
+    assert_eq!(contract.reconciled_epoch_stake_amount, ntoy(30));
+}
+
+fn unstake_strategy_setup() -> (VMContext, NearxPool, Vec<AccountId>) {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.epoch_height = 10;
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    let validators: Vec<AccountId> = (1..=3)
+        .map(|i| AccountId::from_str(&format!("stake_public_key_{}", i)).unwrap())
+        .collect();
+    // expected stake is 200 each, the second validator only lets 150 be unstaked
+    let staked = [
+        (ntoy(100), ntoy(100)),
+        (ntoy(300), ntoy(150)),
+        (ntoy(200), ntoy(200)),
+    ];
+
+    for (validator, (staked, max_unstakable_limit)) in validators.iter().zip(staked) {
+        contract.add_validator(validator.clone(), 10);
+
+        let mut validator_info = get_validator(&contract, validator.clone());
+        validator_info.staked = staked;
+        validator_info.max_unstakable_limit = max_unstakable_limit;
+        update_validator(&mut contract, validator.clone(), &validator_info);
+    }
+
+    contract.total_staked = ntoy(600);
+    contract.reconciled_epoch_unstake_amount = ntoy(90);
+
+    (context, contract, validators)
+}
+
+#[test]
+fn test_get_validator_to_unstake_largest_first() {
+    let (_context, contract, validators) = unstake_strategy_setup();
+
+    assert_eq!(
+        contract.get_unstake_strategy(),
+        UnstakeStrategy::LargestFirst
+    );
+
+    let (validator, amount) = contract.get_validator_to_unstake();
+    assert_eq!(validator.unwrap().account_id, validators[2]);
+    assert_eq!(amount, ntoy(200));
+}
+
+#[test]
+fn test_get_validator_to_unstake_weight_deviation_first() {
+    let (_context, mut contract, validators) = unstake_strategy_setup();
+
+    contract.set_unstake_strategy(UnstakeStrategy::WeightDeviationFirst);
+
+    let (validator, amount) = contract.get_validator_to_unstake();
+    assert_eq!(validator.unwrap().account_id, validators[1]);
+    assert_eq!(amount, ntoy(150));
+}
+
+#[test]
+fn test_get_validator_to_unstake_pro_rata() {
+    let (_context, mut contract, validators) = unstake_strategy_setup();
+
+    contract.set_unstake_strategy(UnstakeStrategy::ProRata);
+
+    // 90 split as 40, 30 and 20 over the 200, 150 and 100 unstakable
+    for (validator_index, expected_amount) in [(2, ntoy(40)), (1, ntoy(30)), (0, ntoy(20))] {
+        let (validator, amount) = contract.get_validator_to_unstake();
+        let mut validator = validator.unwrap();
+        assert_eq!(validator.account_id, validators[validator_index]);
+        assert_eq!(amount, expected_amount);
+
+        // the validator stays in its unbonding period after the unstake
+        validator.unstake_start_epoch = 10;
+        update_validator(&mut contract, validator.account_id.clone(), &validator);
+        contract.reconciled_epoch_unstake_amount -= amount;
+    }
+
+    assert_eq!(contract.reconciled_epoch_unstake_amount, 0);
+    assert!(contract.get_validator_to_unstake().0.is_none());
+}
+
+#[test]
+#[should_panic]
+fn test_set_unstake_strategy_unauthorized() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.set_unstake_strategy(UnstakeStrategy::ProRata);
+}
+
+#[test]
+fn test_rebalance_epoch() {
+    let (mut context, mut contract, validators) = unstake_strategy_setup();
+
+    context.predecessor_account_id = operator_account();
+    testing_env!(context.clone());
+
+    // the second validator is 100 over its expected stake of 200
+    assert!(contract.rebalance_epoch());
+
+    let val2_info = get_validator(&contract, validators[1].clone());
+    assert_eq!(val2_info.staked, ntoy(200));
+    assert_eq!(val2_info.unstake_start_epoch, 10);
+
+    context.predecessor_account_id = contract_account();
+    testing_env_with_promise_results(context.clone(), PromiseResult::Successful(Vec::default()));
+
+    contract.on_stake_pool_rebalance_unstake(validators[1].clone(), ntoy(100));
+
+    let val2_info = get_validator(&contract, validators[1].clone());
+    assert_eq!(val2_info.unstaked_amount, ntoy(100));
+    assert_eq!(val2_info.max_unstakable_limit, ntoy(50));
+    assert_eq!(
+        contract.get_rebalance_unstaked_amount(validators[1].clone()),
+        U128(ntoy(100))
+    );
+
+    // nothing is over target anymore
+    context.predecessor_account_id = operator_account();
+    testing_env!(context.clone());
+    assert!(!contract.rebalance_epoch());
+
+    // the withdrawn amount gets staked again
+    context.predecessor_account_id = contract_account();
+    testing_env_with_promise_results(context.clone(), PromiseResult::Successful(Vec::default()));
+
+    contract.on_stake_pool_withdraw_all(validators[1].clone(), ntoy(100), None);
+
+    assert_eq!(contract.user_amount_to_stake_in_epoch, ntoy(100));
+    assert_eq!(
+        contract.get_rebalance_unstaked_amount(validators[1].clone()),
+        U128(0)
+    );
+}
+
+#[test]
+fn test_on_stake_pool_rebalance_unstake_fail() {
+    let (mut context, mut contract, validators) = unstake_strategy_setup();
+
+    context.predecessor_account_id = operator_account();
+    testing_env!(context.clone());
+
+    assert!(contract.rebalance_epoch());
+
+    context.predecessor_account_id = contract_account();
+    testing_env_with_promise_results(context.clone(), PromiseResult::Failed);
+
+    contract.on_stake_pool_rebalance_unstake(validators[1].clone(), ntoy(100));
+
+    let val2_info = get_validator(&contract, validators[1].clone());
+    assert_eq!(val2_info.staked, ntoy(300));
+    assert_eq!(val2_info.unstaked_amount, 0);
+    assert_eq!(val2_info.unstake_start_epoch, 0);
+    assert_eq!(
+        contract.get_rebalance_unstaked_amount(validators[1].clone()),
+        U128(0)
+    );
+}
+
+#[test]
+fn test_rebalance_epoch_skips_unbonding_validators() {
+    let (mut context, mut contract, validators) = unstake_strategy_setup();
+
+    let mut val2_info = get_validator(&contract, validators[1].clone());
+    val2_info.unstake_start_epoch = 9;
+    update_validator(&mut contract, validators[1].clone(), &val2_info);
+
+    context.predecessor_account_id = operator_account();
+    testing_env!(context);
+
+    assert!(!contract.rebalance_epoch());
+}
+
+#[test]
+#[should_panic]
+fn test_rebalance_epoch_paused() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = operator_account();
+    testing_env!(context);
+
+    contract.operations_control.rebalance_epoch_paused = true;
+
+    contract.rebalance_epoch();
+}
+
+#[test]
+#[should_panic]
+fn test_rebalance_epoch_unauthorized() {
+    let (mut context, mut contract, _validators) = unstake_strategy_setup();
+
+    context.predecessor_account_id = AccountId::from_str("random_account").unwrap();
+    testing_env!(context);
+
+    contract.rebalance_epoch();
+}
+
+#[test]
+fn test_update_ft_metadata() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let mut metadata = contract.ft_metadata();
+    assert_eq!(metadata.symbol, "NearX");
+    assert_eq!(metadata.decimals, 24);
+
+    metadata.icon = Some("data:image/svg+xml,%3Csvg%3E%3C/svg%3E".to_string());
+    metadata.reference = Some("https://example.com/nearx.json".to_string());
+    metadata.reference_hash = Some(vec![1; 32].into());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.update_ft_metadata(metadata);
+
+    let metadata = contract.ft_metadata();
+    assert_eq!(
+        metadata.icon,
+        Some("data:image/svg+xml,%3Csvg%3E%3C/svg%3E".to_string())
+    );
+    assert_eq!(
+        metadata.reference,
+        Some("https://example.com/nearx.json".to_string())
+    );
+    assert_eq!(metadata.reference_hash, Some(vec![1; 32].into()));
+}
+
+#[test]
+#[should_panic(expected = "Icon should be a data URI of at most 10KB")]
+fn test_update_ft_metadata_icon_too_large() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let mut metadata = contract.ft_metadata();
+    metadata.icon = Some(format!("data:image/svg+xml,{}", "a".repeat(10 * 1024)));
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.update_ft_metadata(metadata);
+}
+
+#[test]
+#[should_panic(expected = "Reference hash should be 32 bytes")]
+fn test_update_ft_metadata_invalid_reference_hash() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let mut metadata = contract.ft_metadata();
+    metadata.reference_hash = Some(vec![1; 16].into());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.update_ft_metadata(metadata);
+}
+
+#[test]
+#[should_panic(expected = "NearX decimals cannot be changed")]
+fn test_update_ft_metadata_decimals() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let mut metadata = contract.ft_metadata();
+    metadata.decimals = 18;
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.update_ft_metadata(metadata);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_update_ft_metadata_unauthorized() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let metadata = contract.ft_metadata();
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.update_ft_metadata(metadata);
+}
+
+fn timelocked_contract_setup(delay: u64) -> (VMContext, NearxPool) {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    context.epoch_height = 10;
+    testing_env!(context.clone());
+
+    contract.set_proposal_delay(U64(delay));
+
+    (context, contract)
+}
+
+#[test]
+fn test_proposal_lifecycle() {
+    let (mut context, mut contract) = timelocked_contract_setup(2);
+
+    let proposal_id = contract.propose(ProposalAction::SetMinDeposit {
+        min_deposit: U128(ntoy(50)),
+    });
+    assert_eq!(proposal_id, U64(1));
+
+    let proposals = contract.get_proposals();
+    assert_eq!(proposals.len(), 1);
+    assert_eq!(proposals[0].proposal_id, U64(1));
+    assert_eq!(proposals[0].proposer, owner_account());
+    assert_eq!(proposals[0].created_epoch, U64(10));
+    assert_eq!(proposals[0].executable_epoch, U64(12));
+    assert!(!proposals[0].can_execute);
+
+    context.epoch_height = 12;
+    testing_env!(context.clone());
+
+    assert!(contract.get_proposals()[0].can_execute);
+    contract.execute_proposal(proposal_id);
+
+    assert_eq!(contract.min_deposit_amount, ntoy(50));
+    assert!(contract.get_proposals().is_empty());
+    assert!(get_logs()
+        .last()
+        .unwrap()
+        .contains(r#""event":"proposal_executed""#));
+}
+
+#[test]
+#[should_panic(expected = "Owner actions are timelocked, they need to go through a proposal")]
+fn test_owner_action_timelocked() {
+    let (_context, mut contract) = timelocked_contract_setup(2);
+
+    contract.set_min_deposit(U128(ntoy(50)));
+}
+
+#[test]
+#[should_panic(expected = "Proposal is still in its delay period")]
+fn test_execute_proposal_in_delay_period() {
+    let (mut context, mut contract) = timelocked_contract_setup(2);
+
+    let proposal_id = contract.propose(ProposalAction::SetUnstakeStrategy {
+        strategy: UnstakeStrategy::WeightDeviationFirst,
+    });
+
+    context.epoch_height = 11;
+    testing_env!(context);
+
+    contract.execute_proposal(proposal_id);
+}
+
+#[test]
+fn test_proposal_delay_change_goes_through_proposal() {
+    let (mut context, mut contract) = timelocked_contract_setup(2);
+
+    let proposal_id = contract.propose(ProposalAction::SetProposalDelay { epochs: 0 });
+
+    context.epoch_height = 12;
+    testing_env!(context);
+
+    contract.execute_proposal(proposal_id);
+    assert_eq!(contract.get_proposal_delay(), U64(0));
+
+    contract.set_min_deposit(U128(ntoy(50)));
+    assert_eq!(contract.min_deposit_amount, ntoy(50));
+}
+
+#[test]
+fn test_validator_manager_actions_through_proposal() {
+    let (mut context, mut contract) = timelocked_contract_setup(2);
+
+    let validator = AccountId::from_str("stake_public_key_1").unwrap();
+
+    let add_id = contract.propose(ProposalAction::AddValidator {
+        validator: validator.clone(),
+        weight: 10,
+    });
+    let update_id = contract.propose(ProposalAction::UpdateValidator {
+        validator: validator.clone(),
+        weight: 20,
+    });
+    let remove_id = contract.propose(ProposalAction::RemoveValidator {
+        validator: validator.clone(),
+    });
+
+    context.epoch_height = 12;
+    testing_env!(context);
+
+    contract.execute_proposal(add_id);
+    contract.execute_proposal(update_id);
+    assert_eq!(get_validator(&contract, validator.clone()).weight, 20);
+    assert_eq!(contract.total_validator_weight, 20);
+
+    // pausing stays immediate
+    contract.pause_validator(validator.clone());
+    contract.execute_proposal(remove_id);
+    assert!(contract.validator_info_map.get(&validator).is_none());
+    assert_eq!(contract.total_validator_weight, 0);
+}
+
+#[test]
+#[should_panic(expected = "Owner actions are timelocked, they need to go through a proposal")]
+fn test_update_validator_timelocked() {
+    let (mut context, mut contract) = timelocked_contract_setup(0);
+
+    let validator = AccountId::from_str("stake_public_key_1").unwrap();
+    contract.add_validator(validator.clone(), 10);
+    contract.set_proposal_delay(U64(2));
+
+    context.predecessor_account_id = operator_account();
+    testing_env!(context);
+
+    contract.update_validator(validator, 20);
+}
+
+#[test]
+fn test_update_ft_metadata_through_proposal() {
+    let (mut context, mut contract) = timelocked_contract_setup(2);
+
+    let mut metadata = contract.ft_metadata();
+    metadata.reference = Some("https://example.com/nearx.json".to_string());
+    let proposal_id = contract.propose(ProposalAction::UpdateFtMetadata {
+        metadata: ProposedFtMetadata(metadata),
+    });
+
+    context.epoch_height = 12;
+    testing_env!(context);
+
+    contract.execute_proposal(proposal_id);
+    assert_eq!(
+        contract.ft_metadata().reference,
+        Some("https://example.com/nearx.json".to_string())
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Upgrade proposals are executed by calling upgrade with the proposed code"
+)]
+fn test_execute_upgrade_proposal() {
+    let (mut context, mut contract) = timelocked_contract_setup(2);
+
+    let proposal_id = contract.propose(ProposalAction::Upgrade {
+        code_hash: [1; 32].into(),
+    });
+
+    context.epoch_height = 12;
+    testing_env!(context);
+
+    contract.execute_proposal(proposal_id);
+}
+
+#[test]
+#[should_panic(expected = "Proposal not found")]
+fn test_cancel_proposal() {
+    let (mut context, mut contract) = timelocked_contract_setup(2);
+
+    let proposal_id =
+        contract.propose(ProposalAction::SetValidatorDelinquencyThreshold { threshold: 3 });
+    contract.cancel_proposal(proposal_id);
+    assert!(contract.get_proposals().is_empty());
+
+    context.epoch_height = 12;
+    testing_env!(context);
+
+    contract.execute_proposal(proposal_id);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_propose_unauthorized() {
+    let (mut context, mut contract) = timelocked_contract_setup(2);
+
+    context.predecessor_account_id = operator_account();
+    testing_env!(context);
+
+    contract.propose(ProposalAction::SetMinDeposit {
+        min_deposit: U128(ntoy(50)),
+    });
+}
+
+fn stake_paused_request(paused: bool) -> OperationsControlUpdateRequest {
+    OperationsControlUpdateRequest {
+        stake_paused: Some(paused),
+        direct_stake_paused: None,
+        unstake_paused: None,
+        withdraw_paused: None,
+        staking_epoch_paused: None,
+        unstaking_epoch_paused: None,
+        withdraw_epoch_paused: None,
+        autocompounding_epoch_paused: None,
+        sync_validator_balance_paused: None,
+        ft_transfer_paused: None,
+        ft_transfer_call_paused: None,
+        instant_unstake_paused: None,
+        rebalance_epoch_paused: None,
+    }
+}
+
+fn council_member(index: u8) -> AccountId {
+    AccountId::from_str(&format!("council_{}", index)).unwrap()
+}
+
+fn council_contract_setup() -> (VMContext, NearxPool) {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    context.epoch_height = 10;
+    testing_env!(context.clone());
+
+    contract.set_council(
+        vec![council_member(1), council_member(2), council_member(3)],
+        2,
+        U64(2),
+    );
+
+    (context, contract)
+}
+
+fn approve_as(
+    context: &mut VMContext,
+    contract: &mut NearxPool,
+    approver: AccountId,
+    action: CouncilAction,
+) {
+    context.predecessor_account_id = approver;
+    testing_env!(context.clone());
+    contract.approve_council_action(action);
+}
+
+#[test]
+#[should_panic(expected = "Action does not have enough council approvals")]
+fn test_council_set_owner_without_approvals() {
+    let (context, mut contract) = council_contract_setup();
+    let new_owner = AccountId::from_str("new_owner").unwrap();
+
+    approve_as(
+        &mut context.clone(),
+        &mut contract,
+        council_member(1),
+        CouncilAction::SetOwner {
+            new_owner: new_owner.clone(),
+        },
+    );
+
+    testing_env!(context);
+    contract.set_owner(new_owner);
+}
+
+#[test]
+fn test_council_set_owner_with_approvals() {
+    let (mut context, mut contract) = council_contract_setup();
+    let new_owner = AccountId::from_str("new_owner").unwrap();
+    let action = CouncilAction::SetOwner {
+        new_owner: new_owner.clone(),
+    };
+
+    approve_as(
+        &mut context,
+        &mut contract,
+        council_member(1),
+        action.clone(),
+    );
+    approve_as(
+        &mut context,
+        &mut contract,
+        council_member(2),
+        action.clone(),
+    );
+    // approving twice does not count twice
+    approve_as(
+        &mut context,
+        &mut contract,
+        council_member(2),
+        action.clone(),
+    );
+    assert_eq!(
+        contract
+            .get_council_approvals(action.clone())
+            .approvals
+            .len(),
+        2
+    );
+
+    context.predecessor_account_id = owner_account();
+    testing_env!(context);
+    contract.set_owner(new_owner.clone());
+
+    assert_eq!(contract.temp_owner, Some(new_owner));
+    assert!(contract.get_council_approvals(action).approvals.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Action does not have enough council approvals")]
+fn test_council_approval_expired() {
+    let (mut context, mut contract) = council_contract_setup();
+    let new_owner = AccountId::from_str("new_owner").unwrap();
+    let action = CouncilAction::SetOwner {
+        new_owner: new_owner.clone(),
+    };
+
+    approve_as(
+        &mut context,
+        &mut contract,
+        council_member(1),
+        action.clone(),
+    );
+
+    context.epoch_height = 13;
+    approve_as(&mut context, &mut contract, council_member(2), action);
+
+    context.predecessor_account_id = owner_account();
+    testing_env!(context);
+    contract.set_owner(new_owner);
+}
+
+#[test]
+fn test_council_unpause() {
+    let (mut context, mut contract) = council_contract_setup();
+
+    // pausing does not need the council
+    contract.update_operations_control(stake_paused_request(true));
+    assert!(contract.operations_control.stake_paused);
+
+    let unpause_request = stake_paused_request(false);
+    let action = CouncilAction::Unpause {
+        update_operations_control_request: unpause_request.clone(),
+    };
+    approve_as(
+        &mut context,
+        &mut contract,
+        council_member(1),
+        action.clone(),
+    );
+    approve_as(&mut context, &mut contract, council_member(3), action);
+
+    context.predecessor_account_id = owner_account();
+    testing_env!(context);
+    contract.update_operations_control(unpause_request);
+
+    assert!(!contract.operations_control.stake_paused);
+}
+
+#[test]
+#[should_panic(expected = "Only council members can do this")]
+fn test_council_approve_not_member() {
+    let (mut context, mut contract) = council_contract_setup();
+
+    approve_as(
+        &mut context,
+        &mut contract,
+        operator_account(),
+        CouncilAction::CommitRewardFee {
+            numerator: 5,
+            denominator: 100,
+        },
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Council members should be distinct with a threshold between 1 and their number, or empty with a threshold of 0"
+)]
+fn test_set_council_threshold_too_high() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.set_council(vec![council_member(1), council_member(2)], 3, U64(2));
+}
+
+#[test]
+fn test_validator_manager_role() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+    let manager = AccountId::from_str("validator_manager").unwrap();
+    let validator = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.grant_role(Role::ValidatorManager, manager.clone());
+    let granted_roles = contract.get_roles().granted_roles;
+    assert_eq!(granted_roles.len(), 1);
+    assert_eq!(granted_roles[0].role, Role::ValidatorManager);
+    assert_eq!(granted_roles[0].account_ids, vec![manager.clone()]);
+
+    context.predecessor_account_id = manager;
+    testing_env!(context);
+
+    contract.add_validator(validator.clone(), 10);
+    assert!(contract.get_validator_info(validator).weight == 10);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_revoked_role() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+    let manager = AccountId::from_str("validator_manager").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.grant_role(Role::ValidatorManager, manager.clone());
+    contract.revoke_role(Role::ValidatorManager, manager.clone());
+    assert!(contract.get_roles().granted_roles.is_empty());
+
+    context.predecessor_account_id = manager;
+    testing_env!(context);
+
+    contract.add_validator(AccountId::from_str("stake_public_key_1").unwrap(), 10);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_pauser_cannot_unpause() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+    let pauser = AccountId::from_str("pauser").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.grant_role(Role::Pauser, pauser.clone());
+
+    context.predecessor_account_id = pauser;
+    testing_env!(context);
+
+    contract.update_operations_control(stake_paused_request(true));
+    assert!(contract.get_operations_control().stake_paused);
+
+    contract.update_operations_control(stake_paused_request(false));
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_grant_role_unauthorized() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.grant_role(Role::Upgrader, operator_account());
+}
+
+fn emergency_pause_setup() -> (VMContext, NearxPool) {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+    let guardian = AccountId::from_str("guardian").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.grant_role(Role::Guardian, guardian.clone());
+
+    context.predecessor_account_id = guardian;
+    testing_env!(context.clone());
+
+    contract.emergency_pause();
+
+    (context, contract)
+}
+
+#[test]
+fn test_emergency_pause() {
+    let (mut context, mut contract) = emergency_pause_setup();
+
+    assert_eq!(
+        contract.get_operations_control(),
+        OperationControls {
+            stake_paused: true,
+            direct_stake_paused: true,
+            unstaked_paused: true,
+            withdraw_paused: true,
+            staking_epoch_paused: true,
+            unstaking_epoch_paused: true,
+            withdraw_epoch_paused: true,
+            autocompounding_epoch_paused: true,
+            sync_validator_balance_paused: true,
+            ft_transfer_paused: true,
+            ft_transfer_call_paused: true,
+            instant_unstake_paused: true,
+            rebalance_epoch_paused: true,
+        }
+    );
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"data":[{"caller":"guardian"}],"event":"emergency_pause","standard":"nearx","version":"1.0.0"}"#
+        ]
+    );
+
+    context.predecessor_account_id = owner_account();
+    testing_env!(context);
+
+    contract.update_operations_control(stake_paused_request(false));
+    assert!(!contract.get_operations_control().stake_paused);
+    assert!(contract.get_operations_control().unstaked_paused);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_emergency_pause_unauthorized() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.emergency_pause();
+}
+
+#[test]
+#[should_panic(
+    expected = "Operations are resumed one group at a time: deposits, withdrawals, epoch actions or transfers"
+)]
+fn test_unpause_more_than_one_group() {
+    let (mut context, mut contract) = emergency_pause_setup();
+
+    context.predecessor_account_id = owner_account();
+    testing_env!(context);
+
+    let mut request = stake_paused_request(false);
+    request.ft_transfer_paused = Some(false);
+    contract.update_operations_control(request);
+}
+
+#[test]
+fn test_unpause_request_for_running_operations_is_not_an_unpause() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+    let pauser = AccountId::from_str("pauser").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.grant_role(Role::Pauser, pauser.clone());
+
+    // the pauser may send flags which already run, even across groups
+    context.predecessor_account_id = pauser;
+    testing_env!(context);
+
+    let mut request = stake_paused_request(false);
+    request.ft_transfer_paused = Some(false);
+    request.unstake_paused = Some(true);
+    contract.update_operations_control(request);
+
+    let operations_control = contract.get_operations_control();
+    assert!(!operations_control.stake_paused);
+    assert!(!operations_control.ft_transfer_paused);
+    assert!(operations_control.unstaked_paused);
+}
+
+#[test]
+fn test_unstake_epoch_cap_queues_unstakes() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+    let user1 = AccountId::from_str("user1").unwrap();
+
+    context.epoch_height = 10;
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.set_unstake_epoch_cap(Some(UnstakeEpochCap::Absolute {
+        amount: U128(ntoy(1000)),
+    }));
+
+    contract.total_staked = ntoy(5000);
+    contract.total_stake_shares = ntoy(5000);
+    contract.last_reconcilation_epoch = 9;
+    contract.user_amount_to_unstake_in_epoch = ntoy(200);
+
+    let mut user1_account = Account::default();
+    user1_account.stake_shares = ntoy(5000);
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    context.predecessor_account_id = user1.clone();
+    testing_env!(context.clone());
+
+    contract.unstake(U128(ntoy(2500)));
+
+    // 800 NEAR fit in the current epoch, the rest is taken over the next two
+    assert_eq!(
+        contract.get_unstake_queue(),
+        vec![UnstakeQueueEntryResponse {
+            epoch: U64(12),
+            amount: U128(ntoy(2500)),
+        }]
+    );
+    assert_eq!(contract.user_amount_to_unstake_in_epoch, ntoy(200));
+    assert_eq!(
+        get_account(&contract, user1).withdrawable_epoch_height(),
+        12 + NUM_EPOCHS_TO_UNLOCK
+    );
+
+    contract.epoch_reconcilation();
+
+    assert_eq!(contract.reconciled_epoch_unstake_amount, ntoy(1000));
+    assert_eq!(
+        contract.get_unstake_queue(),
+        vec![UnstakeQueueEntryResponse {
+            epoch: U64(12),
+            amount: U128(ntoy(1700)),
+        }]
+    );
+
+    context.epoch_height = 11;
+    testing_env!(context.clone());
+    contract.reconciled_epoch_unstake_amount = 0;
+    contract.epoch_reconcilation();
+
+    assert_eq!(contract.reconciled_epoch_unstake_amount, ntoy(1000));
+    assert_eq!(
+        contract.get_unstake_queue(),
+        vec![UnstakeQueueEntryResponse {
+            epoch: U64(12),
+            amount: U128(ntoy(700)),
+        }]
+    );
+
+    // a lower cap does not hold back what is due
+    context.epoch_height = 12;
+    testing_env!(context);
+    contract.reconciled_epoch_unstake_amount = 0;
+    contract.total_staked = ntoy(2500);
+    contract.unstake_epoch_cap = Some(UnstakeEpochCap::ShareOfTotalStaked {
+        share: Fraction::new(1, 10),
+    });
+    contract.epoch_reconcilation();
+
+    assert_eq!(contract.reconciled_epoch_unstake_amount, ntoy(700));
+    assert!(contract.get_unstake_queue().is_empty());
+    assert_eq!(contract.unstake_queue_amount, 0);
+}
+
+#[test]
+fn test_unstake_epoch_cap_share_of_total_staked() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+    let user1 = AccountId::from_str("user1").unwrap();
+
+    context.epoch_height = 10;
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    contract.set_unstake_epoch_cap(Some(UnstakeEpochCap::ShareOfTotalStaked {
+        share: Fraction::new(1, 10),
+    }));
+
+    contract.total_staked = ntoy(20000);
+    contract.total_stake_shares = ntoy(20000);
+    contract.last_reconcilation_epoch = 10;
+
+    let mut user1_account = Account::default();
+    user1_account.stake_shares = ntoy(5000);
+    update_account(&mut contract, user1.clone(), &user1_account);
+
+    context.predecessor_account_id = user1.clone();
+    testing_env!(context);
+
+    contract.unstake(U128(ntoy(3000)));
+
+    // reconciliation already ran this epoch so the queue starts in the next one
+    assert_eq!(
+        contract.get_unstake_queue(),
+        vec![UnstakeQueueEntryResponse {
+            epoch: U64(12),
+            amount: U128(ntoy(3000)),
+        }]
+    );
+    assert_eq!(
+        get_account(&contract, user1).withdrawable_epoch_height(),
+        12 + NUM_EPOCHS_TO_UNLOCK
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Unstake epoch cap should be at least 1000 NEAR or a share of the total staked between 0 and 1"
+)]
+fn test_set_unstake_epoch_cap_too_low() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context);
+
+    contract.set_unstake_epoch_cap(Some(UnstakeEpochCap::Absolute {
+        amount: U128(ntoy(10)),
+    }));
+}
+
+#[test]
+fn test_price_history() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    context.epoch_height = 10;
+    testing_env!(context.clone());
+    contract.epoch_reconcilation();
+
+    context.epoch_height = 20;
+    testing_env!(context.clone());
+    contract.total_staked = ntoy(101);
+    contract.epoch_reconcilation();
+    // only the first call of the epoch takes a snapshot
+    contract.total_staked = ntoy(105);
+    contract.last_reconcilation_epoch = 0;
+    contract.epoch_reconcilation();
+
+    assert_eq!(
+        contract.get_price_history(U64(0), 10),
+        vec![
+            PriceSnapshotResponse {
+                epoch: U64(10),
+                total_staked: U128(ntoy(100)),
+                total_stake_shares: U128(ntoy(100)),
+                price: U128(ntoy(1)),
+            },
+            PriceSnapshotResponse {
+                epoch: U64(20),
+                total_staked: U128(ntoy(101)),
+                total_stake_shares: U128(ntoy(100)),
+                price: U128(ntoy(101) / 100),
+            },
+        ]
+    );
+    assert_eq!(contract.get_price_history(U64(15), 10).len(), 1);
+    assert_eq!(contract.get_price_history(U64(0), 1)[0].epoch, U64(10));
+
+    // 1% over 10 epochs
+    assert_eq!(
+        contract.get_apy(U64(30)),
+        Some(ApyResponse {
+            from_epoch: U64(10),
+            to_epoch: U64(20),
+            apy_basis_points: U64(100 * EPOCHS_PER_YEAR / 10),
+        })
+    );
+    assert_eq!(contract.get_apy(U64(5)), None);
+}
+
+#[test]
+fn test_price_history_ring_buffer() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    for epoch in 1..=PRICE_HISTORY_LENGTH + 1 {
+        context.epoch_height = epoch;
+        testing_env!(context.clone());
+        contract.epoch_reconcilation();
+    }
+
+    let history = contract.get_price_history(U64(0), PRICE_HISTORY_LENGTH + 1);
+    assert_eq!(history.len() as u64, PRICE_HISTORY_LENGTH);
+    assert_eq!(history[0].epoch, U64(2));
+    assert_eq!(history.last().unwrap().epoch, U64(PRICE_HISTORY_LENGTH + 1));
+
+    // the wrapped around history still takes one snapshot per epoch
+    contract.last_reconcilation_epoch = 0;
+    contract.epoch_reconcilation();
+    assert_eq!(
+        contract.get_price_history(U64(0), PRICE_HISTORY_LENGTH + 1),
+        history
+    );
+}
+
+#[test]
+fn test_price_subscribers() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let lending_market = AccountId::from_str("lending_market").unwrap();
+    let money_market = AccountId::from_str("money_market").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.add_price_subscriber(lending_market.clone());
+    contract.add_price_subscriber(money_market.clone());
+    assert_eq!(
+        contract.get_price_subscribers(),
+        vec![lending_market.clone(), money_market.clone()]
+    );
+
+    contract.remove_price_subscriber(lending_market);
+    assert_eq!(contract.get_price_subscribers(), vec![money_market]);
+}
+
+#[test]
+#[should_panic(expected = "Too many price subscribers")]
+fn test_add_price_subscriber_over_max() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    for i in 0..=MAX_PRICE_SUBSCRIBERS {
+        contract.add_price_subscriber(AccountId::from_str(&format!("market_{}", i)).unwrap());
+    }
+}
+
+#[test]
+fn test_price_pushed_to_subscribers_after_rewards() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let validator = AccountId::from_str("stake_public_key_1").unwrap();
+    let lending_market = AccountId::from_str("lending_market").unwrap();
+    let money_market = AccountId::from_str("money_market").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.add_validator(validator.clone(), 10);
+    contract.add_price_subscriber(lending_market.clone());
+    contract.add_price_subscriber(money_market.clone());
+
+    context.predecessor_account_id = contract_account();
+    context.attached_deposit = 0;
+    context.epoch_height = 100;
+    testing_env!(context.clone());
+
+    let mut validator_info = get_validator(&contract, validator.clone());
+    validator_info.staked = ntoy(100);
+    update_validator(&mut contract, validator.clone(), &validator_info);
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    contract.on_get_sp_staked_balance_for_rewards(validator.clone(), None, U128(ntoy(110)));
+
+    assert_eq!(contract.get_price_update_nonce(), U64(1));
+    let receivers: Vec<AccountId> = get_created_receipts()
+        .into_iter()
+        .map(|receipt| receipt.receiver_id)
+        .collect();
+    assert_eq!(receivers, vec![lending_market, money_market]);
+
+    // no rewards, nothing to push
+    context.epoch_height = 101;
+    testing_env!(context.clone());
+    contract.on_get_sp_staked_balance_for_rewards(validator, None, U128(ntoy(110)));
+    assert_eq!(contract.get_price_update_nonce(), U64(1));
+    assert!(get_created_receipts().is_empty());
+}
+
+fn exchange_rate_guard_setup() -> (VMContext, NearxPool, AccountId) {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let validator = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.add_validator(validator.clone(), 10);
+    contract.set_exchange_rate_bounds(Some(ExchangeRateBounds {
+        max_increase_per_epoch: Fraction::new(1, 100),
+        max_decrease_per_epoch: Fraction::new(1, 100),
+    }));
+
+    let mut validator_info = get_validator(&contract, validator.clone());
+    validator_info.staked = ntoy(100);
+    validator_info.max_unstakable_limit = ntoy(100);
+    validator_info.last_redeemed_rewards_epoch = 99;
+    update_validator(&mut contract, validator.clone(), &validator_info);
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    context.predecessor_account_id = contract_account();
+    context.attached_deposit = 0;
+    context.epoch_height = 100;
+    testing_env!(context.clone());
+
+    (context, contract, validator)
+}
+
+#[test]
+fn test_exchange_rate_guard_quarantines_reward_spike() {
+    let (mut context, mut contract, validator) = exchange_rate_guard_setup();
+
+    contract.on_get_sp_staked_balance_for_rewards(validator.clone(), None, U128(ntoy(150)));
+
+    assert_eq!(contract.total_staked, ntoy(100));
+    assert_eq!(
+        get_validator(&contract, validator.clone()).staked,
+        ntoy(100)
+    );
+    assert_eq!(
+        contract.get_quarantined_balances(),
+        vec![QuarantinedBalanceResponse {
+            validator_id: validator.clone(),
+            staked: U128(ntoy(100)),
+            reported: U128(ntoy(150)),
+            epoch: U64(100),
+            reported_unstaked: None,
+        }]
+    );
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.confirm_quarantined_balance(validator.clone());
+
+    assert!(contract.get_quarantined_balances().is_empty());
+    assert_eq!(contract.total_staked, ntoy(150));
+    assert_eq!(contract.accumulated_staked_rewards, ntoy(50));
+    assert_eq!(get_validator(&contract, validator).staked, ntoy(150));
+}
+
+#[test]
+fn test_exchange_rate_guard_books_losses() {
+    let (mut context, mut contract, validator) = exchange_rate_guard_setup();
+
+    // within the bounds, booked right away
+    contract.on_get_sp_staked_balance_for_rewards(validator.clone(), None, U128(ntoy(99)));
+    assert_eq!(contract.total_staked, ntoy(99));
+    assert_eq!(get_validator(&contract, validator.clone()).staked, ntoy(99));
+    assert!(contract.get_nearx_price().0 < ntoy(1));
+
+    context.epoch_height = 101;
+    testing_env!(context.clone());
+    contract.on_get_sp_staked_balance_for_rewards(validator.clone(), None, U128(ntoy(50)));
+    assert_eq!(contract.total_staked, ntoy(99));
+    assert_eq!(contract.get_quarantined_balances().len(), 1);
+
+    // rejected reports are dropped
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.reject_quarantined_balance(validator.clone());
+    assert!(contract.get_quarantined_balances().is_empty());
+
+    context.predecessor_account_id = contract_account();
+    context.attached_deposit = 0;
+    context.epoch_height = 102;
+    testing_env!(context.clone());
+    contract.on_get_sp_staked_balance_for_rewards(validator.clone(), None, U128(ntoy(50)));
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.confirm_quarantined_balance(validator.clone());
+
+    assert_eq!(contract.total_staked, ntoy(50));
+    let validator_info = get_validator(&contract, validator);
+    assert_eq!(validator_info.staked, ntoy(50));
+    assert_eq!(validator_info.max_unstakable_limit, ntoy(50));
+}
+
+#[test]
+fn test_autocompounding_books_losses_without_exchange_rate_bounds() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let validator = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.add_validator(validator.clone(), 10);
+    assert!(contract.get_exchange_rate_bounds().is_none());
+
+    let mut validator_info = get_validator(&contract, validator.clone());
+    validator_info.staked = ntoy(100);
+    validator_info.max_unstakable_limit = ntoy(100);
+    update_validator(&mut contract, validator.clone(), &validator_info);
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    context.predecessor_account_id = contract_account();
+    context.attached_deposit = 0;
+    context.epoch_height = 100;
+    testing_env!(context.clone());
+
+    // the rounding of the staking pool is not a loss
+    contract.on_get_sp_staked_balance_for_rewards(validator.clone(), None, U128(ntoy(100) - 1));
+    assert_eq!(contract.total_staked, ntoy(100));
+    assert_eq!(
+        get_validator(&contract, validator.clone()).staked,
+        ntoy(100)
+    );
+
+    context.epoch_height = 101;
+    testing_env!(context.clone());
+    contract.on_get_sp_staked_balance_for_rewards(validator.clone(), None, U128(ntoy(90)));
+
+    assert!(contract.get_quarantined_balances().is_empty());
+    assert_eq!(contract.total_staked, ntoy(90));
+    let validator_info = get_validator(&contract, validator);
+    assert_eq!(validator_info.staked, ntoy(90));
+    assert_eq!(validator_info.max_unstakable_limit, ntoy(90));
+    assert_eq!(
+        contract.get_insurance_fund().uncovered_losses,
+        U128(ntoy(10))
+    );
+}
+
+#[test]
+fn test_exchange_rate_guard_quarantines_sync_shortfall() {
+    let (mut context, mut contract, validator) = exchange_rate_guard_setup();
+
+    contract.on_stake_pool_get_account(
+        validator.clone(),
+        HumanReadableAccount {
+            account_id: validator.clone(),
+            unstaked_balance: U128(ntoy(10)),
+            staked_balance: U128(ntoy(60)),
+            can_withdraw: true,
+        },
+    );
+
+    assert_eq!(contract.total_staked, ntoy(100));
+    assert_eq!(
+        get_validator(&contract, validator.clone()).staked,
+        ntoy(100)
+    );
+    assert_eq!(
+        contract.get_quarantined_balances(),
+        vec![QuarantinedBalanceResponse {
+            validator_id: validator.clone(),
+            staked: U128(ntoy(100)),
+            reported: U128(ntoy(60)),
+            epoch: U64(100),
+            reported_unstaked: Some(U128(ntoy(10))),
+        }]
+    );
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.confirm_quarantined_balance(validator.clone());
+
+    assert!(contract.get_quarantined_balances().is_empty());
+    assert_eq!(contract.total_staked, ntoy(70));
+    let validator_info = get_validator(&contract, validator);
+    assert_eq!(validator_info.staked, ntoy(60));
+    assert_eq!(validator_info.unstaked_amount, ntoy(10));
+    assert_eq!(validator_info.max_unstakable_limit, ntoy(60));
+}
+
+#[test]
+fn test_validator_manager_handles_quarantined_balance() {
+    let (mut context, mut contract, validator) = exchange_rate_guard_setup();
+    let manager = AccountId::from_str("manager").unwrap();
+
+    contract.on_get_sp_staked_balance_for_rewards(validator.clone(), None, U128(ntoy(150)));
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.grant_role(Role::ValidatorManager, manager.clone());
+
+    context.predecessor_account_id = manager;
+    testing_env!(context);
+    contract.reject_quarantined_balance(validator);
+
+    assert!(contract.get_quarantined_balances().is_empty());
+    assert_eq!(contract.total_staked, ntoy(100));
+}
+
+#[test]
+#[should_panic(expected = "Not enough pre-paid gas")]
+fn test_confirm_quarantined_balance_reserves_price_push_gas() {
+    let (mut context, mut contract, validator) = exchange_rate_guard_setup();
+
+    contract.on_get_sp_staked_balance_for_rewards(validator.clone(), None, U128(ntoy(150)));
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    for subscriber in ["lending_market", "money_market"] {
+        contract.add_price_subscriber(AccountId::from_str(subscriber).unwrap());
+    }
+
+    context.predecessor_account_id = operator_account();
+    context.prepaid_gas = Gas(30 * 1_000_000_000_000);
+    testing_env!(context);
+    contract.confirm_quarantined_balance(validator);
+}
+
+#[test]
+#[should_panic(expected = "Validator stake changed since the balance was quarantined")]
+fn test_confirm_stale_quarantined_balance() {
+    let (mut context, mut contract, validator) = exchange_rate_guard_setup();
+
+    contract.on_get_sp_staked_balance_for_rewards(validator.clone(), None, U128(ntoy(150)));
+
+    let mut validator_info = get_validator(&contract, validator.clone());
+    validator_info.staked = ntoy(110);
+    update_validator(&mut contract, validator.clone(), &validator_info);
+
+    context.predecessor_account_id = operator_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.confirm_quarantined_balance(validator);
+}
+
+#[test]
+fn test_validator_loss_waterfall() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let validator = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.add_validator(validator.clone(), 10);
+    contract.set_insurance_fund_coverage(Fraction::new(1, 2));
+
+    let mut validator_info = get_validator(&contract, validator.clone());
+    validator_info.staked = ntoy(100);
+    validator_info.max_unstakable_limit = ntoy(100);
+    update_validator(&mut contract, validator.clone(), &validator_info);
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+    contract.rewards_buffer = ntoy(2);
+
+    context.predecessor_account_id = AccountId::from_str("user1").unwrap();
+    context.attached_deposit = ntoy(10);
+    testing_env!(context.clone());
+    contract.fund_insurance_fund();
+    assert_eq!(contract.get_insurance_fund().shares, U128(ntoy(10)));
+    assert_eq!(contract.total_staked, ntoy(110));
+
+    // slashed by 6 NEAR
+    context.predecessor_account_id = contract_account();
+    context.attached_deposit = 0;
+    testing_env!(context.clone());
+    contract.on_stake_pool_get_account(
+        validator.clone(),
+        HumanReadableAccount {
+            account_id: validator.clone(),
+            unstaked_balance: U128(0),
+            staked_balance: U128(ntoy(94)),
+            can_withdraw: true,
+        },
+    );
+
+    let validator_info = get_validator(&contract, validator);
+    assert_eq!(validator_info.staked, ntoy(94));
+    assert_eq!(validator_info.max_unstakable_limit, ntoy(94));
+    assert_eq!(contract.total_staked, ntoy(104));
+
+    // the rewards buffer takes 2 NEAR and is staked again
+    assert_eq!(contract.rewards_buffer, 0);
+    assert_eq!(contract.user_amount_to_stake_in_epoch, ntoy(12));
+
+    // the insurance fund covers half of the remaining 4 NEAR, the buffer is already part of
+    // the price so holders take the other 4 NEAR
+    let insurance_fund = contract.get_insurance_fund();
+    let burnt_shares = ntoy(2) * 110 / 104;
+    assert!(abs_diff_eq(
+        insurance_fund.shares.0,
+        ntoy(10) - burnt_shares,
+        100
+    ));
+    assert_eq!(
+        contract.total_stake_shares,
+        ntoy(100) + insurance_fund.shares.0
+    );
+    assert!(abs_diff_eq(insurance_fund.uncovered_losses.0, ntoy(4), 100));
+    assert!(abs_diff_eq(
+        contract.get_nearx_price().0,
+        ntoy(104) / ((ntoy(110) - burnt_shares) / 1_000_000) * (ntoy(1) / 1_000_000),
+        ntoy(1) / 1_000_000
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Insurance fund coverage should be a fraction of at most 1")]
+fn test_set_insurance_fund_coverage_above_one() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.set_insurance_fund_coverage(Fraction::new(3, 2));
+}
+
+#[test]
+fn test_insurance_fee_share() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let validator = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.add_validator(validator.clone(), 10);
+    contract.set_insurance_fee_share(Fraction::new(1, 2));
+
+    let mut validator_info = get_validator(&contract, validator.clone());
+    validator_info.staked = ntoy(100);
+    update_validator(&mut contract, validator.clone(), &validator_info);
+    contract.rewards_fee = Fraction::new(10, 100);
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    context.predecessor_account_id = contract_account();
+    context.attached_deposit = 0;
+    context.epoch_height = 100;
+    testing_env!(context.clone());
+    contract.on_get_sp_staked_balance_for_rewards(validator, None, U128(ntoy(150)));
+
+    // the 5 NEAR fee is split between the treasury and the insurance fund
+    let insurance_fund = contract.get_insurance_fund();
+    let treasury_balance = contract.get_account(treasury_account()).staked_balance.0;
+    assert!(abs_diff_eq(
+        insurance_fund.amount.0,
+        treasury_balance,
+        ntoy(1) / 1_000_000
+    ));
+    assert!(abs_diff_eq(insurance_fund.amount.0, ntoy(5) / 2, ntoy(1)));
+    assert_eq!(
+        contract.get_insurance_fund_history(U64(0), 10),
+        vec![InsuranceFundRecordResponse {
+            epoch: U64(100),
+            fee_shares: insurance_fund.shares,
+            deposited_shares: U128(0),
+            burnt_shares: U128(0),
+            covered_amount: U128(0),
+            fund_shares: insurance_fund.shares,
+        }]
+    );
+}
+
+#[test]
+fn test_cover_loss_from_insurance_fund() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+    contract.uncovered_validator_losses = ntoy(5);
+
+    context.epoch_height = 10;
+    context.attached_deposit = ntoy(10);
+    testing_env!(context.clone());
+    contract.fund_insurance_fund();
+
+    context.epoch_height = 11;
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.cover_loss_from_insurance_fund(U128(ntoy(4)));
+
+    assert_eq!(contract.get_insurance_fund().shares, U128(ntoy(6)));
+    assert_eq!(
+        contract.get_insurance_fund().uncovered_losses,
+        U128(ntoy(1))
+    );
+    assert_eq!(contract.ft_balance_of(contract_account()), U128(ntoy(6)));
+    assert_eq!(contract.total_staked, ntoy(110));
+    assert_eq!(contract.total_stake_shares, ntoy(106));
+    assert_eq!(
+        contract.get_insurance_fund_history(U64(0), 10),
+        vec![
+            InsuranceFundRecordResponse {
+                epoch: U64(10),
+                fee_shares: U128(0),
+                deposited_shares: U128(ntoy(10)),
+                burnt_shares: U128(0),
+                covered_amount: U128(0),
+                fund_shares: U128(ntoy(10)),
+            },
+            InsuranceFundRecordResponse {
+                epoch: U64(11),
+                fee_shares: U128(0),
+                deposited_shares: U128(0),
+                burnt_shares: U128(ntoy(4)),
+                covered_amount: U128(ntoy(4)),
+                fund_shares: U128(ntoy(6)),
+            },
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Insurance fund is too small to cover the amount")]
+fn test_cover_loss_from_insurance_fund_above_fund() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    context.attached_deposit = ntoy(10);
+    testing_env!(context.clone());
+    contract.fund_insurance_fund();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.cover_loss_from_insurance_fund(U128(ntoy(11)));
+}
+
+#[test]
+#[should_panic(expected = "Amount is above the validator losses left uncovered")]
+fn test_cover_loss_from_insurance_fund_above_uncovered_losses() {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    contract.uncovered_validator_losses = ntoy(2);
+
+    context.attached_deposit = ntoy(10);
+    testing_env!(context.clone());
+    contract.fund_insurance_fund();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.cover_loss_from_insurance_fund(U128(ntoy(3)));
+}
+
+fn insured_validator_loss_setup() -> (VMContext, NearxPool) {
+    let (mut context, mut contract) =
+        contract_setup(owner_account(), operator_account(), treasury_account());
+
+    let validator = AccountId::from_str("stake_public_key_1").unwrap();
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+    contract.add_validator(validator.clone(), 10);
+    contract.set_insurance_fund_coverage(Fraction::new(1, 2));
+
+    let mut validator_info = get_validator(&contract, validator.clone());
+    validator_info.staked = ntoy(100);
+    validator_info.max_unstakable_limit = ntoy(100);
+    update_validator(&mut contract, validator.clone(), &validator_info);
+    contract.total_staked = ntoy(100);
+    contract.total_stake_shares = ntoy(100);
+
+    context.predecessor_account_id = AccountId::from_str("user1").unwrap();
+    context.attached_deposit = ntoy(10);
+    testing_env!(context.clone());
+    contract.fund_insurance_fund();
+
+    // a 6 NEAR loss, half of it is covered right away
+    context.predecessor_account_id = contract_account();
+    context.attached_deposit = 0;
+    context.epoch_height = 100;
+    testing_env!(context.clone());
+    contract.on_get_sp_staked_balance_for_rewards(validator, None, U128(ntoy(94)));
+
+    context.predecessor_account_id = owner_account();
+    context.attached_deposit = 1;
+    testing_env!(context.clone());
+
+    (context, contract)
+}
+
+#[test]
+fn test_insurance_fund_covers_the_rest_of_an_automatically_covered_loss() {
+    let (_context, mut contract) = insured_validator_loss_setup();
+
+    let insurance_fund = contract.get_insurance_fund();
+    let uncovered_losses = insurance_fund.uncovered_losses.0;
+    assert!(abs_diff_eq(uncovered_losses, ntoy(3), 100));
+
+    contract.cover_loss_from_insurance_fund(U128(uncovered_losses));
+
+    let covered_insurance_fund = contract.get_insurance_fund();
+    assert!(covered_insurance_fund.uncovered_losses.0 <= 100);
+    assert!(covered_insurance_fund.shares.0 < insurance_fund.shares.0);
+}
+
+#[test]
+#[should_panic(expected = "Amount is above the validator losses left uncovered")]
+fn test_insurance_fund_cannot_cover_an_automatically_covered_loss_again() {
+    let (_context, mut contract) = insured_validator_loss_setup();
+
+    // only the 3 NEAR socialized are left, the whole loss cannot be covered again
+    contract.cover_loss_from_insurance_fund(U128(ntoy(6)));
+}